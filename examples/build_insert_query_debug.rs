@@ -45,7 +45,7 @@ fn main() {
          .set("name", &"product1")
          .returns(vec!["category.name"]);
 
-    let frag = query.debug_build(db.as_ref());
+    let frag = query.debug_build(db.as_ref()).unwrap();
 
     let expected = "
    INSERT INTO bazaar.product( name )\x20
@@ -59,24 +59,24 @@ fn main() {
          .having("count(*)", Equality::GT, &1)
          .asc("product.name")
          .desc("product.created");
-    let frag = query.debug_build(db.as_ref());
+    let frag = query.debug_build(db.as_ref()).unwrap();
 
     let expected = "
 SELECT *
-     FROM bazaar.product
-          LEFT JOIN bazaar.product_category 
-          ON product_category.product_id = product.product_id 
-          LEFT JOIN bazaar.category 
-          ON category.category_id = product_category.category_id 
-          LEFT JOIN product_photo 
-          ON product.product_id = product_photo.product_id 
-          LEFT JOIN bazaar.photo 
-          ON product_photo.photo_id = photo.photo_id 
-    WHERE product.name = 'GTX660 Ti videocard'
-      AND category.name = 'Electronic'
- GROUP BY category.name 
+     FROM \"bazaar\".\"product\"
+          LEFT JOIN \"bazaar\".\"product_category\" 
+          ON \"product_category\".\"product_id\" = \"product\".\"product_id\" 
+          LEFT JOIN \"bazaar\".\"category\" 
+          ON \"category\".\"category_id\" = \"product_category\".\"category_id\" 
+          LEFT JOIN \"product_photo\" 
+          ON \"product\".\"product_id\" = \"product_photo\".\"product_id\" 
+          LEFT JOIN \"bazaar\".\"photo\" 
+          ON \"product_photo\".\"photo_id\" = \"photo\".\"photo_id\" 
+    WHERE \"product\".\"name\" = 'GTX660 Ti videocard'
+      AND \"category\".\"name\" = 'Electronic'
+ GROUP BY \"category\".\"name\" 
    HAVING count(*) > '1'
- ORDER BY product.name ASC, product.created DESC 
+ ORDER BY \"product\".\"name\" ASC, \"product\".\"created\" DESC 
  ".to_string();
     println!("actual:   {{\n{}}} [{}]", frag.sql, frag.sql.len());
     println!("expected: {{{}}} [{}]", expected, expected.len());
@@ -83,6 +83,7 @@ impl IsTable for Product{
                 },
             ],
             is_view: false,
+            indexes: vec![],
         }
     }
 }
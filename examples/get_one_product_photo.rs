@@ -48,6 +48,7 @@ impl IsTable for Photo{
             comment: None,
             columns: vec![],
             is_view: false,
+            indexes: vec![],
         }
     }
 }
@@ -98,6 +98,7 @@ impl IsTable for Category{
             comment: None,
             columns: vec![],
             is_view: false,
+            indexes: vec![],
         }
     }
 }
@@ -63,25 +63,25 @@ fn main() {
          .having("count(*)", Equality::GT, &1)
          .asc("product.name")
          .desc("product.created");
-    let frag = query.build(db.as_ref());
+    let frag = query.build(db.as_ref()).unwrap();
 
     let expected = "
-SELECT product.product_id AS product_product_id, product.name AS product_name, category.product_id AS category_product_id,\x20
-    category.name AS category_name, photo.url
- FROM bazaar.product
-    LEFT JOIN bazaar.product_category\x20
-        ON product_category.product_id = product.product_id\x20
-    LEFT JOIN bazaar.category\x20
-        ON category.category_id = product_category.category_id\x20
-    LEFT JOIN product_photo\x20
-        ON product.product_id = product_photo.product_id\x20
-    LEFT JOIN bazaar.photo\x20
-        ON product_photo.photo_id = photo.photo_id\x20
-    WHERE product.name = $1\x20
-        AND category.name = $2\x20
-    GROUP BY category.name\x20
+SELECT \"product\".\"product_id\" AS \"product_product_id\", \"product\".\"name\" AS \"product_name\", \"category\".\"product_id\" AS \"category_product_id\",\x20
+    \"category\".\"name\" AS \"category_name\", \"photo\".\"url\"
+ FROM \"bazaar\".\"product\"
+    LEFT JOIN \"bazaar\".\"product_category\"\x20
+        ON \"product_category\".\"product_id\" = \"product\".\"product_id\"\x20
+    LEFT JOIN \"bazaar\".\"category\"\x20
+        ON \"category\".\"category_id\" = \"product_category\".\"category_id\"\x20
+    LEFT JOIN \"product_photo\"\x20
+        ON \"product\".\"product_id\" = \"product_photo\".\"product_id\"\x20
+    LEFT JOIN \"bazaar\".\"photo\"\x20
+        ON \"product_photo\".\"photo_id\" = \"photo\".\"photo_id\"\x20
+    WHERE \"product\".\"name\" = $1\x20
+        AND \"category\".\"name\" = $2\x20
+    GROUP BY \"category\".\"name\"\x20
     HAVING count(*) > $3\x20
-    ORDER BY product.name ASC, product.created DESC".to_string();
+    ORDER BY \"product\".\"name\" ASC, \"product\".\"created\" DESC".to_string();
     println!("actual:   {{{}}} [{}]", frag.sql, frag.sql.len());
     println!("expected: {{{}}} [{}]", expected, expected.len());
     assert!(frag.sql.trim() == expected.trim());
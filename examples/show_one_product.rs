@@ -54,6 +54,7 @@ impl IsTable for Product{
             comment: None,
             columns: vec![],
             is_view: false,
+            indexes: vec![],
         }
     }
 }
@@ -53,6 +53,7 @@ impl IsTable for Product{
             comment: None,
             columns: vec![],
             is_view: false,
+            indexes: vec![],
         }
     }
 }
@@ -6,8 +6,10 @@ use chrono::naive::time::NaiveTime;
 use chrono::naive::datetime::NaiveDateTime;
 use chrono::offset::utc::UTC;
 use std::fmt;
+use std::panic::{self, AssertUnwindSafe};
 use query::ColumnName;
 use table::IsTable;
+use database::DbError;
 use rustc_serialize::{Encodable, Encoder, Decodable, Decoder};
 use rustc_serialize::json::{self, ToJson, Json};
 use rustc_serialize::DecoderHelpers;
@@ -230,6 +232,20 @@ pub trait IsDao {
     /// taking into considerating the renamed columns
     fn from_dao(dao: &Dao) -> Self;
 
+    /// convert the dao, an error instead of a panic when a column is missing or of another type,
+    /// defaults to catching the panic of `from_dao`
+    fn try_from_dao(dao: &Dao) -> Result<Self, DbError>
+        where Self: Sized
+    {
+        panic::catch_unwind(AssertUnwindSafe(|| Self::from_dao(dao))).map_err(|cause| {
+            let message = match cause.downcast_ref::<&str>() {
+                Some(message) => message.to_string(),
+                None => cause.downcast_ref::<String>().cloned().unwrap_or_default(),
+            };
+            DbError::new(&format!("Unable to convert the record: {}", message))
+        })
+    }
+
     /// convert from an instance of the struct to a dao representation
     /// to be saved into the database
     fn to_dao(&self) -> Dao;
@@ -331,14 +347,16 @@ impl DaoResult {
         obj
     }
 
-    /// FIXME: should return an error when there are more than 1 to be casted
-    pub fn cast_one<T: IsTable + IsDao>(&self) -> Option<T> {
+    /// cast the only record, returns an error when there are more than 1 record
+    pub fn cast_one<T: IsTable + IsDao>(&self) -> Result<Option<T>, DbError> {
         let mut casted = self.cast::<T>();
-        if casted.len() < 1 {
-            return None;
+        if casted.len() > 1 {
+            return Err(DbError::new(&format!("Expecting only 1 record, found {}", casted.len())));
+        }
+        if casted.is_empty() {
+            return Ok(None);
         }
-        assert!(casted.len() == 1);
-        Some(casted.remove(0))
+        Ok(Some(casted.remove(0)))
     }
 }
 
@@ -353,14 +371,13 @@ pub type ParseError = String;
 
 impl Dao{
 	pub fn from_str(s: &str)->Result<Vec<Self>, ParseError>{
-		println!("parsing multiple records from json");
-		let json: Json = Json::from_str(s).unwrap();
-		println!("from str json: {:#?}", json);
+		let json: Json = try!(Json::from_str(s).map_err(|e| format!("{}", e)));
+		trace!("from str json: {:#?}", json);
 		match json{
 			Json::Array(array) => {
 				let mut dao_list = vec![];
 				for obj in array{
-					let map = Self::json_object_to_btree(obj);
+					let map = try!(Self::json_object_to_btree(obj));
 					let dao = Dao{ values: map };
 					dao_list.push(dao);
 				}
@@ -370,7 +387,7 @@ impl Dao{
 		}
 	}
 
-	fn json_object_to_btree(json: Json)->BTreeMap<String, Value>{
+	fn json_object_to_btree(json: Json)->Result<BTreeMap<String, Value>, ParseError>{
 		match json{
 			Json::Object(btree) => {
 				let mut new_map:BTreeMap<String, Value> = BTreeMap::new();
@@ -396,17 +413,17 @@ impl Dao{
 					};
 					new_map.insert(k.to_owned(), value);
 				}
-				new_map
+				Ok(new_map)
 			},
-			_ => panic!("expecting an object"),
+			_ => Err("Expecting an object".to_owned()),
 		}
 	}
 	/// reconstruct a dao from json string value
-	pub fn from_str_one(s: &str)->Result<Self, ()>{
-		let json: Json = Json::from_str(s).unwrap();
+	pub fn from_str_one(s: &str)->Result<Self, ParseError>{
+		let json: Json = try!(Json::from_str(s).map_err(|e| format!("{}", e)));
 		// then convert this map to Value
-		println!("from str: {:#?}", json);
-		let values = Self::json_object_to_btree(json);
+		trace!("from str: {:#?}", json);
+		let values = try!(Self::json_object_to_btree(json));
 		Ok(Dao{
 			values: values
 		})
@@ -451,6 +468,8 @@ impl Dao {
     pub fn set_value(&mut self, column: &str, value: Value) {
         self.values.insert(column.to_owned(), value);
     }
+    /// the value of the column,
+    /// panics when there is no such column, `try_get` reports it as an error instead
     pub fn get_value(&self, column: &str) -> Value {
         let value = self.values.get(column);
         match value {
@@ -458,7 +477,8 @@ impl Dao {
             None => panic!("No such value for {}", column),
         }
     }
-    /// take the value and remove the content
+    /// take the value and remove the content,
+    /// panics when there is no such column or the value is not of the expected type
     pub fn remove<T>(&mut self, column: &str) -> T
         where T: FromValue
     {
@@ -466,14 +486,46 @@ impl Dao {
         FromValue::from_type(value)
     }
 
-    /// take the value but not removing the content
+    /// take the value but not removing the content,
+    /// panics when there is no such column or the value is not of the expected type,
+    /// use `try_get` when the record does not come from a known query
     pub fn get<T>(&self, column: &str) -> T
         where T: FromValue
     {
         let value = self.values.get(column).unwrap();
         FromValue::from_type(value.clone())
     }
-    /// get optional value
+    /// take the value, returns an error when there is no such column
+    /// or the value is not of the expected type
+    pub fn try_get<T>(&self, column: &str) -> Result<T, DbError>
+        where T: FromValue
+    {
+        match self.values.get(column) {
+            Some(value) => {
+                FromValue::try_from_type(value.clone()).map_err(|v| {
+                    DbError::new(&format!("Unexpected value {:?} for column {}", v, column))
+                })
+            }
+            None => Err(DbError::new(&format!("No such value for {}", column))),
+        }
+    }
+
+    /// get optional value, returns an error when the value is not of the expected type
+    pub fn try_get_opt<T>(&self, column: &str) -> Result<Option<T>, DbError>
+        where T: FromValue
+    {
+        match self.values.get(column) {
+            None | Some(&Value::None(_)) => Ok(None),
+            Some(value) => {
+                FromValue::try_from_type(value.clone()).map(Some).map_err(|v| {
+                    DbError::new(&format!("Unexpected value {:?} for column {}", v, column))
+                })
+            }
+        }
+    }
+
+    /// get optional value,
+    /// panics when the value is not of the expected type, `try_get_opt` reports it as an error instead
     pub fn get_opt<T>(&self, column: &str) -> Option<T>
         where T: FromValue
     {
@@ -484,7 +536,8 @@ impl Dao {
         }
     }
 
-    /// get a reference of the type
+    /// get a reference of the type,
+    /// panics when there is no such column
     pub fn as_ref(&self, column: &str) -> &Value {
         self.values.get(column).unwrap()
     }
@@ -492,8 +545,9 @@ impl Dao {
 
     fn correct_renamed_columns(&mut self, renamed_columns: &Vec<(String, String)>) {
         for &(ref column, ref rename) in renamed_columns {
-            let value = self.get_value(rename);
-            self.set_value(&column, value);
+            if let Some(value) = self.values.get(rename).cloned() {
+                self.set_value(&column, value);
+            }
         }
     }
 
@@ -650,160 +704,173 @@ impl ToValue for Json {
 ///
 ///
 ///
-pub trait FromValue {
-    fn from_type(ty: Value) -> Self;
+/// implement either of the two methods, `try_from_type` defaults to catching the panic of `from_type`
+pub trait FromValue: Sized {
+    /// convert the value, the value is handed back when it is not of the expected type
+    fn try_from_type(ty: Value) -> Result<Self, Value> {
+        let value = ty.clone();
+        panic::catch_unwind(AssertUnwindSafe(|| Self::from_type(ty))).map_err(|_| value)
+    }
+
+    /// convert the value, panics when it is not of the expected type
+    fn from_type(ty: Value) -> Self {
+        match Self::try_from_type(ty) {
+            Ok(x) => x,
+            Err(ty) => panic!("unexpected type {:?}", ty),
+        }
+    }
 }
 
 impl FromValue for bool {
-    fn from_type(ty: Value) -> Self {
+    fn try_from_type(ty: Value) -> Result<Self, Value> {
         match ty {
-            Value::Bool(x) => x,
-            _ => panic!("error!"),
+            Value::Bool(x) => Ok(x),
+            _ => Err(ty),
         }
     }
 }
 
 impl FromValue for i8 {
-    fn from_type(ty: Value) -> Self {
+    fn try_from_type(ty: Value) -> Result<Self, Value> {
         match ty {
-            Value::I8(x) => x,
-            _ => panic!("error!"),
+            Value::I8(x) => Ok(x),
+            _ => Err(ty),
         }
     }
 }
 impl FromValue for i16 {
-    fn from_type(ty: Value) -> Self {
+    fn try_from_type(ty: Value) -> Result<Self, Value> {
         match ty {
-            Value::I16(x) => x,
-            _ => panic!("error!"),
+            Value::I16(x) => Ok(x),
+            _ => Err(ty),
         }
     }
 }
 impl FromValue for i32 {
-    fn from_type(ty: Value) -> Self {
+    fn try_from_type(ty: Value) -> Result<Self, Value> {
         match ty {
-            Value::I32(x) => x,
-            _ => panic!("error!"),
+            Value::I32(x) => Ok(x),
+            _ => Err(ty),
         }
     }
 }
 impl FromValue for i64 {
-    fn from_type(ty: Value) -> Self {
+    fn try_from_type(ty: Value) -> Result<Self, Value> {
         match ty {
-            Value::I64(x) => x,
-            _ => panic!("error!"),
+            Value::I64(x) => Ok(x),
+            _ => Err(ty),
         }
     }
 }
 impl FromValue for u8 {
-    fn from_type(ty: Value) -> Self {
+    fn try_from_type(ty: Value) -> Result<Self, Value> {
         match ty {
-            Value::U8(x) => x,
-            _ => panic!("error!"),
+            Value::U8(x) => Ok(x),
+            _ => Err(ty),
         }
     }
 }
 impl FromValue for u16 {
-    fn from_type(ty: Value) -> Self {
+    fn try_from_type(ty: Value) -> Result<Self, Value> {
         match ty {
-            Value::U16(x) => x,
-            _ => panic!("error!"),
+            Value::U16(x) => Ok(x),
+            _ => Err(ty),
         }
     }
 }
 impl FromValue for u32 {
-    fn from_type(ty: Value) -> Self {
+    fn try_from_type(ty: Value) -> Result<Self, Value> {
         match ty {
-            Value::U32(x) => x,
-            _ => panic!("error!"),
+            Value::U32(x) => Ok(x),
+            _ => Err(ty),
         }
     }
 }
 impl FromValue for u64 {
-    fn from_type(ty: Value) -> Self {
+    fn try_from_type(ty: Value) -> Result<Self, Value> {
         match ty {
-            Value::U64(x) => x,
-            _ => panic!("error!"),
+            Value::U64(x) => Ok(x),
+            _ => Err(ty),
         }
     }
 }
 
 impl FromValue for f32 {
-    fn from_type(ty: Value) -> Self {
+    fn try_from_type(ty: Value) -> Result<Self, Value> {
         match ty {
-            Value::F32(x) => x,
-            _ => panic!("error!"),
+            Value::F32(x) => Ok(x),
+            _ => Err(ty),
         }
     }
 }
 impl FromValue for f64 {
-    fn from_type(ty: Value) -> Self {
+    fn try_from_type(ty: Value) -> Result<Self, Value> {
         match ty {
-            Value::F64(x) => x,
-            _ => panic!("error! {:?}", ty),
+            Value::F64(x) => Ok(x),
+            _ => Err(ty),
         }
     }
 }
 
 impl FromValue for String {
-    fn from_type(ty: Value) -> Self {
+    fn try_from_type(ty: Value) -> Result<Self, Value> {
         match ty {
-            Value::String(x) => x,
-            _ => panic!("error! {:?}", ty),
+            Value::String(x) => Ok(x),
+            _ => Err(ty),
         }
     }
 }
 
 impl FromValue for Uuid {
-    fn from_type(ty: Value) -> Self {
+    fn try_from_type(ty: Value) -> Result<Self, Value> {
         match ty {
-            Value::Uuid(x) => x,
-            _ => panic!("error!"),
+            Value::Uuid(x) => Ok(x),
+            _ => Err(ty),
         }
     }
 }
 
 impl FromValue for DateTime<UTC> {
-    fn from_type(ty: Value) -> Self {
+    fn try_from_type(ty: Value) -> Result<Self, Value> {
         match ty {
-            Value::DateTime(x) => x,
-            _ => panic!("error!"),
+            Value::DateTime(x) => Ok(x),
+            _ => Err(ty),
         }
     }
 }
 
 impl FromValue for NaiveTime {
-    fn from_type(ty: Value) -> Self {
+    fn try_from_type(ty: Value) -> Result<Self, Value> {
         match ty {
-            Value::NaiveTime(x) => x,
-            _ => panic!("error!"),
+            Value::NaiveTime(x) => Ok(x),
+            _ => Err(ty),
         }
     }
 }
 
 impl FromValue for NaiveDate {
-    fn from_type(ty: Value) -> Self {
+    fn try_from_type(ty: Value) -> Result<Self, Value> {
         match ty {
-            Value::NaiveDate(x) => x,
-            _ => panic!("error!"),
+            Value::NaiveDate(x) => Ok(x),
+            _ => Err(ty),
         }
     }
 }
 
 impl FromValue for NaiveDateTime {
-    fn from_type(ty: Value) -> Self {
+    fn try_from_type(ty: Value) -> Result<Self, Value> {
         match ty {
-            Value::NaiveDateTime(x) => x,
-            _ => panic!("error!"),
+            Value::NaiveDateTime(x) => Ok(x),
+            _ => Err(ty),
         }
     }
 }
 
 impl FromValue for Json {
-    fn from_type(ty: Value) -> Self {
+    fn try_from_type(ty: Value) -> Result<Self, Value> {
         match ty {
-            Value::Json(x) => x,
-            _ => panic!("error!"),
+            Value::Json(x) => Ok(x),
+            _ => Err(ty),
         }
     }
 }
@@ -845,3 +912,27 @@ fn test_json() {
     println!("expected: {}", expected);
     println!("actual: {}", actual);
 }
+
+#[test]
+fn test_from_str() {
+    let daos = Dao::from_str(r#"[{"name": "lee", "age": 20}]"#).unwrap();
+    assert_eq!(daos[0].get::<String>("name"), "lee");
+    assert!(Dao::from_str("[{").is_err());
+    assert!(Dao::from_str("[1]").is_err());
+    assert!(Dao::from_str_one("not json").is_err());
+}
+
+#[test]
+fn test_default_try_from_type() {
+    struct Even(i32);
+    impl FromValue for Even {
+        fn from_type(ty: Value) -> Self {
+            match ty {
+                Value::I32(x) if x % 2 == 0 => Even(x),
+                _ => panic!("expecting an even number"),
+            }
+        }
+    }
+    assert_eq!(Even::try_from_type(Value::I32(2)).ok().map(|e| e.0), Some(2));
+    assert_eq!(Even::try_from_type(Value::I32(3)).err(), Some(Value::I32(3)));
+}
@@ -1,13 +1,20 @@
 use query::Query;
-use table::Table;
+use table::{Table, Column, Index};
 use dao::{Dao, DaoResult, Value};
 use writer::SqlFrag;
 use query::{Connector, Equality, Operand, Field};
+use query::{ColumnName, TableName};
 use query::{Direction, Modifier, NullsWhere, JoinType};
 use query::{Filter, Condition};
+use query::{Function, DateUnit};
+use query::SetOperation;
+use query::{Order, Window, FrameUnit, FrameBound};
+use query::OnConflict;
 use query::SqlType;
+use query::Error as QueryError;
 use std::error::Error;
 use std::fmt;
+use std::cmp;
 use r2d2;
 use postgres::error::Error as PgError;
 use postgres::error::ConnectError as PgConnectError;
@@ -18,6 +25,8 @@ use regex::Error as RegexError;
 use rusqlite::SqliteError;
 use platform::PlatformError;
 use dao::Type;
+use logger::QueryLog;
+use dialect::Dialect;
 
 /// SqlOption, contains the info about the features and quirks of underlying database
 #[derive(PartialEq)]
@@ -37,6 +46,15 @@ pub enum SqlOption {
     /// wheter the returned rows in a query included Meta columns for easy extraction of records
     /// (postgres returns this), sqlite does not return meta columns, so you have to extract it by index yourself.
     ReturnMetaColumns,
+    /// supports server side cursors for fetching the records in batches (postgresql)
+    SupportsCursor,
+    /// the schema changes are rolled back with the transaction (postgresql, sqlite)
+    SupportsTransactionalDDL,
+    /// supports INTERSECT and EXCEPT besides UNION (postgresql, sqlite, mysql 8.0.31)
+    SupportsIntersectExcept,
+    /// the selects combined with UNION, INTERSECT, EXCEPT can be wrapped in parentheses,
+    /// so each can have its own ORDER BY and LIMIT (postgresql, mysql)
+    SupportsParenthesizedSetOperand,
 }
 
 /// specifies if the sql will be build in debug mode for debugging purposed
@@ -49,17 +67,97 @@ pub enum BuildMode{
 	Standard,
 }
 
+/// portable classification of the errors reported by the database
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(PartialEq)]
+pub enum ErrorKind {
+    UniqueViolation,
+    ForeignKeyViolation,
+    NotNullViolation,
+    CheckViolation,
+    /// the transaction could not be serialized, it can be retried
+    SerializationFailure,
+    Deadlock,
+    ConnectionLost,
+    SyntaxError,
+    /// statement timeout, lock timeout or a busy database
+    Timeout,
+    Other,
+}
+
+impl ErrorKind {
+    /// classify the standard SQLSTATE code
+    pub fn from_sqlstate(sqlstate: &str) -> Self {
+        match sqlstate {
+            "23505" => ErrorKind::UniqueViolation,
+            "23503" => ErrorKind::ForeignKeyViolation,
+            "23502" => ErrorKind::NotNullViolation,
+            "23514" => ErrorKind::CheckViolation,
+            "40001" => ErrorKind::SerializationFailure,
+            "40P01" => ErrorKind::Deadlock,
+            "57014" | "55P03" => ErrorKind::Timeout,
+            "57P01" | "57P02" => ErrorKind::ConnectionLost,
+            "42601" => ErrorKind::SyntaxError,
+            _ if sqlstate.starts_with("08") => ErrorKind::ConnectionLost,
+            _ => ErrorKind::Other,
+        }
+    }
+}
+
+/// the details of the error, filled in when the driver provides them
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(PartialEq)]
+#[derive(Default)]
+pub struct ErrorDetail {
+    pub sqlstate: Option<String>,
+    /// the error code specific to the database
+    pub native_code: Option<i32>,
+    pub constraint: Option<String>,
+    pub table: Option<String>,
+    pub column: Option<String>,
+}
+
 #[derive(Debug)]
 pub enum DbError {
     Error(String),
     PoolError(r2d2::InitializationError),
+    /// no connection could be taken out of the pool
+    ConnectionError(r2d2::GetTimeout),
     PlatformError(PlatformError),
+    QueryError(QueryError),
 }
 
 impl DbError {
     pub fn new(description: &str) -> Self {
         DbError::Error(description.to_owned())
     }
+
+    /// the portable kind of this error, errors not from the database are `Other`
+    pub fn kind(&self) -> ErrorKind {
+        match *self {
+            DbError::PlatformError(ref err) => err.kind(),
+            DbError::PoolError(_) | DbError::ConnectionError(_) => ErrorKind::ConnectionLost,
+            _ => ErrorKind::Other,
+        }
+    }
+
+    /// the SQLSTATE, native code, constraint, table and column of the error
+    pub fn detail(&self) -> ErrorDetail {
+        match *self {
+            DbError::PlatformError(ref err) => err.detail(),
+            _ => ErrorDetail::default(),
+        }
+    }
+
+    /// whether the failed transaction can be retried as is
+    pub fn is_transient(&self) -> bool {
+        match self.kind() {
+            ErrorKind::SerializationFailure | ErrorKind::Deadlock => true,
+            _ => false,
+        }
+    }
 }
 
 impl Error for DbError {
@@ -67,7 +165,9 @@ impl Error for DbError {
         match *self {
             DbError::Error(ref description) => description,
             DbError::PoolError(ref err) => err.description(),
+            DbError::ConnectionError(ref err) => err.description(),
             DbError::PlatformError(ref err) => err.description(),
+            DbError::QueryError(ref err) => err.description(),
         }
     }
 
@@ -75,7 +175,9 @@ impl Error for DbError {
         match *self {
             DbError::Error(_) => None,
             DbError::PoolError(ref err) => Some(err),
+            DbError::ConnectionError(ref err) => Some(err),
             DbError::PlatformError(ref err) => Some(err),
+            DbError::QueryError(ref err) => Some(err),
         }
     }
 }
@@ -84,7 +186,9 @@ impl fmt::Display for DbError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match *self {
             DbError::PoolError(ref err) => write!(f, "Pool error: {}", err),
-            DbError::PlatformError(ref err) => write!(f, "PostgreSQL error: {}", err),
+            DbError::ConnectionError(ref err) => write!(f, "Unable to connect due to {}", err),
+            DbError::PlatformError(ref err) => write!(f, "{}", err),
+            DbError::QueryError(ref err) => write!(f, "Query error: {}", err),
             DbError::Error(_) => write!(f, "{}", self.description()),
         }
     }
@@ -96,12 +200,24 @@ impl From<r2d2::InitializationError> for DbError {
     }
 }
 
+impl From<r2d2::GetTimeout> for DbError {
+    fn from(err: r2d2::GetTimeout) -> Self {
+        DbError::ConnectionError(err)
+    }
+}
+
 impl From<PlatformError> for DbError {
     fn from(err: PlatformError) -> Self {
         DbError::PlatformError(err)
     }
 }
 
+impl From<QueryError> for DbError {
+    fn from(err: QueryError) -> Self {
+        DbError::QueryError(err)
+    }
+}
+
 impl From<RegexError> for DbError {
     fn from(err: RegexError) -> Self {
         DbError::new(err.description())
@@ -150,15 +266,34 @@ pub trait Database {
     fn version(&self) -> Result<String, DbError>;
 
     /// begin database transaction
-    fn begin(&self);
+    fn begin(&self) -> Result<(), DbError>;
 
     /// commit database transaction
-    fn commit(&self);
+    fn commit(&self) -> Result<(), DbError>;
 
     /// rollback data changes executed prior to calling the begin method
-    fn rollback(&self);
+    fn rollback(&self) -> Result<(), DbError>;
 
-    /// determine if this transaction has been committed or rolledback
+    /// mark a savepoint inside the current transaction,
+    /// used for nesting transactions
+    fn savepoint(&self, name: &str) -> Result<(), DbError> {
+        try!(self.execute_sql(&format!("SAVEPOINT {}", name), &[]));
+        Ok(())
+    }
+
+    /// release the savepoint, keeping the changes made after it
+    fn release_savepoint(&self, name: &str) -> Result<(), DbError> {
+        try!(self.execute_sql(&format!("RELEASE SAVEPOINT {}", name), &[]));
+        Ok(())
+    }
+
+    /// undo the changes made after the savepoint, the outer transaction remains open
+    fn rollback_to_savepoint(&self, name: &str) -> Result<(), DbError> {
+        try!(self.execute_sql(&format!("ROLLBACK TO SAVEPOINT {}", name), &[]));
+        Ok(())
+    }
+
+    /// determine if there is an open transaction on this connection
     fn is_transacted(&self) -> bool;
 
     /// determine if the database connection closed
@@ -173,8 +308,15 @@ pub trait Database {
     /// determine if the database connection is still valid
     fn is_valid(&self) -> bool;
 
-    /// reset the database connection
-    fn reset(&self);
+    /// reset the database connection,
+    /// any open transaction is rolled back
+    fn reset(&self) -> Result<(), DbError>;
+
+    /// how the statements executed on this connection are logged
+    fn query_log(&self) -> &QueryLog;
+
+    /// the sql flavor of this database
+    fn dialect(&self) -> &Dialect;
 
     /// select
     /// returns an array to the qualified records
@@ -186,7 +328,7 @@ pub trait Database {
     /// insert an object, returns the inserted Dao value
     /// including the value generated via the defaults
     fn insert(&self, query: &Query) -> Result<Dao, DbError> {
-        let sql_frag = self.build_insert(query, BuildMode::Standard);
+        let sql_frag = try!(self.build_insert(query, BuildMode::Standard));
         match self.execute_sql_with_one_return(&sql_frag.sql, &sql_frag.params) {
             Ok(Some(result)) => Ok(result),
             Ok(None) => Err(DbError::new("No result from insert")),
@@ -194,18 +336,103 @@ pub trait Database {
         }
     }
 
+    /// insert multiple records in 1 go, the values of the query are rows of the enumerated columns.
+    /// The rows are split into several statements to stay under the number of parameters
+    /// that the database can bind in a single statement.
+    /// returns the number of inserted records
+    fn insert_bulk(&self, query: &Query) -> Result<usize, DbError> {
+        let n_columns = query.enumerated_fields.len();
+        if n_columns == 0 {
+            return Err(DbError::new("No columns specified for inserting records"));
+        }
+        let rows_per_statement = cmp::max(1, self.max_parameters() / n_columns);
+        let mut chunk_query = query.clone();
+        chunk_query.enumerated_returns = vec![];
+        let mut inserted = 0;
+        for chunk in query.values.chunks(rows_per_statement * n_columns) {
+            chunk_query.values = chunk.to_vec();
+            let sql_frag = try!(self.build_insert(&chunk_query, BuildMode::Standard));
+            inserted += try!(self.execute_sql(&sql_frag.sql, &sql_frag.params));
+        }
+        Ok(inserted)
+    }
+
+    /// the maximum number of parameters that can be bound in a single statement
+    fn max_parameters(&self) -> usize {
+        self.dialect().max_parameters()
+    }
+
     /// update
-    /// returns the updated Dao
-    fn update(&self, query: &Query) -> Dao;
+    /// returns the updated Dao,
+    /// when the database doesn't support the returning clause, the record is selected again
+    /// using the same filters of the update query
+    fn update(&self, query: &Query) -> Result<Dao, DbError> {
+        let sql_frag = try!(self.build_update(query, BuildMode::Standard));
+        if self.sql_options().contains(&SqlOption::SupportsReturningClause) {
+            match try!(self.execute_sql_with_one_return(&sql_frag.sql, &sql_frag.params)) {
+                Some(result) => Ok(result),
+                None => Err(DbError::new("No record updated")),
+            }
+        } else {
+            // the record is selected again with the same filters,
+            // which would no longer match when they are on the updated columns
+            let updated = query.get_enumerated_columns();
+            if query.filters.iter().any(|f| updated.iter().any(|c| f.uses_column(&c.column))) {
+                return Err(DbError::new("Unable to select the updated record, \
+                                         the filters are on the updated columns"));
+            }
+            if try!(self.execute_sql(&sql_frag.sql, &sql_frag.params)) == 0 {
+                return Err(DbError::new("No record updated"));
+            }
+            let mut select = Query::select();
+            select.from = query.from.clone();
+            select.filters = query.filters.clone();
+            select.enumerated_fields = query.enumerated_returns.clone();
+            if select.enumerated_fields.is_empty() {
+                select.all();
+            }
+            match try!(self.execute_with_one_return(&select)) {
+                Some(result) => Ok(result),
+                None => Err(DbError::new("Unable to select the updated record")),
+            }
+        }
+    }
 
     /// delete records
     /// returns the number of deleted records
-    fn delete(&self, query: &Query) -> Result<usize, String>;
+    fn delete(&self, query: &Query) -> Result<usize, DbError> {
+        self.execute(query)
+    }
+
+    /// check that the features used in the query are supported by this database
+    fn check_query_support(&self, query: &Query) -> Result<(), DbError> {
+        let sql_options = self.sql_options();
+        if !query.declared_query.is_empty() && !sql_options.contains(&SqlOption::SupportsCTE) {
+            return Err(DbError::new("This database does not support CTE (WITH clause) declared queries"));
+        }
+        for combined in &query.combined_queries {
+            match combined.operation {
+                SetOperation::INTERSECT | SetOperation::EXCEPT
+                    if !sql_options.contains(&SqlOption::SupportsIntersectExcept) => {
+                    return Err(DbError::new("This database does not support INTERSECT and EXCEPT"));
+                }
+                _ => (),
+            }
+            if !sql_options.contains(&SqlOption::SupportsParenthesizedSetOperand) &&
+               (!combined.query.order_by.is_empty() || combined.query.range.is_some()) {
+                return Err(DbError::new("This database does not support ORDER BY or LIMIT \
+                                         on a query combined with UNION, INTERSECT or EXCEPT"));
+            }
+            try!(self.check_query_support(&combined.query));
+        }
+        Ok(())
+    }
 
     /// execute query with return dao,
     /// use the enumerated column for data extraction when db doesn't support returning the records column names
     fn execute_with_return(&self, query: &Query) -> Result<DaoResult, DbError> {
-        let sql_frag = &self.build_query(query, BuildMode::Standard);
+        try!(self.check_query_support(query));
+        let sql_frag = &try!(self.build_query(query, BuildMode::Standard));
         let result = try!(self.execute_sql_with_return(&sql_frag.sql, &sql_frag.params));
         let dao_result = DaoResult {
             dao: result,
@@ -219,13 +446,15 @@ pub trait Database {
 
     /// execute query with 1 return dao
     fn execute_with_one_return(&self, query: &Query) -> Result<Option<Dao>, DbError> {
-        let sql_frag = &self.build_query(query, BuildMode::Standard);
+        try!(self.check_query_support(query));
+        let sql_frag = &try!(self.build_query(query, BuildMode::Standard));
         self.execute_sql_with_one_return(&sql_frag.sql, &sql_frag.params)
     }
 
     /// execute query with no return dao
     fn execute(&self, query: &Query) -> Result<usize, DbError> {
-        let sql_frag = &self.build_query(query, BuildMode::Standard);
+        try!(self.check_query_support(query));
+        let sql_frag = &try!(self.build_query(query, BuildMode::Standard));
         self.execute_sql(&sql_frag.sql, &sql_frag.params)
     }
 
@@ -251,7 +480,7 @@ pub trait Database {
     /// use by select to build the select query
     /// build all types of query
     /// TODO: need to supply the number of parameters where to start the numbering of the number parameters
-    fn build_query(&self, query: &Query, build_mode: BuildMode) -> SqlFrag {
+    fn build_query(&self, query: &Query, build_mode: BuildMode) -> Result<SqlFrag, QueryError> {
         match query.sql_type {
             SqlType::SELECT => self.build_select(query, build_mode),
             SqlType::INSERT => self.build_insert(query, build_mode),
@@ -260,42 +489,134 @@ pub trait Database {
         }
     }
 
+    /// the character used for quoting identifiers, `"` is the SQL standard
+    fn identifier_quote(&self) -> char {
+        self.dialect().identifier_quote()
+    }
+
+    /// reject identifiers that can not be safely quoted,
+    /// i.e: empty, containing the quote character or control characters
+    fn validate_identifier(&self, name: &str) -> Result<(), QueryError> {
+        let quote = self.identifier_quote();
+        if name.is_empty() || name.chars().any(|c| c == quote || c.is_control()) {
+            return Err(QueryError::InvalidIdentifier(format!("{:?}", name)));
+        }
+        Ok(())
+    }
+
+    /// quote the table, column, alias name so mixed case names,
+    /// reserved words and names with spaces are taken as is
+    fn quote_identifier(&self, name: &str) -> Result<String, QueryError> {
+        try!(self.validate_identifier(name));
+        let quote = self.identifier_quote();
+        Ok(format!("{}{}{}", quote, name, quote))
+    }
+
+    /// quote the column, `*` is left as is
+    fn quote_column(&self, column: &str) -> Result<String, QueryError> {
+        if column == "*" {
+            Ok(column.to_owned())
+        } else {
+            self.quote_identifier(column)
+        }
+    }
+
+    /// quote the column, qualified with the table when specified
+    fn quote_column_name(&self, column_name: &ColumnName) -> Result<String, QueryError> {
+        let column = try!(self.quote_column(&column_name.column));
+        match column_name.table {
+            Some(ref table) => Ok(format!("{}.{}", try!(self.quote_identifier(table)), column)),
+            None => Ok(column),
+        }
+    }
+
+    /// quote the table, qualified with the schema when the database uses schema
+    fn quote_table_name(&self, table_name: &TableName) -> Result<String, QueryError> {
+        let table = try!(self.quote_identifier(&table_name.name));
+        match table_name.schema {
+            Some(ref schema) if self.sql_options().contains(&SqlOption::UsesSchema) => {
+                Ok(format!("{}.{}", try!(self.quote_identifier(schema)), table))
+            }
+            _ => Ok(table),
+        }
+    }
+
+    /// the database data type of the rust type, an error when the platform has none
+    fn data_type_name(&self, data_type: &Type) -> Result<String, QueryError> {
+        match self.dialect().type_name(data_type) {
+            Some(name) => Ok(name),
+            None => {
+                Err(QueryError::SqlError(format!("No {} data type for {:?}", self.dialect().name(), data_type)))
+            }
+        }
+    }
+
+    /// the column definition used in create and alter table, i.e: `"name" character varying NOT NULL`
+    fn build_column_definition(&self, w: &mut SqlFrag, column: &Column) -> Result<(), QueryError> {
+        let data_type = try!(self.data_type_name(&column.data_type));
+        w.append(&try!(self.quote_identifier(&column.name)));
+        w.append(" ");
+        w.append(&data_type);
+        if column.not_null {
+            w.append(" NOT NULL");
+        }
+        if let Some(ref default) = column.default {
+            w.append(" DEFAULT ");
+            try!(self.build_column_default(w, default));
+        }
+        if column.is_unique && !column.is_primary {
+            w.append(" UNIQUE");
+        }
+        Ok(())
+    }
+
+    /// the default of the column, `Operand::Sql` is the expression as introspected,
+    /// i.e: `now()`, `nextval('product_seq'::regclass)`, the values are written as literals
+    fn build_column_default(&self, w: &mut SqlFrag, default: &Operand) -> Result<(), QueryError> {
+        match *default {
+            Operand::Sql(ref expr) => {
+                w.append(expr);
+            }
+            Operand::Value(Value::String(ref text)) => {
+                w.append(&format!("'{}'", text.replace("'", "''")));
+            }
+            _ => {
+                let mut literal = SqlFrag::new(self.sql_options(), BuildMode::Debug);
+                try!(self.build_operand(&mut literal, &Query::select(), default));
+                w.append(literal.sql.trim());
+            }
+        }
+        Ok(())
+    }
+
     /// build operand, i.e: columns, query, function, values
-    fn build_operand(&self, w: &mut SqlFrag, parent_query: &Query, operand: &Operand) {
+    fn build_operand(&self, w: &mut SqlFrag, parent_query: &Query, operand: &Operand) -> Result<(), QueryError> {
         match *operand {
             Operand::ColumnName(ref column_name) => {
-                if parent_query.joins.is_empty() {
-                    w.append(&column_name.column);
+                let is_outer = match column_name.table {
+                    Some(ref table) => !parent_query.is_local_table(table),
+                    None => false,
+                };
+                if parent_query.joins.is_empty() && !is_outer {
+                    w.append(&try!(self.quote_column(&column_name.column)));
                 } else {
-                    w.append(&column_name.complete_name());
+                    w.append(&try!(self.quote_column_name(column_name)));
                 }
             }
             Operand::TableName(ref table_name) => {
-                if self.sql_options().contains(&SqlOption::UsesSchema) {
-                    w.append(&table_name.complete_name());
-                } else {
-                    w.append(&table_name.name);
-                }
+                w.append(&try!(self.quote_table_name(table_name)));
             }
             Operand::Function(ref function) => {
+                try!(self.build_function(w, parent_query, function));
+            }
+            Operand::Window(ref window) => {
+                try!(self.build_window(w, parent_query, window));
+            }
+            Operand::Query(ref q) => {
                 w.append("(");
-                let mut do_comma = false;
-                for param in &function.params {
-                    if do_comma {
-                        w.commasp();
-                    } else {
-                        do_comma = true;
-                    }
-                    self.build_operand(w, parent_query, param);
-                }
+                try!(self.build_subquery(w, q));
                 w.append(")");
             }
-            Operand::Query(ref _q) => {
-                //panic!("TODO: causes error Attributes 'readnone and readonly' are incompatible! \
-                //        LLVM ERROR: Broken function found, compilation aborted!")
-                let sql_frag = &self.build_query(&_q, w.build_mode.clone());
-                w.append(&sql_frag.sql);
-            }
             Operand::Value(ref value) => {
                 w.parameter(value.clone());
             }
@@ -304,63 +625,232 @@ pub trait Database {
                 if !operands.is_empty() {
                     w.append("(");
                     for op in operands {
-                        println!("op: {:?}", op);
                         if do_comma {
                             w.commasp();
                         } else {
                             do_comma = true;
                         }
-                        self.build_operand(w, parent_query, op);
+                        try!(self.build_operand(w, parent_query, op));
                     }
                     w.append(")");
                 }
             }
+            Operand::Sql(ref sql) => {
+                w.append(sql);
+            }
         }
+        Ok(())
     }
 
-    fn build_condition(&self, w: &mut SqlFrag, parent_query: &Query, cond: &Condition) {
-        self.build_operand(w, parent_query, &cond.left);
+    fn build_order(&self, w: &mut SqlFrag, parent_query: &Query, order: &Order) -> Result<(), QueryError> {
+        try!(self.build_operand(w, parent_query, &order.operand));
+        match order.direction {
+            Some(Direction::ASC) => w.append(" ASC"),
+            Some(Direction::DESC) => w.append(" DESC"),
+            None => w.append(""),
+        };
+        match order.nulls_where {
+            Some(NullsWhere::FIRST) => w.append(" NULLS FIRST"),
+            Some(NullsWhere::LAST) => w.append(" NULLS LAST"),
+            None => w.append(""),
+        };
+        Ok(())
+    }
+
+    /// build the window function, i.e:
+    /// `ROW_NUMBER() OVER (PARTITION BY category ORDER BY price ASC)`
+    fn build_window(&self, w: &mut SqlFrag, parent_query: &Query, window: &Window) -> Result<(), QueryError> {
+        try!(self.build_function(w, parent_query, &window.function));
+        w.append(" OVER (");
+        let mut do_space = false;
+        if !window.partition_by.is_empty() {
+            w.append("PARTITION BY ");
+            let mut do_comma = false;
+            for operand in &window.partition_by {
+                if do_comma {
+                    w.commasp();
+                } else {
+                    do_comma = true;
+                }
+                try!(self.build_operand(w, parent_query, operand));
+            }
+            do_space = true;
+        }
+        if !window.order_by.is_empty() {
+            if do_space {
+                w.append(" ");
+            }
+            w.append("ORDER BY ");
+            let mut do_comma = false;
+            for order in &window.order_by {
+                if do_comma {
+                    w.commasp();
+                } else {
+                    do_comma = true;
+                }
+                try!(self.build_order(w, parent_query, order));
+            }
+            do_space = true;
+        }
+        if let Some(ref frame) = window.frame {
+            if do_space {
+                w.append(" ");
+            }
+            match frame.unit {
+                FrameUnit::ROWS => w.append("ROWS "),
+                FrameUnit::RANGE => w.append("RANGE "),
+            };
+            match frame.end {
+                Some(ref end) => {
+                    w.append("BETWEEN ");
+                    self.build_frame_bound(w, &frame.start);
+                    w.append(" AND ");
+                    self.build_frame_bound(w, end);
+                }
+                None => self.build_frame_bound(w, &frame.start),
+            }
+        }
+        w.append(")");
+        Ok(())
+    }
+
+    fn build_frame_bound(&self, w: &mut SqlFrag, bound: &FrameBound) {
+        match *bound {
+            FrameBound::UNBOUNDED_PRECEDING => w.append("UNBOUNDED PRECEDING"),
+            FrameBound::PRECEDING(n) => w.append(&format!("{} PRECEDING", n)),
+            FrameBound::CURRENT_ROW => w.append("CURRENT ROW"),
+            FrameBound::FOLLOWING(n) => w.append(&format!("{} FOLLOWING", n)),
+            FrameBound::UNBOUNDED_FOLLOWING => w.append("UNBOUNDED FOLLOWING"),
+        };
+    }
+
+    /// build a function call, translating the portable functions
+    /// NOW, CONCAT and DATE_TRUNC to this database's dialect
+    fn build_function(&self, w: &mut SqlFrag, parent_query: &Query, function: &Function) -> Result<(), QueryError> {
+        let params = &function.params;
+        match &*function.function.to_uppercase() {
+            "NOW" if params.is_empty() => {
+                self.build_now(w);
+            }
+            "CONCAT" => {
+                try!(self.build_concat(w, parent_query, params));
+            }
+            "DATE_TRUNC" if params.len() == 2 => {
+                let unit = match params[0] {
+                    Operand::Value(Value::String(ref unit)) => DateUnit::from_str(unit),
+                    _ => None,
+                };
+                match unit {
+                    Some(unit) => try!(self.build_date_trunc(w, parent_query, &unit, &params[1])),
+                    None => try!(self.build_function_call(w, parent_query, &function.function, params)),
+                }
+            }
+            _ => {
+                try!(self.build_function_call(w, parent_query, &function.function, params));
+            }
+        }
+        Ok(())
+    }
+
+    /// write the function as is, i.e: `name(param1, param2)`
+    fn build_function_call(&self, w: &mut SqlFrag, parent_query: &Query, name: &str, params: &[Operand]) -> Result<(), QueryError> {
+        w.append(name);
+        w.append("(");
+        let mut do_comma = false;
+        for param in params {
+            if do_comma {
+                w.commasp();
+            } else {
+                do_comma = true;
+            }
+            try!(self.build_operand(w, parent_query, param));
+        }
+        w.append(")");
+        Ok(())
+    }
+
+    /// the current timestamp
+    fn build_now(&self, w: &mut SqlFrag) {
+        self.dialect().build_now(w)
+    }
+
+    /// string concatenation
+    fn build_concat(&self, w: &mut SqlFrag, parent_query: &Query, params: &[Operand]) -> Result<(), QueryError> {
+        self.dialect().build_concat(self, w, parent_query, params)
+    }
+
+    /// truncate the timestamp to the unit, i.e: `date_trunc('month', created)`
+    fn build_date_trunc(&self, w: &mut SqlFrag, parent_query: &Query, unit: &DateUnit, operand: &Operand) -> Result<(), QueryError> {
+        self.dialect().build_date_trunc(self, w, parent_query, unit, operand)
+    }
+
+    /// build a query that is nested inside the query being written,
+    /// the parameters of the nested query are carried over to the outer query
+    fn build_subquery(&self, w: &mut SqlFrag, query: &Query) -> Result<(), QueryError> {
+        let mut query = query.clone();
+        query.finalize();
+        let sql_frag = try!(self.build_query(&query, w.build_mode.clone()));
+        w.merge(sql_frag)
+    }
+
+    fn build_condition(&self, w: &mut SqlFrag, parent_query: &Query, cond: &Condition) -> Result<(), QueryError> {
+        match cond.equality {
+            Equality::EXISTS => {
+                w.append("EXISTS ");
+                try!(self.build_operand(w, parent_query, &cond.right));
+                w.append(" ");
+                return Ok(());
+            }
+            Equality::NOT_EXISTS => {
+                w.append("NOT EXISTS ");
+                try!(self.build_operand(w, parent_query, &cond.right));
+                w.append(" ");
+                return Ok(());
+            }
+            _ => (),
+        }
+        try!(self.build_operand(w, parent_query, &cond.left));
         w.append(" ");
         match cond.equality {
             Equality::EQ => {
                 w.append("= ");
-                self.build_operand(w, parent_query, &cond.right);
+                try!(self.build_operand(w, parent_query, &cond.right));
             }
             Equality::NEQ => {
                 w.append("!= ");
-                self.build_operand(w, parent_query, &cond.right);
+                try!(self.build_operand(w, parent_query, &cond.right));
             }
             Equality::LT => {
                 w.append("< ");
-                self.build_operand(w, parent_query, &cond.right);
+                try!(self.build_operand(w, parent_query, &cond.right));
             }
             Equality::LTE => {
                 w.append("<= ");
-                self.build_operand(w, parent_query, &cond.right);
+                try!(self.build_operand(w, parent_query, &cond.right));
             }
             Equality::GT => {
                 w.append("> ");
-                self.build_operand(w, parent_query, &cond.right);
+                try!(self.build_operand(w, parent_query, &cond.right));
             }
             Equality::GTE => {
                 w.append(">= ");
-                self.build_operand(w, parent_query, &cond.right);
+                try!(self.build_operand(w, parent_query, &cond.right));
             }
             Equality::IN => {
                 w.append("IN ");
-                self.build_operand(w, parent_query, &cond.right);
+                try!(self.build_operand(w, parent_query, &cond.right));
             }
             Equality::NOT_IN => {
                 w.append("NOT IN ");
-                self.build_operand(w, parent_query, &cond.right);
+                try!(self.build_operand(w, parent_query, &cond.right));
             }
             Equality::LIKE => {
                 w.append("LIKE ");
-                self.build_operand(w, parent_query, &cond.right);
+                try!(self.build_operand(w, parent_query, &cond.right));
             }
             Equality::ILIKE => {
                 w.append("ILIKE ");
-                self.build_operand(w, parent_query, &cond.right);
+                try!(self.build_operand(w, parent_query, &cond.right));
             }
             Equality::IS_NOT_NULL => {
                 w.append("IS NOT NULL");
@@ -369,27 +859,34 @@ pub trait Database {
             Equality::IS_NULL => {
                 w.append("IS NULL");
             }
+            Equality::EXISTS | Equality::NOT_EXISTS => unreachable!(),
         }
+        Ok(())
     }
 
-    fn build_field(&self, w: &mut SqlFrag, parent_query: &Query, field: &Field) {
-        self.build_operand(w, parent_query, &field.operand);
+    fn build_field(&self, w: &mut SqlFrag, parent_query: &Query, field: &Field) -> Result<(), QueryError> {
+        try!(self.build_operand(w, parent_query, &field.operand));
         match field.name {
             Some(ref name) => {
                 w.append(" AS ");
-                w.append(name);
+                w.append(&try!(self.quote_identifier(name)));
             }
             None => (),
         }
+        Ok(())
     }
 
 
-    fn build_filter(&self, w: &mut SqlFrag, parent_query: &Query, filter: &Filter) {
+    fn build_filter(&self, w: &mut SqlFrag, parent_query: &Query, filter: &Filter) -> Result<(), QueryError> {
         if !filter.sub_filters.is_empty() {
             w.append("( ");
         }
-        self.build_condition(w, parent_query, &filter.condition);
+        try!(self.build_condition(w, parent_query, &filter.condition));
         for filt in &filter.sub_filters {
+            // IS NULL and IS NOT NULL are not followed by a space
+            if !w.sql.ends_with(' ') {
+                w.append(" ");
+            }
             match filt.connector {
                 Connector::And => {
                     w.append("AND ");
@@ -398,16 +895,17 @@ pub trait Database {
                     w.append("OR ");
                 }
             }
-            self.build_filter(w, parent_query, filt);// build sub filters as well
+            try!(self.build_filter(w, parent_query, filt));// build sub filters as well
         }
         if !filter.sub_filters.is_empty() {
             w.append(" )");
         }
+        Ok(())
     }
 
     /// build the filter clause or the where clause of the query
     /// TODO: add the sub filters
-    fn build_filters(&self, w: &mut SqlFrag, parent_query: &Query, filters: &[Filter]) {
+    fn build_filters(&self, w: &mut SqlFrag, parent_query: &Query, filters: &[Filter]) -> Result<(), QueryError> {
         let mut do_and = false;
         for filter in filters {
             if do_and {
@@ -415,15 +913,16 @@ pub trait Database {
             } else {
                 do_and = true;
             }
-            self.build_filter(w, parent_query, filter);
+            try!(self.build_filter(w, parent_query, filter));
         }
+        Ok(())
     }
 
     /// build the enumerated, distinct, *, columns
     fn build_enumerated_fields(&self,
                                w: &mut SqlFrag,
                                parent_query: &Query,
-                               enumerated_fields: &[Field]) {
+                               enumerated_fields: &[Field]) -> Result<(), QueryError> {
         let mut do_comma = false;
         let mut cnt = 0;
         for field in enumerated_fields {
@@ -436,25 +935,66 @@ pub trait Database {
             if cnt % 4 == 0 {//break at every 4 columns to encourage sql tuning/revising
                 w.left_river("");
             }
-            self.build_field(w, parent_query, field);
+            try!(self.build_field(w, parent_query, field));
         }
+        Ok(())
+    }
+
+    /// build the declared queries into the WITH clause
+    fn build_declared_queries(&self, w: &mut SqlFrag, query: &Query) -> Result<(), QueryError> {
+        if !self.sql_options().contains(&SqlOption::SupportsCTE) {
+            return Err(QueryError::SqlError("This database does not support CTE (WITH clause) declared queries".to_owned()));
+        }
+        let is_recursive = query.declared_query.iter().any(|d| d.recursive.is_some());
+        if is_recursive {
+            w.left_river("WITH RECURSIVE");
+        } else {
+            w.left_river("WITH");
+        }
+        let mut do_comma = false;
+        for declared in &query.declared_query {
+            if do_comma {
+                w.comma();
+                w.right_river("");
+            } else {
+                do_comma = true;
+            }
+            w.append(&try!(self.quote_identifier(&declared.name)));
+            w.append(" AS (");
+            try!(self.build_subquery(w, &declared.query));
+            if let Some(ref recursive) = declared.recursive {
+                w.left_river("UNION ALL");
+                try!(self.build_subquery(w, recursive));
+            }
+            w.ln();
+            w.append(")");
+        }
+        Ok(())
     }
 
     /// build the select statment from the query object
-    fn build_select(&self, query: &Query, build_mode: BuildMode) -> SqlFrag {
+    fn build_select(&self, query: &Query, build_mode: BuildMode) -> Result<SqlFrag, QueryError> {
         let mut w = SqlFrag::new(self.sql_options(), build_mode);
+        if !query.declared_query.is_empty() {
+            try!(self.build_declared_queries(&mut w, query));
+        }
+        // each of the combined selects is wrapped, so the ORDER BY and LIMIT apply to the combined records
+        let parenthesize = !query.combined_queries.is_empty() &&
+                           self.sql_options().contains(&SqlOption::SupportsParenthesizedSetOperand);
+        if parenthesize {
+            w.append("(");
+        }
         w.left_river("SELECT");
-        self.build_enumerated_fields(&mut w, query, &query.enumerated_fields); //TODO: add support for column_sql, fields, functions
+        try!(self.build_enumerated_fields(&mut w, query, &query.enumerated_fields)); //TODO: add support for column_sql, fields, functions
         w.left_river("FROM");
 
-        assert!(query.from.is_some(),
-                "There should be table, query, function to select from");
-
         match query.from {
             Some(ref field) => {
-                self.build_field(&mut w, query, field);
+                try!(self.build_field(&mut w, query, field));
+            }
+            None => {
+                return Err(QueryError::NoTableSpecified("There should be table, query, function to select from".to_owned()));
             }
-            None => println!("Warning: No from in this query"),
         }
         if !query.joins.is_empty() {
             for join in &query.joins {
@@ -480,10 +1020,11 @@ pub trait Database {
                     None => (),
                 }
                 w.append("JOIN ");
-                w.append(&join.table_name.complete_name());
+                w.append(&try!(self.quote_table_name(&join.table_name)));
                 w.append(" ");
-                assert!(join.column1.len() == join.column2.len(),
-                        "There should be equal number of corresponding columns to join");
+                if join.column1.len() != join.column2.len() {
+                    return Err(QueryError::SqlError("There should be equal number of corresponding columns to join".to_owned()));
+                }
                 let mut cnt = 0;
                 let mut do_and = false;
                 for jc in &join.column1 {
@@ -493,9 +1034,9 @@ pub trait Database {
                         w.right_river("ON ");
                         do_and = true;
                     }
-                    w.append(jc);
+                    w.append(&try!(self.quote_column_name(&ColumnName::from_str(jc))));
                     w.append(" = ");
-                    w.append(&join.column2[cnt]);
+                    w.append(&try!(self.quote_column_name(&ColumnName::from_str(&join.column2[cnt]))));
                     w.append(" ");
                     cnt += 1;
                 }
@@ -504,7 +1045,7 @@ pub trait Database {
 
         if !query.filters.is_empty() {
             w.left_river("WHERE ");
-            self.build_filters(&mut w, query, &query.filters);
+            try!(self.build_filters(&mut w, query, &query.filters));
         }
 
         if !query.group_by.is_empty() {
@@ -516,7 +1057,7 @@ pub trait Database {
                 } else {
                     do_comma = true;
                 }
-                self.build_operand(&mut w, query, operand);
+                try!(self.build_operand(&mut w, query, operand));
                 w.append(" ");
             }
         }
@@ -530,7 +1071,32 @@ pub trait Database {
                 } else {
                     do_comma = true;
                 }
-                self.build_filter(&mut w, query, hav);
+                // an expression `Query::having` could not take apart, it would be quoted as a column
+                if let Operand::ColumnName(ref column) = hav.condition.left {
+                    if column.column.contains('(') {
+                        return Err(QueryError::InvalidIdentifier(format!("{:?}", column.complete_name())));
+                    }
+                }
+                try!(self.build_filter(&mut w, query, hav));
+            }
+        }
+
+        if parenthesize {
+            w.append(")");
+        }
+        for combined in &query.combined_queries {
+            match combined.operation {
+                SetOperation::UNION => w.left_river("UNION"),
+                SetOperation::UNION_ALL => w.left_river("UNION ALL"),
+                SetOperation::INTERSECT => w.left_river("INTERSECT"),
+                SetOperation::EXCEPT => w.left_river("EXCEPT"),
+            };
+            if parenthesize {
+                w.append("(");
+                try!(self.build_subquery(&mut w, &combined.query));
+                w.append(")");
+            } else {
+                try!(self.build_subquery(&mut w, &combined.query));
             }
         }
 
@@ -543,25 +1109,7 @@ pub trait Database {
                 } else {
                     do_comma = true;
                 }
-				self.build_operand(&mut w, query, &order.operand);
-                match &order.direction {
-					&Some(ref direction) => {
-						match direction{
-                    		&Direction::ASC => w.append(" ASC"),
-                    		&Direction::DESC => w.append(" DESC"),
-						}
-					},
-					&None => w.append("")
-                };
-                match &order.nulls_where {
-					&Some(ref nulls_where) => {
-						match nulls_where{
-                    		&NullsWhere::FIRST => w.append(" NULLS FIRST"),
-                    		&NullsWhere::LAST => w.append(" NULLS LAST"),
-						}
-					},
-					&None => w.append(""),
-                };
+                try!(self.build_order(&mut w, query, order));
             }
         }
         
@@ -584,46 +1132,64 @@ pub trait Database {
             },
             None => (),
         }
-        w
+        Ok(w)
     }
 
-    /// TODO: when the number of values is greater than the number of columns
-	/// wrap it into another set and make sure the values are in multiples of the the n columns
+    /// when the number of values is greater than the number of columns
+	/// the values are wrapped into multiple rows of n columns
 	/// http://www.postgresql.org/docs/9.0/static/dml-insert.html
-    fn build_insert(&self, query: &Query, build_mode: BuildMode) -> SqlFrag {
-        println!("building insert query");
+    fn build_insert(&self, query: &Query, build_mode: BuildMode) -> Result<SqlFrag, QueryError> {
         let mut w = SqlFrag::new(self.sql_options(), build_mode);
         w.left_river("INSERT");
         w.append("INTO ");
         let into_table = query.get_from_table();
-        assert!(into_table.is_some(), "There should be table to insert to");
+        if into_table.is_none() {
+            return Err(QueryError::NoTableSpecified("There should be table to insert to".to_owned()));
+        }
         if let Some(table_name) = into_table {
-            if self.sql_options().contains(&SqlOption::UsesSchema) {
-                w.append(&table_name.complete_name());
-            } else {
-                w.append(&table_name.name);
-            }
+            w.append(&try!(self.quote_table_name(table_name)));
         }
 
 
         w.append("( ");
-        self.build_enumerated_fields(&mut w, query, &query.enumerated_fields); //TODO: add support for column_sql, fields, functions
+        try!(self.build_enumerated_fields(&mut w, query, &query.enumerated_fields)); //TODO: add support for column_sql, fields, functions
         w.append(" ) ");
-        assert!(!query.values.is_empty(),
-                "values should not be empty, when inserting records");
+        if query.values.is_empty() {
+            return Err(QueryError::SqlError("values should not be empty, when inserting records".to_owned()));
+        }
+        let n_columns = query.enumerated_fields.len();
+        if n_columns == 0 {
+            return Err(QueryError::NoColumnSpecified("There should be columns to insert to".to_owned()));
+        }
+        if query.values.len() % n_columns != 0 {
+            return Err(QueryError::SqlError("values should be in multiples of the number of columns".to_owned()));
+        }
         if !query.values.is_empty() {
             w.left_river("VALUES");
-            w.append("(");
-            let mut do_comma = false;
-            for vo in &query.values {
-                if do_comma {
-                    w.commasp();
+            let mut do_comma_row = false;
+            for row in query.values.chunks(n_columns) {
+                if do_comma_row {
+                    w.comma();
+                    w.right_river("");
                 } else {
-                    do_comma = true;
+                    do_comma_row = true;
                 }
-                self.build_operand(&mut w, query, vo);
+                w.append("(");
+                let mut do_comma = false;
+                for vo in row {
+                    if do_comma {
+                        w.commasp();
+                    } else {
+                        do_comma = true;
+                    }
+                    try!(self.build_operand(&mut w, query, vo));
+                }
+                w.append(")");
             }
-            w.append(") ");
+            w.append(" ");
+        }
+        if let Some(ref on_conflict) = query.on_conflict {
+            try!(self.build_on_conflict(&mut w, on_conflict));
         }
         if !query.enumerated_returns.is_empty() {
             if self.sql_options().contains(&SqlOption::SupportsReturningClause) {
@@ -635,24 +1201,33 @@ pub trait Database {
                     } else {
                         do_comma = true;
                     }
-                    self.build_field(&mut w, query, field);
+                    try!(self.build_field(&mut w, query, field));
                 }
             }
         }
         w.ln();
-        w
+        Ok(w)
     }
 
+    /// build the upsert clause of the insert statement
+    fn build_on_conflict(&self, w: &mut SqlFrag, on_conflict: &OnConflict) -> Result<(), QueryError> {
+        self.dialect().build_on_conflict(self, w, on_conflict)
+    }
 
-    fn build_update(&self, query: &Query, build_mode: BuildMode) -> SqlFrag {
+    fn build_update(&self, query: &Query, build_mode: BuildMode) -> Result<SqlFrag, QueryError> {
         let mut w = SqlFrag::new(self.sql_options(), build_mode);
         w.left_river("UPDATE ");
         let from_table = query.get_from_table();
-        assert!(from_table.is_some(), "There should be table to update from");
+        if from_table.is_none() {
+            return Err(QueryError::NoTableSpecified("There should be table to update from".to_owned()));
+        }
         if let Some(ref from) = from_table {
-            w.append(&from.complete_name());
+            w.append(&try!(self.quote_table_name(from)));
         }
         let enumerated_columns = query.get_enumerated_columns();
+        if enumerated_columns.len() != query.values.len() {
+            return Err(QueryError::SqlError("There should be a value for each of the columns to update".to_owned()));
+        }
         let mut do_comma = false;
         if !enumerated_columns.is_empty() {
             w.left_river("SET ");
@@ -664,18 +1239,15 @@ pub trait Database {
             } else {
                 do_comma = true;
             }
-            w.append(&ec.column);
+            w.append(&try!(self.quote_identifier(&ec.column)));
             w.append(" = ");
-            let value = &query.values[column_index];
-            if let &Operand::Value(ref value) = value {
-                w.parameter(value.clone());
-            }
+            try!(self.build_operand(&mut w, query, &query.values[column_index]));
             column_index += 1;
         }
 
         if !query.filters.is_empty() {
             w.left_river("WHERE ");
-            self.build_filters(&mut w, query, &query.filters);
+            try!(self.build_filters(&mut w, query, &query.filters));
         }
         if !query.enumerated_returns.is_empty() {
            if self.sql_options().contains(&SqlOption::SupportsReturningClause) {
@@ -687,29 +1259,34 @@ pub trait Database {
                    } else {
                        do_comma = true;
                    }
-                   self.build_field(&mut w, query, field);
+                   try!(self.build_field(&mut w, query, field));
                }
            }
         }
-        w
+        Ok(w)
     }
 
-    fn build_delete(&self, query: &Query, build_mode: BuildMode) -> SqlFrag {
+    fn build_delete(&self, query: &Query, build_mode: BuildMode) -> Result<SqlFrag, QueryError> {
         let mut w = SqlFrag::new(self.sql_options(), build_mode);
         w.left_river("DELETE FROM ");
         let from_table = query.get_from_table();
-        assert!(from_table.is_some(), "There should be table to delete from");
+        if from_table.is_none() {
+            return Err(QueryError::NoTableSpecified("There should be table to delete from".to_owned()));
+        }
         if let Some(ref from) = from_table {
-            w.append(&from.complete_name());
+            w.append(&try!(self.quote_table_name(from)));
         }
         if !query.filters.is_empty() {
             w.left_river("WHERE ");
-            self.build_filters(&mut w, query, &query.filters);
+            try!(self.build_filters(&mut w, query, &query.filters));
         }
-        w
+        Ok(w)
     }
 
-    fn sql_options(&self) -> Vec<SqlOption>;
+    /// the features and quirks of the database
+    fn sql_options(&self) -> Vec<SqlOption> {
+        self.dialect().sql_options()
+    }
 
 }
 
@@ -717,33 +1294,76 @@ pub trait Database {
 /// Deployment Database should implement this trait,
 /// to enable automated installation of the app, regardless what database platform
 /// the app is developed from.
+/// `copy::SchemaCopy` creates the tables introspected from one platform on another this way.
 pub trait DatabaseDDL{
     //////////////////////////////////////////
     /// The following methods involves DDL(Data definition language) operation
     // //////////////////////////////////////
     /// create a database schema
-    fn create_schema(&self, schema: &str);
+    fn create_schema(&self, schema: &str) -> Result<(), DbError>;
 
     /// drop the database schema
-    fn drop_schema(&self, schema: &str);
+    fn drop_schema(&self, schema: &str) -> Result<(), DbError>;
 
     /// create a database table based on the Model Definition
-    fn create_table(&self, model: &Table);
+    fn create_table(&self, model: &Table) -> Result<(), DbError>;
 
     /// build sql for create table
-    fn build_create_table(&self, table: &Table) -> SqlFrag;
+    fn build_create_table(&self, table: &Table) -> Result<SqlFrag, QueryError>;
 
     /// rename table, in the same schema
-    fn rename_table(&self, table: &Table, new_tablename: String);
+    fn rename_table(&self, table: &Table, new_tablename: String) -> Result<(), DbError>;
 
     /// drop table
-    fn drop_table(&self, table: &Table);
+    fn drop_table(&self, table: &Table) -> Result<(), DbError>;
 
     /// set the foreign key constraint of a table
-    fn set_foreign_constraint(&self, model: &Table);
+    fn set_foreign_constraint(&self, model: &Table) -> Result<(), DbError>;
 
     /// set the primary key constraint of a table
-    fn set_primary_constraint(&self, model: &Table);
+    fn set_primary_constraint(&self, model: &Table) -> Result<(), DbError>;
+
+    /// build sql for create index
+    fn build_create_index(&self, table: &Table, index: &Index) -> Result<SqlFrag, QueryError>;
+
+    /// create the index on the table
+    fn create_index(&self, table: &Table, index: &Index) -> Result<(), DbError>;
+
+    /// drop the index of the table
+    fn drop_index(&self, table: &Table, index: &Index) -> Result<(), DbError>;
+
+    /// build sql for rename table, in the same schema
+    fn build_rename_table(&self, table: &Table, new_tablename: &str) -> Result<SqlFrag, QueryError>;
+
+    /// build sql for drop table
+    fn build_drop_table(&self, table: &Table) -> Result<SqlFrag, QueryError>;
+
+    /// build sql for drop index
+    fn build_drop_index(&self, table: &Table, index: &Index) -> Result<SqlFrag, QueryError>;
+
+    /// build sql to add the column to the table
+    fn build_add_column(&self, table: &Table, column: &Column) -> Result<SqlFrag, QueryError>;
+
+    /// build sql to drop the column of the table
+    fn build_drop_column(&self, table: &Table, column: &Column) -> Result<SqlFrag, QueryError>;
+
+    /// build sql to rename the column
+    fn build_rename_column(&self, table: &Table, column: &Column, new_name: &str) -> Result<SqlFrag, QueryError>;
+
+    /// build sql to change the data type of the column to the one of the column definition
+    fn build_alter_column_type(&self, table: &Table, column: &Column) -> Result<SqlFrag, QueryError>;
+
+    /// build sql to set or drop the NOT NULL of the column, as in the column definition
+    fn build_alter_column_nullability(&self, table: &Table, column: &Column) -> Result<SqlFrag, QueryError>;
+
+    /// build sql to set or drop the default of the column, as in the column definition
+    fn build_alter_column_default(&self, table: &Table, column: &Column) -> Result<SqlFrag, QueryError>;
+
+    /// build sql to add the foreign key of the column
+    fn build_add_foreign_key(&self, table: &Table, column: &Column) -> Result<SqlFrag, QueryError>;
+
+    /// build sql to drop the foreign key of the column
+    fn build_drop_foreign_key(&self, table: &Table, column: &Column) -> Result<SqlFrag, QueryError>;
 }
 
 
@@ -0,0 +1,572 @@
+use std::fmt;
+use dao::{Dao, Value, Type};
+use database::{Database, DbError, SqlOption};
+use query::{Query, Operand, DateUnit, OnConflict};
+use query::Error as QueryError;
+use writer::SqlFrag;
+use logger::QueryLog;
+
+/// the database platforms sql can be written for
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DialectKind {
+    Postgres,
+    Sqlite,
+    Mysql,
+}
+
+/// the version of a database server, i.e: 9.5.3
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Version {
+    pub major: u32,
+    pub minor: u32,
+    pub patch: u32,
+}
+
+impl Version {
+    pub fn new(major: u32, minor: u32, patch: u32) -> Self {
+        Version {
+            major: major,
+            minor: minor,
+            patch: patch,
+        }
+    }
+
+    /// parse the version reported by the server,
+    /// i.e: `9.5.3`, `8.0.21-log`, `PostgreSQL 9.6.1 on x86_64-pc-linux-gnu`
+    pub fn parse(version: &str) -> Option<Self> {
+        let token = match version.split_whitespace()
+                                 .find(|t| t.chars().next().map_or(false, |c| c.is_digit(10))) {
+            Some(token) => token,
+            None => return None,
+        };
+        let mut parts = vec![];
+        for part in token.split('.').take(3) {
+            let digits: String = part.chars().take_while(|c| c.is_digit(10)).collect();
+            match digits.parse::<u32>() {
+                Ok(n) => parts.push(n),
+                Err(_) => break,
+            }
+        }
+        if parts.is_empty() {
+            return None;
+        }
+        parts.resize(3, 0);
+        Some(Version::new(parts[0], parts[1], parts[2]))
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+/// The sql flavor of a database platform, independent of any connection:
+/// placeholders, identifier quoting, supported features and data type names.
+///
+/// When the server version is not specified, the features of the recent versions are assumed.
+///
+/// ```ignore
+/// let mut dialect = Dialect::from_name("mysql").unwrap();
+/// dialect.version(Version::new(8, 0, 0));
+/// let frag = try!(dialect.render(&mut query));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Dialect {
+    kind: DialectKind,
+    version: Option<Version>,
+}
+
+impl Dialect {
+    pub fn new(kind: DialectKind) -> Self {
+        Dialect {
+            kind: kind,
+            version: None,
+        }
+    }
+
+    pub fn postgres() -> Self {
+        Dialect::new(DialectKind::Postgres)
+    }
+
+    pub fn sqlite() -> Self {
+        Dialect::new(DialectKind::Sqlite)
+    }
+
+    pub fn mysql() -> Self {
+        Dialect::new(DialectKind::Mysql)
+    }
+
+    /// the dialect of the platform name, as used in the connection url
+    pub fn from_name(name: &str) -> Option<Self> {
+        match &*name.to_lowercase() {
+            "postgres" | "postgresql" | "pg" => Some(Dialect::postgres()),
+            "sqlite" | "sqlite3" => Some(Dialect::sqlite()),
+            "mysql" | "mariadb" => Some(Dialect::mysql()),
+            _ => None,
+        }
+    }
+
+    /// target the features of this server version
+    pub fn version(&mut self, version: Version) -> &mut Self {
+        self.version = Some(version);
+        self
+    }
+
+    pub fn get_version(&self) -> Option<Version> {
+        self.version
+    }
+
+    pub fn kind(&self) -> DialectKind {
+        self.kind
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self.kind {
+            DialectKind::Postgres => "postgres",
+            DialectKind::Sqlite => "sqlite",
+            DialectKind::Mysql => "mysql",
+        }
+    }
+
+    /// whether the server is at least of this version,
+    /// an unknown version is not assumed to have the newer features
+    pub fn is_at_least(&self, major: u32, minor: u32, patch: u32) -> bool {
+        match self.version {
+            Some(version) => version >= Version::new(major, minor, patch),
+            None => false,
+        }
+    }
+
+    /// whether the server is known to be older than this version
+    pub fn is_before(&self, major: u32, minor: u32, patch: u32) -> bool {
+        match self.version {
+            Some(version) => version < Version::new(major, minor, patch),
+            None => false,
+        }
+    }
+
+    /// the features and quirks of the platform
+    /// http://www.postgresql.org/about/featurematrix/
+    pub fn sql_options(&self) -> Vec<SqlOption> {
+        let mut options = vec![];
+        match self.kind {
+            DialectKind::Postgres => {
+                options.push(SqlOption::UsesNumberedParam);
+                // every maintained release has them, only the servers known to be older are excluded
+                if !self.is_before(8, 2, 0) {
+                    options.push(SqlOption::SupportsReturningClause);
+                }
+                if !self.is_before(8, 4, 0) {
+                    options.push(SqlOption::SupportsCTE);
+                }
+                options.push(SqlOption::SupportsInheritance);
+                options.push(SqlOption::UsesSchema);
+                options.push(SqlOption::ReturnMetaColumns);
+                options.push(SqlOption::SupportsCursor);
+                options.push(SqlOption::SupportsTransactionalDDL);
+                options.push(SqlOption::SupportsIntersectExcept);
+                options.push(SqlOption::SupportsParenthesizedSetOperand);
+            }
+            DialectKind::Sqlite => {
+                options.push(SqlOption::UsesNumberedParam);
+                if self.is_at_least(3, 8, 3) {
+                    options.push(SqlOption::SupportsCTE);
+                }
+                options.push(SqlOption::SupportsTransactionalDDL);
+                options.push(SqlOption::SupportsIntersectExcept);
+            }
+            DialectKind::Mysql => {
+                options.push(SqlOption::UsesQuestionMark);
+                if self.is_at_least(8, 0, 0) {
+                    options.push(SqlOption::SupportsCTE);
+                }
+                if self.is_at_least(8, 0, 31) {
+                    options.push(SqlOption::SupportsIntersectExcept);
+                }
+                options.push(SqlOption::SupportsParenthesizedSetOperand);
+            }
+        }
+        options
+    }
+
+    /// the placeholder of the nth parameter, starting at 1
+    pub fn placeholder(&self, index: usize) -> String {
+        match self.kind {
+            DialectKind::Mysql => "?".to_owned(),
+            _ => format!("${}", index),
+        }
+    }
+
+    /// mysql quotes identifiers with backticks, unless in ANSI_QUOTES mode
+    pub fn identifier_quote(&self) -> char {
+        match self.kind {
+            DialectKind::Mysql => '`',
+            _ => '"',
+        }
+    }
+
+    /// the maximum number of parameters that can be bound in a single statement,
+    /// sqlite prior to 3.32 only allows 999,
+    /// postgresql and mysql send it as 16 bit integer
+    pub fn max_parameters(&self) -> usize {
+        match self.kind {
+            DialectKind::Sqlite => {
+                if self.is_at_least(3, 32, 0) {
+                    32766
+                } else {
+                    999
+                }
+            }
+            _ => 65535,
+        }
+    }
+
+    /// the database data type of the rust type, used for creating tables
+    pub fn type_name(&self, rust_type: &Type) -> Option<String> {
+        let name = match self.kind {
+            DialectKind::Postgres => {
+                match *rust_type {
+                    Type::Bool => "boolean",
+                    Type::I8 => "char",
+                    Type::I16 => "smallint",
+                    Type::I32 => "integer",
+                    Type::U32 => "oid",
+                    Type::I64 => "bigint",
+                    Type::F32 => "real",
+                    Type::F64 => "double precision",
+                    Type::String => "character varying",
+                    Type::VecU8 => "bytea",
+                    Type::Json => "json",
+                    Type::Uuid => "uuid",
+                    Type::NaiveDateTime => "timestamp",
+                    Type::DateTime => "timestamp with time zone",
+                    Type::NaiveDate => "date",
+                    Type::NaiveTime => "time",
+                    Type::Object => "hstore",
+                    _ => return None,
+                }
+            }
+            DialectKind::Sqlite => {
+                match *rust_type {
+                    Type::Bool => "boolean",
+                    Type::I8 => "tinyint",
+                    Type::I16 => "smallint",
+                    Type::I32 => "int",
+                    Type::I64 => "integer",
+                    Type::U8 => "integer",
+                    Type::U16 => "integer",
+                    Type::U32 => "integer",
+                    Type::U64 => "integer",
+                    Type::F32 => "float",
+                    Type::F64 => "real",
+                    Type::String => "text",
+                    Type::VecU8 => "blob",
+                    Type::Json => "json",
+                    Type::Uuid => "uuid",
+                    Type::NaiveDateTime => "timestamp",
+                    Type::DateTime => "timestamp with time zone",
+                    Type::NaiveDate => "date",
+                    Type::NaiveTime => "time",
+                    _ => return None,
+                }
+            }
+            DialectKind::Mysql => {
+                match *rust_type {
+                    Type::Bool => "bool",
+                    Type::I8 => "tinyint(1)",
+                    Type::I16 => "integer",
+                    Type::I32 => "integer",
+                    Type::U32 => "integer unsigned",
+                    Type::I64 => "bigint",
+                    Type::F32 => "real",
+                    Type::F64 => "real",
+                    Type::String => "text",
+                    Type::VecU8 => "blob",
+                    Type::Json => "text",
+                    Type::Uuid => "varchar(36)",
+                    Type::NaiveDateTime => "timestamp",
+                    Type::DateTime => "timestamp",
+                    Type::NaiveDate => "date",
+                    Type::NaiveTime => "time",
+                    _ => return None,
+                }
+            }
+        };
+        Some(name.to_owned())
+    }
+
+    /// the current timestamp
+    pub fn build_now(&self, w: &mut SqlFrag) {
+        match self.kind {
+            DialectKind::Postgres => w.append("now()"),
+            DialectKind::Sqlite => w.append("datetime('now')"),
+            DialectKind::Mysql => w.append("CURRENT_TIMESTAMP"),
+        };
+    }
+
+    /// string concatenation, uses the standard `||` operator,
+    /// except in mysql where `||` is a logical OR
+    pub fn build_concat<D>(&self, db: &D, w: &mut SqlFrag, parent_query: &Query, params: &[Operand]) -> Result<(), QueryError>
+        where D: Database + ?Sized
+    {
+        if self.kind == DialectKind::Mysql {
+            return db.build_function_call(w, parent_query, "CONCAT", params);
+        }
+        w.append("(");
+        let mut do_concat = false;
+        for param in params {
+            if do_concat {
+                w.append(" || ");
+            } else {
+                do_concat = true;
+            }
+            try!(db.build_operand(w, parent_query, param));
+        }
+        w.append(")");
+        Ok(())
+    }
+
+    /// truncate the timestamp to the unit, i.e: `date_trunc('month', created)`,
+    /// sqlite and mysql have no date_trunc, the timestamp is formatted with the lesser parts zeroed
+    pub fn build_date_trunc<D>(&self, db: &D, w: &mut SqlFrag, parent_query: &Query, unit: &DateUnit, operand: &Operand) -> Result<(), QueryError>
+        where D: Database + ?Sized
+    {
+        match self.kind {
+            DialectKind::Postgres => {
+                w.append("date_trunc('");
+                w.append(unit.as_str());
+                w.append("', ");
+                try!(db.build_operand(w, parent_query, operand));
+                w.append(")");
+            }
+            DialectKind::Sqlite => {
+                let format = match *unit {
+                    DateUnit::Year => "%Y-01-01 00:00:00",
+                    DateUnit::Month => "%Y-%m-01 00:00:00",
+                    DateUnit::Day => "%Y-%m-%d 00:00:00",
+                    DateUnit::Hour => "%Y-%m-%d %H:00:00",
+                    DateUnit::Minute => "%Y-%m-%d %H:%M:00",
+                    DateUnit::Second => "%Y-%m-%d %H:%M:%S",
+                };
+                w.append(&format!("strftime('{}', ", format));
+                try!(db.build_operand(w, parent_query, operand));
+                w.append(")");
+            }
+            DialectKind::Mysql => {
+                let format = match *unit {
+                    DateUnit::Year => "%Y-01-01 00:00:00",
+                    DateUnit::Month => "%Y-%m-01 00:00:00",
+                    DateUnit::Day => "%Y-%m-%d 00:00:00",
+                    DateUnit::Hour => "%Y-%m-%d %H:00:00",
+                    DateUnit::Minute => "%Y-%m-%d %H:%i:00",
+                    DateUnit::Second => "%Y-%m-%d %H:%i:%s",
+                };
+                w.append("CAST(DATE_FORMAT(");
+                try!(db.build_operand(w, parent_query, operand));
+                w.append(&format!(", '{}') AS DATETIME)", format));
+            }
+        }
+        Ok(())
+    }
+
+    /// build the upsert clause of the insert statement
+    /// postgresql (>= 9.5) and sqlite (>= 3.24) uses `ON CONFLICT`,
+    /// mysql uses `ON DUPLICATE KEY UPDATE`, which matches any of the unique keys,
+    /// doing nothing is done by setting the conflicting column to itself
+    pub fn build_on_conflict<D>(&self, db: &D, w: &mut SqlFrag, on_conflict: &OnConflict) -> Result<(), QueryError>
+        where D: Database + ?Sized
+    {
+        let supported = match self.kind {
+            // the releases before 9.5 are no longer maintained, only excluded when known
+            DialectKind::Postgres => !self.is_before(9, 5, 0),
+            DialectKind::Sqlite => self.is_at_least(3, 24, 0),
+            DialectKind::Mysql => true,
+        };
+        if !supported {
+            return Err(QueryError::SqlError(format!("{} {} does not support upsert",
+                                                    self.name(),
+                                                    self.version.map(|v| v.to_string()).unwrap_or_default())));
+        }
+        if self.kind == DialectKind::Mysql {
+            w.left_river("ON DUPLICATE KEY UPDATE ");
+            if on_conflict.update_columns.is_empty() {
+                if let Some(c) = on_conflict.columns.first() {
+                    let column = try!(db.quote_identifier(&c.column));
+                    w.append(&format!("{} = {}", column, column));
+                }
+            } else {
+                let mut do_comma = false;
+                for c in &on_conflict.update_columns {
+                    if do_comma {
+                        w.commasp();
+                    } else {
+                        do_comma = true;
+                    }
+                    let column = try!(db.quote_identifier(&c.column));
+                    w.append(&format!("{} = VALUES({})", column, column));
+                }
+            }
+            w.append(" ");
+            return Ok(());
+        }
+        w.left_river("ON CONFLICT");
+        w.append("(");
+        let mut do_comma = false;
+        for c in &on_conflict.columns {
+            if do_comma {
+                w.commasp();
+            } else {
+                do_comma = true;
+            }
+            w.append(&try!(db.quote_identifier(&c.column)));
+        }
+        w.append(") ");
+        if on_conflict.update_columns.is_empty() {
+            w.append("DO NOTHING ");
+        } else {
+            w.append("DO UPDATE ");
+            w.left_river("SET ");
+            let mut do_comma = false;
+            for c in &on_conflict.update_columns {
+                if do_comma {
+                    w.commasp();
+                } else {
+                    do_comma = true;
+                }
+                let column = try!(db.quote_identifier(&c.column));
+                w.append(&format!("{} = EXCLUDED.{}", column, column));
+            }
+            w.append(" ");
+        }
+        Ok(())
+    }
+
+    /// a database without connection writing the sql of this dialect
+    pub fn renderer(&self) -> Renderer {
+        Renderer::new(self.clone())
+    }
+
+    /// the sql and parameters of the query in this dialect
+    pub fn render(&self, query: &mut Query) -> Result<SqlFrag, QueryError> {
+        query.build(&self.renderer())
+    }
+}
+
+/// Writes the sql of a dialect without a connection,
+/// executing statements fails.
+pub struct Renderer {
+    dialect: Dialect,
+    query_log: QueryLog,
+}
+
+impl Renderer {
+    pub fn new(dialect: Dialect) -> Self {
+        Renderer {
+            dialect: dialect,
+            query_log: QueryLog::new(),
+        }
+    }
+
+    fn not_connected(&self) -> DbError {
+        DbError::new(&format!("No connection, the {} dialect can only build sql", self.dialect.name()))
+    }
+}
+
+impl Database for Renderer {
+    fn version(&self) -> Result<String, DbError> {
+        match self.dialect.get_version() {
+            Some(version) => Ok(version.to_string()),
+            None => Err(self.not_connected()),
+        }
+    }
+
+    fn begin(&self) -> Result<(), DbError> {
+        Err(self.not_connected())
+    }
+
+    fn commit(&self) -> Result<(), DbError> {
+        Err(self.not_connected())
+    }
+
+    fn rollback(&self) -> Result<(), DbError> {
+        Err(self.not_connected())
+    }
+
+    fn is_transacted(&self) -> bool {
+        false
+    }
+
+    fn is_closed(&self) -> bool {
+        true
+    }
+
+    fn is_connected(&self) -> bool {
+        false
+    }
+
+    fn close(&self) {
+    }
+
+    fn is_valid(&self) -> bool {
+        false
+    }
+
+    fn reset(&self) -> Result<(), DbError> {
+        Ok(())
+    }
+
+    fn query_log(&self) -> &QueryLog {
+        &self.query_log
+    }
+
+    fn dialect(&self) -> &Dialect {
+        &self.dialect
+    }
+
+    fn execute_sql_with_return(&self, _sql: &str, _params: &[Value]) -> Result<Vec<Dao>, DbError> {
+        Err(self.not_connected())
+    }
+
+    fn execute_sql(&self, _sql: &str, _params: &[Value]) -> Result<usize, DbError> {
+        Err(self.not_connected())
+    }
+}
+
+#[test]
+fn test_parse_version() {
+    assert_eq!(Version::parse("9.5.3"), Some(Version::new(9, 5, 3)));
+    assert_eq!(Version::parse("8.0.21-log"), Some(Version::new(8, 0, 21)));
+    assert_eq!(Version::parse("10.1"), Some(Version::new(10, 1, 0)));
+    assert_eq!(Version::parse("PostgreSQL 9.6.1 on x86_64-pc-linux-gnu"),
+               Some(Version::new(9, 6, 1)));
+    assert_eq!(Version::parse("unknown"), None);
+}
+
+#[test]
+fn test_version_specific_options() {
+    let mut sqlite = Dialect::sqlite();
+    assert_eq!(sqlite.max_parameters(), 999);
+    sqlite.version(Version::new(3, 32, 0));
+    assert_eq!(sqlite.max_parameters(), 32766);
+    let mut mysql = Dialect::mysql();
+    assert!(!mysql.sql_options().contains(&SqlOption::SupportsCTE));
+    mysql.version(Version::new(5, 7, 31));
+    assert!(!mysql.sql_options().contains(&SqlOption::SupportsCTE));
+    mysql.version(Version::new(8, 0, 21));
+    assert!(mysql.sql_options().contains(&SqlOption::SupportsCTE));
+    let mut pg = Dialect::postgres();
+    assert!(pg.sql_options().contains(&SqlOption::SupportsReturningClause));
+    pg.version(Version::new(8, 1, 0));
+    assert!(!pg.sql_options().contains(&SqlOption::SupportsReturningClause));
+    assert_eq!(mysql.placeholder(2), "?");
+    assert_eq!(Dialect::postgres().placeholder(2), "$2");
+}
+
+#[test]
+fn test_type_name() {
+    assert_eq!(Dialect::mysql().type_name(&Type::I64), Some("bigint".to_owned()));
+    assert_eq!(Dialect::mysql().type_name(&Type::U32), Some("integer unsigned".to_owned()));
+    assert_eq!(Dialect::postgres().type_name(&Type::I64), Some("bigint".to_owned()));
+    assert_eq!(Dialect::sqlite().type_name(&Type::Object), None);
+}
@@ -0,0 +1,286 @@
+use table::{Table, Column, Index, order_by_foreign_keys};
+use database::{Database, DatabaseDDL, BuildMode};
+use writer::SqlFrag;
+use query::Error as QueryError;
+
+/// a change of the schema, the table is the one being altered
+#[derive(Debug, Clone)]
+pub enum SchemaChange {
+    CreateTable(Table),
+    DropTable(Table),
+    RenameTable(Table, String),
+    AddColumn(Table, Column),
+    DropColumn(Table, Column),
+    RenameColumn(Table, Column, String),
+    /// the column is the desired one
+    AlterColumnType(Table, Column),
+    /// the column is the desired one
+    AlterColumnNullability(Table, Column),
+    /// the column is the desired one
+    AlterColumnDefault(Table, Column),
+    AddForeignKey(Table, Column),
+    DropForeignKey(Table, Column),
+    CreateIndex(Table, Index),
+    DropIndex(Table, Index),
+}
+
+impl SchemaChange {
+
+    /// the sql of the change, for the platform
+    pub fn build(&self, ddl: &DatabaseDDL) -> Result<SqlFrag, QueryError> {
+        match *self {
+            SchemaChange::CreateTable(ref table) => ddl.build_create_table(table),
+            SchemaChange::DropTable(ref table) => ddl.build_drop_table(table),
+            SchemaChange::RenameTable(ref table, ref new_name) => ddl.build_rename_table(table, new_name),
+            SchemaChange::AddColumn(ref table, ref column) => ddl.build_add_column(table, column),
+            SchemaChange::DropColumn(ref table, ref column) => ddl.build_drop_column(table, column),
+            SchemaChange::RenameColumn(ref table, ref column, ref new_name) => {
+                ddl.build_rename_column(table, column, new_name)
+            }
+            SchemaChange::AlterColumnType(ref table, ref column) => ddl.build_alter_column_type(table, column),
+            SchemaChange::AlterColumnNullability(ref table, ref column) => {
+                ddl.build_alter_column_nullability(table, column)
+            }
+            SchemaChange::AlterColumnDefault(ref table, ref column) => {
+                ddl.build_alter_column_default(table, column)
+            }
+            SchemaChange::AddForeignKey(ref table, ref column) => ddl.build_add_foreign_key(table, column),
+            SchemaChange::DropForeignKey(ref table, ref column) => ddl.build_drop_foreign_key(table, column),
+            SchemaChange::CreateIndex(ref table, ref index) => ddl.build_create_index(table, index),
+            SchemaChange::DropIndex(ref table, ref index) => ddl.build_drop_index(table, index),
+        }
+    }
+}
+
+/// the changes, grouped in the order they have to be applied
+#[derive(Default)]
+struct Changes {
+    drop_constraints: Vec<SchemaChange>,
+    renames: Vec<SchemaChange>,
+    create_tables: Vec<SchemaChange>,
+    alter_columns: Vec<SchemaChange>,
+    drop_columns: Vec<SchemaChange>,
+    drop_tables: Vec<SchemaChange>,
+    add_constraints: Vec<SchemaChange>,
+}
+
+impl Changes {
+    fn into_vec(self) -> Vec<SchemaChange> {
+        let mut changes = self.drop_constraints;
+        changes.extend(self.renames);
+        changes.extend(self.create_tables);
+        changes.extend(self.alter_columns);
+        changes.extend(self.drop_columns);
+        changes.extend(self.drop_tables);
+        changes.extend(self.add_constraints);
+        changes
+    }
+}
+
+/// Compares the current tables with the desired tables.
+///
+/// Tables and columns are matched by name, a renamed table or column would otherwise be
+/// dropped and created again, so the renames are given as hints.
+/// The data types are compared with the generic `Type`, so the snapshots can come from different platforms.
+/// The defaults are compared as the platform writes them.
+///
+/// ```ignore
+/// let current: Vec<Table> = dev.get_all_tables()
+///                              .iter()
+///                              .map(|&(ref schema, ref table, is_view)| dev.get_table_metadata(schema, table, is_view))
+///                              .collect();
+/// let mut diff = SchemaDiff::new();
+/// diff.rename_column("bazaar.product", "title", "name");
+/// for change in diff.diff(db, &current, &desired) {
+///     let frag = try!(change.build(ddl));
+///     try!(db.execute_sql(&frag.sql, &frag.params));
+/// }
+/// ```
+pub struct SchemaDiff {
+    /// (from, to) complete names of the tables
+    table_renames: Vec<(String, String)>,
+    /// (complete name of the desired table, from, to)
+    column_renames: Vec<(String, String, String)>,
+}
+
+impl SchemaDiff {
+
+    pub fn new() -> Self {
+        SchemaDiff {
+            table_renames: vec![],
+            column_renames: vec![],
+        }
+    }
+
+    /// the table is renamed, i.e: `rename_table("bazaar.item", "bazaar.product")`
+    pub fn rename_table(&mut self, from: &str, to: &str) -> &mut Self {
+        self.table_renames.push((from.to_owned(), to.to_owned()));
+        self
+    }
+
+    /// the column of the desired table is renamed, i.e: `rename_column("bazaar.product", "title", "name")`
+    pub fn rename_column(&mut self, table: &str, from: &str, to: &str) -> &mut Self {
+        self.column_renames.push((table.to_owned(), from.to_owned(), to.to_owned()));
+        self
+    }
+
+    /// the changes to turn the current tables into the desired tables, in the order they can be applied:
+    /// the foreign keys and indexes are dropped first, then the renames, the created tables,
+    /// the added and altered columns, the dropped columns and tables,
+    /// and lastly the foreign keys and indexes are created.
+    /// The referenced tables are created before and dropped after the tables referencing them.
+    pub fn diff(&self, db: &Database, current: &[Table], desired: &[Table]) -> Vec<SchemaChange> {
+        let mut changes = Changes::default();
+        let mut matched = vec![];
+        let mut created = vec![];
+        for table in desired {
+            match self.find_current_table(current, table) {
+                Some(existing) => {
+                    matched.push(existing.complete_name());
+                    if existing.name != table.name {
+                        changes.renames.push(SchemaChange::RenameTable(existing.clone(), table.name.to_owned()));
+                    }
+                    self.diff_table(db, &mut changes, existing, table);
+                }
+                None => created.push(table.clone()),
+            }
+        }
+        for table in order_by_foreign_keys(created) {
+            for index in &table.indexes {
+                changes.add_constraints.push(SchemaChange::CreateIndex(table.clone(), index.clone()));
+            }
+            changes.create_tables.push(SchemaChange::CreateTable(table));
+        }
+        let dropped = current.iter()
+                             .filter(|table| !matched.contains(&table.complete_name()))
+                             .cloned()
+                             .collect();
+        for table in order_by_foreign_keys(dropped).into_iter().rev() {
+            changes.drop_tables.push(SchemaChange::DropTable(table));
+        }
+        changes.into_vec()
+    }
+
+    fn find_current_table<'a>(&self, current: &'a [Table], desired: &Table) -> Option<&'a Table> {
+        let name = desired.complete_name();
+        let renamed_from = self.table_renames
+                               .iter()
+                               .find(|&&(_, ref to)| *to == name)
+                               .map(|&(ref from, _)| from.to_owned());
+        let name = renamed_from.unwrap_or(name);
+        current.iter().find(|t| t.complete_name() == name)
+    }
+
+    fn find_current_column<'a>(&self, current: &'a Table, desired_table: &Table, desired: &Column) -> Option<&'a Column> {
+        let table_name = desired_table.complete_name();
+        let name = self.column_renames
+                       .iter()
+                       .find(|&&(ref table, _, ref to)| *table == table_name && *to == desired.name)
+                       .map(|&(_, ref from, _)| from.to_owned())
+                       .unwrap_or(desired.name.to_owned());
+        current.columns.iter().find(|c| c.name == name)
+    }
+
+    /// the constraints are dropped from the current table, the rest is applied on the renamed table
+    fn diff_table(&self, db: &Database, changes: &mut Changes, current: &Table, desired: &Table) {
+        let mut matched = vec![];
+        for column in &desired.columns {
+            match self.find_current_column(current, desired, column) {
+                Some(existing) => {
+                    matched.push(existing.name.to_owned());
+                    if existing.name != column.name {
+                        changes.renames.push(SchemaChange::RenameColumn(desired.clone(),
+                                                                        existing.clone(),
+                                                                        column.name.to_owned()));
+                    }
+                    if existing.data_type != column.data_type {
+                        changes.alter_columns.push(SchemaChange::AlterColumnType(desired.clone(), column.clone()));
+                    }
+                    if existing.not_null != column.not_null {
+                        changes.alter_columns
+                               .push(SchemaChange::AlterColumnNullability(desired.clone(), column.clone()));
+                    }
+                    if !same_default(db, existing, column) {
+                        changes.alter_columns
+                               .push(SchemaChange::AlterColumnDefault(desired.clone(), column.clone()));
+                    }
+                    if !same_foreign(existing, column) {
+                        if existing.foreign.is_some() {
+                            changes.drop_constraints
+                                   .push(SchemaChange::DropForeignKey(current.clone(), existing.clone()));
+                        }
+                        if column.foreign.is_some() {
+                            changes.add_constraints
+                                   .push(SchemaChange::AddForeignKey(desired.clone(), column.clone()));
+                        }
+                    }
+                }
+                None => {
+                    changes.alter_columns.push(SchemaChange::AddColumn(desired.clone(), column.clone()));
+                    if column.foreign.is_some() {
+                        changes.add_constraints.push(SchemaChange::AddForeignKey(desired.clone(), column.clone()));
+                    }
+                }
+            }
+        }
+        for column in &current.columns {
+            if !matched.contains(&column.name) {
+                if column.foreign.is_some() {
+                    changes.drop_constraints.push(SchemaChange::DropForeignKey(current.clone(), column.clone()));
+                }
+                changes.drop_columns.push(SchemaChange::DropColumn(desired.clone(), column.clone()));
+            }
+        }
+        for index in &current.indexes {
+            if !desired.indexes.iter().any(|d| same_index(index, d)) {
+                changes.drop_constraints.push(SchemaChange::DropIndex(current.clone(), index.clone()));
+            }
+        }
+        for index in &desired.indexes {
+            if !current.indexes.iter().any(|c| same_index(c, index)) {
+                changes.add_constraints.push(SchemaChange::CreateIndex(desired.clone(), index.clone()));
+            }
+        }
+    }
+}
+
+/// the defaults are compared as the platform writes them,
+/// so the introspected expression matches the value it was created from
+fn same_default(db: &Database, current: &Column, desired: &Column) -> bool {
+    let build = |column: &Column| {
+        match column.default {
+            Some(ref default) => {
+                let mut w = SqlFrag::new(db.sql_options(), BuildMode::Standard);
+                db.build_column_default(&mut w, default).ok().map(|_| w.sql)
+            }
+            None => Some(String::new()),
+        }
+    };
+    match (build(current), build(desired)) {
+        (Some(current), Some(desired)) => current == desired,
+        // can't be written, the change will report the error
+        _ => false,
+    }
+}
+
+/// the constraint name is only known from the current table
+fn same_foreign(current: &Column, desired: &Column) -> bool {
+    match (&current.foreign, &desired.foreign) {
+        (&Some(ref current), &Some(ref desired)) => current.same_reference(desired),
+        (&None, &None) => true,
+        _ => false,
+    }
+}
+
+/// the index method is left out when it is the default, btree on all the platforms
+fn same_index(current: &Index, desired: &Index) -> bool {
+    let method = |index: &Index| {
+        index.method
+             .as_ref()
+             .map(|m| m.to_lowercase())
+             .unwrap_or("btree".to_owned())
+    };
+    current.name == desired.name && current.columns == desired.columns &&
+    current.is_unique == desired.is_unique && method(current) == method(desired) &&
+    current.predicate == desired.predicate && current.expression == desired.expression
+}
@@ -2,11 +2,13 @@ use query::{Filter, Operand};
 use query::Query;
 use table::Table;
 use dao::Dao;
-use database::{Database, DbError};
+use database::{Database, DbError, SqlOption};
 use table::IsTable;
 use dao::IsDao;
 use dao::ToValue;
+use dao::{Value, Type};
 use query::Equality;
+use transaction::Transaction;
 
 /// A higher level API for manipulating objects in the database
 /// This serves as a helper function for the query api
@@ -22,16 +24,13 @@ impl <'a>EntityManager<'a> {
     }
 
     /// delete records of this table
-    pub fn delete(&self, table: &Table, filters: Vec<Filter>) -> usize {
+    pub fn delete(&self, table: &Table, filters: Vec<Filter>) -> Result<usize, DbError> {
         let mut query = Query::delete();
         query.from(table);
         for filter in filters {
             query.add_filter(filter);
         }
-        match query.execute(self.db) {
-            Ok(x) => x,
-            Err(e) => panic!("Error deleting record {}", e),
-        }
+        query.execute(self.db)
     }
 
     /// get all the records of this table
@@ -113,8 +112,10 @@ impl <'a>EntityManager<'a> {
     {
         let table = T::table();
         let primary = table.primary_columns();
-        assert!(primary.len() == 1,
-                "There should only be 1 primary column for this to work");
+        if primary.len() != 1 {
+            return Err(DbError::new(&format!("Table {} should have only 1 primary column for this to work",
+                                             table.complete_name())));
+        }
         let pk = primary[0].name.to_owned();
 
         Query::select_all()
@@ -147,6 +148,42 @@ impl <'a>EntityManager<'a> {
         q.collect_one(self.db)
     }
 
+    /// insert all the records in bulk,
+    /// using multi-row inserts instead of 1 statement per record
+    /// returns the number of inserted records
+    pub fn insert_all<T>(&self, list: &[T]) -> Result<usize, DbError>
+        where T: IsTable + IsDao
+    {
+        if list.is_empty() {
+            return Ok(0);
+        }
+        let table = T::table();
+        let daos: Vec<Dao> = list.iter().map(|t| t.to_dao()).collect();
+        // the columns set in any of the records, the missing ones are inserted as NULL
+        let mut columns: Vec<String> = vec![];
+        for dao in &daos {
+            for key in dao.values.keys() {
+                if !columns.contains(key) {
+                    columns.push(key.to_owned());
+                }
+            }
+        }
+        let mut q = Query::insert();
+        q.into_table(&table.complete_name());
+        for c in &columns {
+            q.column(c);
+        }
+        for dao in &daos {
+            for c in &columns {
+                match dao.values.get(c) {
+                    Some(value) => q.add_value(value),
+                    None => q.add_value(&Value::None(null_type(&table, &daos, c))),
+                };
+            }
+        }
+        q.insert_bulk(self.db)
+    }
+
     /// insert this record on the database, ignoring some columns
     /// which are set by the database default
     /// columns that are ignored are set by the database automatically
@@ -185,67 +222,182 @@ impl <'a>EntityManager<'a> {
         unimplemented!()
     }
 
+    /// begin a transaction, the changes are rolled back when the returned
+    /// transaction is dropped without being committed
+    pub fn transaction(&self) -> Result<Transaction<'a>, DbError> {
+        Transaction::begin(self.db)
+    }
+
     /// this is called when there is a problem with the transaction
-    pub fn reset(&self) {
-        unimplemented!()
+    pub fn reset(&self) -> Result<(), DbError> {
+        self.db.reset()
     }
 
     /// when there is a problem with the transaction process, this can be called
-    pub fn rollback(&self) {
-        unimplemented!()
+    pub fn rollback(&self) -> Result<(), DbError> {
+        if self.db.is_transacted() {
+            self.db.rollback()
+        } else {
+            Ok(())
+        }
     }
 
     /// update the Dao, return the updated Dao
-    pub fn update<T>(&self, _dao: &Dao) -> Result<T, DbError>
+    /// the record is matched using the primary columns of the table
+    pub fn update<T>(&self, dao: &Dao) -> Result<T, DbError>
         where T: IsTable + IsDao
     {
-        unimplemented!()
+        let table = T::table();
+        let columns = non_primary_columns(&table, dao);
+        let filters = try!(primary_filters(&table, dao));
+        self.update_columns(&table, dao_values(dao, columns), filters)
     }
 
     /// update the Dao, return the updated Dao
     /// ignored columns will remain unchanged
     pub fn update_ignore_columns<T>(&self,
-                                    _dao: &Dao,
-                                    _ignore_columns: Vec<&str>)
+                                    dao: &Dao,
+                                    ignore_columns: Vec<&str>)
                                     -> Result<T, DbError>
         where T: IsTable + IsDao
     {
-        unimplemented!()
+        let table = T::table();
+        let columns = non_primary_columns(&table, dao).into_iter()
+                          .filter(|c| !ignore_columns.contains(&&c[..]))
+                          .collect();
+        let filters = try!(primary_filters(&table, dao));
+        self.update_columns(&table, dao_values(dao, columns), filters)
     }
 
     /// update the Dao, return the updated Dao
     /// only the columns specified, the rest is unchanged
-    pub fn update_only_columns<T>(&self, _dao: &Dao, _columns: Vec<&str>) -> Result<T, DbError>
+    pub fn update_only_columns<T>(&self, dao: &Dao, columns: Vec<&str>) -> Result<T, DbError>
         where T: IsTable + IsDao
     {
-        unimplemented!()
+        let table = T::table();
+        let columns = non_primary_columns(&table, dao).into_iter()
+                          .filter(|c| columns.contains(&&c[..]))
+                          .collect();
+        let filters = try!(primary_filters(&table, dao));
+        self.update_columns(&table, dao_values(dao, columns), filters)
     }
 
     /// update the Dao, return the updated Dao
     /// the default columns will be reset to whatever the db's default function will come up.
     /// ie. updated column will be defaulted everytime a record is updated.
-    pub fn update_ignore_defaulted_columns<T>(&self, _dao: &Dao) -> Result<T, DbError>
+    pub fn update_ignore_defaulted_columns<T>(&self, dao: &Dao) -> Result<T, DbError>
         where T: IsTable + IsDao
     {
-        unimplemented!()
+        let table = T::table();
+        let mut values = vec![];
+        for c in &table.columns {
+            if c.is_primary {
+                continue;
+            }
+            match c.default {
+                Some(ref default) => values.push((c.name.to_owned(), default.clone())),
+                None => {
+                    if let Some(value) = dao.values.get(&c.name) {
+                        values.push((c.name.to_owned(), Operand::Value(value.clone())));
+                    }
+                }
+            }
+        }
+        let filters = try!(primary_filters(&table, dao));
+        self.update_columns(&table, values, filters)
     }
 
     /// update the Dao with filter, return the updated Dao
-    pub fn update_with_filter<T>(&self, _dao: &Dao, _filter: Vec<Filter>) -> Result<T, DbError>
+    /// the primary columns are not updated,
+    /// when the filter matches multiple records, only the first one is returned
+    pub fn update_with_filter<T>(&self, dao: &Dao, filter: Vec<Filter>) -> Result<T, DbError>
         where T: IsTable + IsDao
     {
-        unimplemented!()
+        let table = T::table();
+        let columns = non_primary_columns(&table, dao);
+        self.update_columns(&table, dao_values(dao, columns), filter)
+    }
+
+    /// set the columns to the values on the records that matched the filters
+    fn update_columns<T>(&self,
+                         table: &Table,
+                         values: Vec<(String, Operand)>,
+                         filters: Vec<Filter>)
+                         -> Result<T, DbError>
+        where T: IsTable + IsDao
+    {
+        if values.is_empty() {
+            return Err(DbError::new("There are no columns to update"));
+        }
+        let filters_updated = filters.iter().any(|f| values.iter().any(|&(ref c, _)| f.uses_column(c)));
+        let mut q = Query::update();
+        q.from(table);
+        for (column, value) in values {
+            q.column(&column);
+            q.add_value_operand(value);
+        }
+        q.add_filters(filters.clone());
+        q.return_all();
+        q.finalize();
+        if self.db.sql_options().contains(&SqlOption::SupportsReturningClause) || !filters_updated {
+            let updated = try!(self.db.update(&q));
+            return Ok(T::from_dao(&updated));
+        }
+        // the filters no longer match once their columns are updated,
+        // the record is selected again by the primary key it had before the update
+        let mut matched = Query::select();
+        matched.from(table);
+        for p in table.primary_columns() {
+            matched.column(&p.name);
+        }
+        matched.add_filters(filters);
+        let primary = match try!(matched.retrieve_one(self.db)) {
+            Some(record) => try!(primary_filters(table, &record)),
+            None => return Err(DbError::new("No record updated")),
+        };
+        if try!(q.execute(self.db)) == 0 {
+            return Err(DbError::new("No record updated"));
+        }
+        let mut select = Query::select_all();
+        select.from(table);
+        select.add_filters(primary);
+        select.collect_one(self.db)
     }
 
     /// whether to use insert or update
     /// insert when it is a new record
-    /// update when it is an existing recor
-    /// may use UPSERT in newer versions of postgres
+    /// update when it is an existing record, matched by the primary columns
+    /// uses the upsert of the database platform in a single statement
     /// may use MERGE in oracle, mssql
-    pub fn save<T>(&self, _dao: T) -> Result<T, DbError>
+    pub fn save<T>(&self, t: &T) -> Result<T, DbError>
         where T: IsTable + IsDao
     {
-        unimplemented!()
+        let table = T::table();
+        let dao = t.to_dao();
+        let filters = try!(primary_filters(&table, &dao));
+        let primary: Vec<String> = table.primary_columns().iter().map(|c| c.name.to_owned()).collect();
+        let update_columns = non_primary_columns(&table, &dao);
+        let mut q = Query::insert();
+        q.into_table(&table.complete_name());
+        for (column, value) in &dao.values {
+            q.set_value(column, value);
+        }
+        q.on_conflict(primary.iter().map(|c| &c[..]).collect(),
+                      update_columns.iter().map(|c| &c[..]).collect());
+        q.return_all();
+        if self.db.sql_options().contains(&SqlOption::SupportsReturningClause) {
+            let result = try!(q.retrieve(self.db));
+            if let Some(saved) = try!(result.cast_one()) {
+                return Ok(saved);
+            }
+        } else {
+            try!(q.execute(self.db));
+        }
+        // no returning clause, or nothing is returned when nothing is updated
+        let mut select = Query::select_all();
+        select.from(&table);
+        select.add_filters(filters);
+        select.collect_one(self.db)
     }
 
     ///
@@ -259,3 +411,53 @@ impl <'a>EntityManager<'a> {
     }
 
 }
+
+/// the columns of the dao which are not part of the primary key of the table
+fn non_primary_columns(table: &Table, dao: &Dao) -> Vec<String> {
+    dao.values
+       .keys()
+       .filter(|c| !table.is_primary(c))
+       .cloned()
+       .collect()
+}
+
+/// the values of the columns in the dao
+fn dao_values(dao: &Dao, columns: Vec<String>) -> Vec<(String, Operand)> {
+    columns.into_iter()
+           .filter_map(|c| dao.values.get(&c).map(|v| (c.to_owned(), Operand::Value(v.clone()))))
+           .collect()
+}
+
+/// filter for matching the record using the values of the primary columns in the dao
+fn primary_filters(table: &Table, dao: &Dao) -> Result<Vec<Filter>, DbError> {
+    let primary = table.primary_columns();
+    if primary.is_empty() {
+        return Err(DbError::new(&format!("Table {} has no primary column to match the record",
+                                         table.complete_name())));
+    }
+    let mut filters = vec![];
+    for p in primary {
+        match dao.values.get(&p.name) {
+            Some(value) => filters.push(Filter::with_value(&p.name, Equality::EQ, value.clone())),
+            None => {
+                return Err(DbError::new(&format!("No value for primary column {}", p.name)));
+            }
+        }
+    }
+    Ok(filters)
+}
+
+/// the type of the NULL inserted for a column missing in a record,
+/// taken from the table column, otherwise from the value set in the other records
+fn null_type(table: &Table, daos: &[Dao], column: &str) -> Type {
+    match table.get_column(column) {
+        Some(c) => c.data_type,
+        None => {
+            daos.iter()
+                .filter_map(|dao| dao.values.get(column))
+                .map(|v| v.get_type())
+                .next()
+                .unwrap_or(Type::String)
+        }
+    }
+}
@@ -55,7 +55,8 @@
 //!             sub_table:vec![],
 //!             comment:None,
 //!             columns:vec![],
-//!             is_view: false
+//!             is_view: false,
+//!             indexes: vec![],
 //!         }
 //!     }
 //! }
@@ -77,6 +78,7 @@
 //!
 
 extern crate rustc_serialize;
+#[macro_use]
 extern crate postgres;
 #[cfg(feature = "sqlite")]
 extern crate rusqlite;
@@ -85,12 +87,16 @@ extern crate mysql;
 extern crate uuid;
 extern crate chrono;
 extern crate regex;
+#[macro_use]
+extern crate lazy_static;
 extern crate url;
 extern crate r2d2;
 extern crate r2d2_postgres;
 #[cfg(feature = "sqlite")]
 extern crate r2d2_sqlite;
 extern crate time;
+#[macro_use]
+extern crate log;
 
 
 pub mod em;
@@ -102,3 +108,10 @@ pub mod table;
 pub mod writer;
 pub mod config;
 pub mod pool;
+pub mod transaction;
+pub mod stream;
+pub mod logger;
+pub mod dialect;
+pub mod diff;
+pub mod migration;
+pub mod copy;
@@ -0,0 +1,158 @@
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use dao::{Dao, DaoResult, Value};
+use database::DbError;
+
+/// what is known about an executed statement
+#[derive(Debug)]
+pub struct QueryEvent<'a> {
+    pub sql: &'a str,
+    /// None when the parameters are redacted
+    pub params: Option<&'a [Value]>,
+    pub elapsed: Duration,
+    /// the number of records returned or affected, None when it failed
+    pub rows: Option<usize>,
+    pub error: Option<&'a DbError>,
+    /// whether it took longer than the slow query threshold
+    pub slow: bool,
+}
+
+/// receives every statement executed on a database,
+/// i.e: to collect metrics or report the slow queries
+pub trait QueryLogger: Send + Sync {
+    fn log_query(&self, event: &QueryEvent);
+}
+
+/// the number of records of a statement result
+pub trait RowCount {
+    fn row_count(&self) -> usize;
+}
+
+impl RowCount for usize {
+    fn row_count(&self) -> usize {
+        *self
+    }
+}
+
+impl RowCount for u64 {
+    fn row_count(&self) -> usize {
+        *self as usize
+    }
+}
+
+impl RowCount for Vec<Dao> {
+    fn row_count(&self) -> usize {
+        self.len()
+    }
+}
+
+impl RowCount for DaoResult {
+    fn row_count(&self) -> usize {
+        self.dao.len()
+    }
+}
+
+/// How the statements executed on a database are logged.
+///
+/// The sql is logged at `debug` level and the parameters at `trace` level through the `log` crate,
+/// statements slower than the threshold are logged at `warn` level.
+/// The logger, when set, receives each of the statements.
+///
+/// ```ignore
+/// let mut log = QueryLog::new();
+/// log.redact_params(true)
+///    .slow_threshold(Duration::from_millis(500))
+///    .logger(Arc::new(MetricsLogger));
+/// let db = try!(pool.connect_with_log(&log));
+/// ```
+#[derive(Clone)]
+pub struct QueryLog {
+    logger: Option<Arc<QueryLogger>>,
+    redact_params: bool,
+    slow_threshold: Option<Duration>,
+}
+
+impl Default for QueryLog {
+    fn default() -> Self {
+        QueryLog::new()
+    }
+}
+
+impl QueryLog {
+    pub fn new() -> Self {
+        QueryLog {
+            logger: None,
+            redact_params: false,
+            slow_threshold: None,
+        }
+    }
+
+    /// the observer receiving each executed statement
+    pub fn logger(&mut self, logger: Arc<QueryLogger>) -> &mut Self {
+        self.logger = Some(logger);
+        self
+    }
+
+    /// don't log the parameters, which may contain sensitive values
+    pub fn redact_params(&mut self, redact: bool) -> &mut Self {
+        self.redact_params = redact;
+        self
+    }
+
+    /// statements taking longer than this are considered slow
+    pub fn slow_threshold(&mut self, threshold: Duration) -> &mut Self {
+        self.slow_threshold = Some(threshold);
+        self
+    }
+
+    pub fn is_redacted(&self) -> bool {
+        self.redact_params
+    }
+
+    /// run the statement, then log it with the time it took and the outcome
+    pub fn timed<T, F>(&self, sql: &str, params: &[Value], run: F) -> Result<T, DbError>
+        where T: RowCount,
+              F: FnOnce() -> Result<T, DbError>
+    {
+        debug!("SQL: \n{}", sql);
+        if !self.redact_params {
+            trace!("params: {:?}", params);
+        }
+        let start = Instant::now();
+        let result = run();
+        let elapsed = start.elapsed();
+        let slow = match self.slow_threshold {
+            Some(threshold) => elapsed >= threshold,
+            None => false,
+        };
+        {
+            let (rows, error) = match result {
+                Ok(ref ret) => (Some(ret.row_count()), None),
+                Err(ref e) => (None, Some(e)),
+            };
+            if slow {
+                warn!("slow query took {} ms: {}", millis(elapsed), sql);
+            }
+            match error {
+                Some(e) => debug!("failed after {} ms: {}", millis(elapsed), e),
+                None => debug!("{} rows in {} ms", rows.unwrap_or(0), millis(elapsed)),
+            }
+            if let Some(ref logger) = self.logger {
+                let event = QueryEvent {
+                    sql: sql,
+                    params: if self.redact_params { None } else { Some(params) },
+                    elapsed: elapsed,
+                    rows: rows,
+                    error: error,
+                    slow: slow,
+                };
+                logger.log_query(&event);
+            }
+        }
+        result
+    }
+}
+
+fn millis(duration: Duration) -> u64 {
+    duration.as_secs() * 1000 + (duration.subsec_nanos() / 1_000_000) as u64
+}
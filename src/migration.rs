@@ -0,0 +1,507 @@
+use std::fmt;
+use std::fs;
+use std::io::Read;
+use std::path::Path;
+use dao::{Dao, Value};
+use database::{Database, DbError, SqlOption};
+use query::Query;
+use transaction::Transaction;
+use writer::quoted_len;
+
+/// the table where the applied migrations are recorded
+pub const MIGRATIONS_TABLE: &'static str = "schema_migrations";
+
+/// what a migration executes
+enum Step {
+    /// the statements are separated with `;`
+    Sql(String),
+    Code(Box<Fn(&Database) -> Result<(), DbError>>),
+}
+
+impl Step {
+    fn run(&self, db: &Database) -> Result<(), DbError> {
+        match *self {
+            Step::Sql(ref sql) => {
+                for statement in split_statements(sql) {
+                    try!(db.execute_sql(&statement, &[]));
+                }
+                Ok(())
+            }
+            Step::Code(ref code) => code(db),
+        }
+    }
+}
+
+/// A versioned change of the database, applied with `up` and reverted with `down`.
+///
+/// The checksum of the sql is recorded, so a migration edited after it was applied is detected.
+/// The closures can not be checksummed, the checksum of the revision given with the code is recorded instead.
+pub struct Migration {
+    pub version: u64,
+    pub name: String,
+    up: Step,
+    down: Option<Step>,
+    checksum: String,
+}
+
+impl Migration {
+
+    /// a migration executing the sql statements
+    pub fn sql(version: u64, name: &str, up: &str) -> Self {
+        Migration {
+            version: version,
+            name: name.to_owned(),
+            up: Step::Sql(up.to_owned()),
+            down: None,
+            checksum: checksum(up),
+        }
+    }
+
+    /// a migration done in rust code,
+    /// the revision has to be changed whenever the code is edited, i.e: `"v1"` or a commit hash
+    pub fn code<F>(version: u64, name: &str, revision: &str, up: F) -> Self
+        where F: Fn(&Database) -> Result<(), DbError> + 'static
+    {
+        Migration {
+            version: version,
+            name: name.to_owned(),
+            up: Step::Code(Box::new(up)),
+            down: None,
+            checksum: checksum(revision),
+        }
+    }
+
+    /// the sql statements reverting the migration
+    pub fn down_sql(&mut self, down: &str) -> &mut Self {
+        self.down = Some(Step::Sql(down.to_owned()));
+        self
+    }
+
+    /// the code reverting the migration
+    pub fn down_code<F>(&mut self, down: F) -> &mut Self
+        where F: Fn(&Database) -> Result<(), DbError> + 'static
+    {
+        self.down = Some(Step::Code(Box::new(down)));
+        self
+    }
+
+    pub fn checksum(&self) -> &str {
+        &self.checksum
+    }
+
+    pub fn is_reversible(&self) -> bool {
+        self.down.is_some()
+    }
+}
+
+impl fmt::Debug for Migration {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Migration {{ version: {}, name: {:?} }}", self.version, self.name)
+    }
+}
+
+/// a migration recorded in the migrations table
+#[derive(Debug, Clone, PartialEq)]
+pub struct AppliedMigration {
+    pub version: u64,
+    pub name: String,
+    pub checksum: String,
+}
+
+/// Applies the migrations in the order of their version, on postgresql, sqlite or mysql.
+///
+/// Each migration runs in its own transaction when the schema changes of the database can be rolled back,
+/// mysql commits the schema changes as they are executed.
+///
+/// ```ignore
+/// let migrator = try!(Migrator::from_dir("migrations"));
+/// let applied = try!(migrator.up(platform.as_ref()));
+/// ```
+pub struct Migrator {
+    migrations: Vec<Migration>,
+}
+
+impl Migrator {
+
+    pub fn new() -> Self {
+        Migrator { migrations: vec![] }
+    }
+
+    /// the sql files of the directory, named with the version and the name of the migration:
+    /// `0001_create_product.up.sql` and its optional `0001_create_product.down.sql`,
+    /// or `0001_create_product.sql` when it can not be reverted
+    pub fn from_dir<P: AsRef<Path>>(dir: P) -> Result<Self, DbError> {
+        let entries = try!(fs::read_dir(dir.as_ref()).map_err(|e| io_error(dir.as_ref(), e)));
+        let mut files = vec![];
+        for entry in entries {
+            let path = try!(entry.map_err(|e| io_error(dir.as_ref(), e))).path();
+            if let Some(file) = path.file_name().and_then(|f| f.to_str()).and_then(parse_file_name) {
+                files.push((file, path.clone()));
+            }
+        }
+        files.sort_by(|a, b| a.0.cmp(&b.0));
+
+        let mut migrator = Migrator::new();
+        let mut downs = vec![];
+        for ((version, name, is_down), path) in files {
+            let sql = try!(read_file(&path));
+            if is_down {
+                downs.push((version, sql));
+            } else {
+                migrator.add(Migration::sql(version, &name, &sql));
+            }
+        }
+        for (version, sql) in downs {
+            match migrator.migrations.iter_mut().find(|m| m.version == version) {
+                Some(migration) => {
+                    migration.down_sql(&sql);
+                }
+                None => {
+                    return Err(DbError::new(&format!("Migration {} has a down but no up file", version)));
+                }
+            }
+        }
+        Ok(migrator)
+    }
+
+    /// the migrations are kept in the order of their version
+    pub fn add(&mut self, migration: Migration) -> &mut Self {
+        let position = self.migrations
+                           .iter()
+                           .position(|m| m.version > migration.version)
+                           .unwrap_or(self.migrations.len());
+        self.migrations.insert(position, migration);
+        self
+    }
+
+    pub fn migrations(&self) -> &[Migration] {
+        &self.migrations
+    }
+
+    /// the migrations recorded in the migrations table, in the order of their version
+    pub fn applied(&self, db: &Database) -> Result<Vec<AppliedMigration>, DbError> {
+        try!(self.create_migrations_table(db));
+        let result = try!(Query::select()
+                              .columns(vec!["version", "name", "checksum"])
+                              .from_table(MIGRATIONS_TABLE)
+                              .asc("version")
+                              .retrieve(db));
+        let mut applied = vec![];
+        for dao in result.dao {
+            applied.push(AppliedMigration {
+                version: try!(version_of(&dao)),
+                name: try!(dao.try_get("name")),
+                checksum: try!(dao.try_get("checksum")),
+            });
+        }
+        Ok(applied)
+    }
+
+    /// the migrations not yet applied, an error when the applied ones don't match the migrations
+    pub fn pending(&self, db: &Database) -> Result<Vec<&Migration>, DbError> {
+        let applied = try!(self.applied(db));
+        try!(self.verify(&applied));
+        Ok(self.migrations
+               .iter()
+               .filter(|m| !applied.iter().any(|a| a.version == m.version))
+               .collect())
+    }
+
+    /// apply all the pending migrations, returns the versions applied
+    pub fn up(&self, db: &Database) -> Result<Vec<u64>, DbError> {
+        self.up_to(db, u64::max_value())
+    }
+
+    /// apply the pending migrations up to and including the version
+    pub fn up_to(&self, db: &Database, version: u64) -> Result<Vec<u64>, DbError> {
+        let mut applied = vec![];
+        for migration in try!(self.pending(db)) {
+            if migration.version > version {
+                break;
+            }
+            info!("applying migration {} {}", migration.version, migration.name);
+            try!(self.in_transaction(db, || {
+                // recorded first, so a concurrent migrator applying the same version
+                // fails on the primary key instead of running the migration twice
+                if let Err(e) = self.record(db, migration) {
+                    return Err(DbError::new(&format!("Migration {} {} is already being applied: {}",
+                                                     migration.version,
+                                                     migration.name,
+                                                     e)));
+                }
+                if let Err(e) = migration.up.run(db) {
+                    if !db.sql_options().contains(&SqlOption::SupportsTransactionalDDL) {
+                        try!(self.unrecord(db, migration.version));
+                    }
+                    return Err(e);
+                }
+                Ok(())
+            }));
+            applied.push(migration.version);
+        }
+        Ok(applied)
+    }
+
+    /// revert the last applied migration, returns its version, None when nothing is applied
+    pub fn down(&self, db: &Database) -> Result<Option<u64>, DbError> {
+        let applied = try!(self.applied(db));
+        try!(self.verify(&applied));
+        match applied.last() {
+            Some(last) => {
+                try!(self.revert(db, last));
+                Ok(Some(last.version))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// revert the applied migrations newer than the version, the latest first, returns the versions reverted
+    pub fn down_to(&self, db: &Database, version: u64) -> Result<Vec<u64>, DbError> {
+        let applied = try!(self.applied(db));
+        try!(self.verify(&applied));
+        let mut reverted = vec![];
+        for a in applied.iter().rev().filter(|a| a.version > version) {
+            try!(self.revert(db, a));
+            reverted.push(a.version);
+        }
+        Ok(reverted)
+    }
+
+    /// the applied migration has been verified to be one of the migrations
+    fn revert(&self, db: &Database, applied: &AppliedMigration) -> Result<(), DbError> {
+        let migration = self.migrations.iter().find(|m| m.version == applied.version).unwrap();
+        let down = match migration.down {
+            Some(ref down) => down,
+            None => {
+                return Err(DbError::new(&format!("Migration {} {} can not be reverted",
+                                                 migration.version,
+                                                 migration.name)))
+            }
+        };
+        info!("reverting migration {} {}", migration.version, migration.name);
+        self.in_transaction(db, || {
+            // removed first, a concurrent migrator reverting the same version finds no record
+            if try!(self.unrecord(db, migration.version)) == 0 {
+                return Err(DbError::new(&format!("Migration {} {} is already being reverted",
+                                                 migration.version,
+                                                 migration.name)));
+            }
+            if let Err(e) = down.run(db) {
+                if !db.sql_options().contains(&SqlOption::SupportsTransactionalDDL) {
+                    try!(self.record(db, migration));
+                }
+                return Err(e);
+            }
+            Ok(())
+        })
+    }
+
+    /// record the migration as applied
+    fn record(&self, db: &Database, migration: &Migration) -> Result<usize, DbError> {
+        Query::insert()
+            .into_table(MIGRATIONS_TABLE)
+            .set("version", &(migration.version as i64))
+            .set("name", &migration.name)
+            .set("checksum", &migration.checksum)
+            .execute(db)
+    }
+
+    /// remove the record of the migration, returns the number of records removed
+    fn unrecord(&self, db: &Database, version: u64) -> Result<usize, DbError> {
+        Query::delete()
+            .from_table(MIGRATIONS_TABLE)
+            .filter_eq("version", &(version as i64))
+            .execute(db)
+    }
+
+    /// the versions have to be unique, the applied migrations have to be unchanged,
+    /// and no pending migration can be older than the last applied one
+    pub fn verify(&self, applied: &[AppliedMigration]) -> Result<(), DbError> {
+        for pair in self.migrations.windows(2) {
+            if pair[0].version == pair[1].version {
+                return Err(DbError::new(&format!("Migrations {} and {} have the same version {}",
+                                                 pair[0].name,
+                                                 pair[1].name,
+                                                 pair[0].version)));
+            }
+        }
+        for a in applied {
+            match self.migrations.iter().find(|m| m.version == a.version) {
+                Some(migration) => {
+                    if migration.checksum != a.checksum {
+                        return Err(DbError::new(&format!("Migration {} {} was edited after it was applied",
+                                                         migration.version,
+                                                         migration.name)));
+                    }
+                }
+                None => {
+                    return Err(DbError::new(&format!("Applied migration {} {} is missing",
+                                                     a.version,
+                                                     a.name)));
+                }
+            }
+        }
+        if let Some(last) = applied.last() {
+            let older = self.migrations
+                            .iter()
+                            .find(|m| m.version < last.version && !applied.iter().any(|a| a.version == m.version));
+            if let Some(older) = older {
+                return Err(DbError::new(&format!("Migration {} {} is older than the last applied migration {}",
+                                                 older.version,
+                                                 older.name,
+                                                 last.version)));
+            }
+        }
+        Ok(())
+    }
+
+    fn create_migrations_table(&self, db: &Database) -> Result<(), DbError> {
+        let sql = format!("CREATE TABLE IF NOT EXISTS {} (
+                version bigint PRIMARY KEY,
+                name varchar(255) NOT NULL,
+                checksum varchar(64) NOT NULL,
+                applied_at timestamp DEFAULT CURRENT_TIMESTAMP
+            )",
+                          try!(db.quote_identifier(MIGRATIONS_TABLE)));
+        try!(db.execute_sql(&sql, &[]));
+        Ok(())
+    }
+
+    /// the migration and its record are committed together, or not at all
+    fn in_transaction<F>(&self, db: &Database, f: F) -> Result<(), DbError>
+        where F: Fn() -> Result<(), DbError>
+    {
+        let transacted = db.sql_options().contains(&SqlOption::SupportsTransactionalDDL);
+        if !transacted {
+            return f();
+        }
+        // rolled back when dropped on an error
+        let tx = try!(Transaction::begin(db));
+        try!(f());
+        tx.commit()
+    }
+}
+
+/// the version, name and whether it is the down migration, None when it is not a migration file
+fn parse_file_name(file: &str) -> Option<(u64, String, bool)> {
+    if !file.ends_with(".sql") {
+        return None;
+    }
+    let stem = &file[..file.len() - 4];
+    let (stem, is_down) = if stem.ends_with(".down") {
+        (&stem[..stem.len() - 5], true)
+    } else if stem.ends_with(".up") {
+        (&stem[..stem.len() - 3], false)
+    } else {
+        (stem, false)
+    };
+    let mut parts = stem.splitn(2, '_');
+    let version = match parts.next().and_then(|v| v.parse::<u64>().ok()) {
+        Some(version) => version,
+        None => return None,
+    };
+    let name = parts.next().unwrap_or("").to_owned();
+    Some((version, name, is_down))
+}
+
+fn read_file(path: &Path) -> Result<String, DbError> {
+    let mut file = try!(fs::File::open(path).map_err(|e| io_error(path, e)));
+    let mut sql = String::new();
+    try!(file.read_to_string(&mut sql).map_err(|e| io_error(path, e)));
+    Ok(sql)
+}
+
+fn io_error(path: &Path, e: ::std::io::Error) -> DbError {
+    DbError::new(&format!("Unable to read migrations {}: {}", path.display(), e))
+}
+
+/// the version is an i64 in postgresql and sqlite, but may be narrowed by the other drivers
+fn version_of(dao: &Dao) -> Result<u64, DbError> {
+    match dao.values.get("version") {
+        Some(&Value::I64(v)) => Ok(v as u64),
+        Some(&Value::I32(v)) => Ok(v as u64),
+        Some(&Value::U64(v)) => Ok(v),
+        Some(&Value::U32(v)) => Ok(v as u64),
+        other => Err(DbError::new(&format!("Unexpected migration version {:?}", other))),
+    }
+}
+
+/// FNV-1a, a checksum which stays the same across rust releases, unlike the std hasher
+fn checksum(sql: &str) -> String {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in sql.bytes() {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    format!("{:016x}", hash)
+}
+
+/// split the sql into statements at the `;`,
+/// except in quotes, comments and the `$$` quoted function bodies of postgresql.
+/// The comments are kept with the statement following them, a trailing comment is left out
+fn split_statements(sql: &str) -> Vec<String> {
+    let mut statements = vec![];
+    let mut current = String::new();
+    let mut has_code = false;
+    let mut i = 0;
+    while i < sql.len() {
+        let rest = &sql[i..];
+        let c = rest.chars().next().unwrap();
+        let skip = quoted_len(rest);
+        if skip > 0 {
+            if !rest.starts_with("--") && !rest.starts_with("/*") {
+                has_code = true;
+            }
+            current.push_str(&rest[..skip]);
+            i += skip;
+            continue;
+        }
+        if c == ';' {
+            if has_code {
+                statements.push(current.trim().to_owned());
+            }
+            current.clear();
+            has_code = false;
+        } else {
+            if !c.is_whitespace() {
+                has_code = true;
+            }
+            current.push(c);
+        }
+        i += c.len_utf8();
+    }
+    if has_code {
+        statements.push(current.trim().to_owned());
+    }
+    statements
+}
+
+#[test]
+fn test_split_statements() {
+    let sql = "
+CREATE TABLE product(name text DEFAULT 'a;b'); -- the product; sold
+CREATE FUNCTION touch() RETURNS trigger AS $body$
+BEGIN
+    NEW.updated = now();
+    RETURN NEW;
+END;
+$body$ LANGUAGE plpgsql;
+/* done; */";
+    let statements = split_statements(sql);
+    assert_eq!(statements.len(), 2);
+    assert_eq!(statements[0], "CREATE TABLE product(name text DEFAULT 'a;b')");
+    assert!(statements[1].starts_with("-- the product; sold\nCREATE FUNCTION"));
+    assert!(statements[1].ends_with("$body$ LANGUAGE plpgsql"));
+    assert_eq!(split_statements("SELECT $1, 'it''s';"), vec!["SELECT $1, 'it''s'".to_owned()]);
+}
+
+#[test]
+fn test_parse_file_name() {
+    assert_eq!(parse_file_name("0001_create_product.up.sql"),
+               Some((1, "create_product".to_owned(), false)));
+    assert_eq!(parse_file_name("0001_create_product.down.sql"),
+               Some((1, "create_product".to_owned(), true)));
+    assert_eq!(parse_file_name("20160125_seed.sql"), Some((20160125, "seed".to_owned(), false)));
+    assert_eq!(parse_file_name("README.md"), None);
+    assert_eq!(parse_file_name("notes.sql"), None);
+}
@@ -0,0 +1,274 @@
+use std::cell::{Cell, Ref, RefCell};
+use dao::{Dao, Value};
+use database::{Database, DbError};
+use logger::QueryLog;
+use dialect::Dialect;
+
+/// a statement executed on the mock database
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExecutedStatement {
+    pub sql: String,
+    pub params: Vec<Value>,
+}
+
+/// the scripted outcome of an expected statement
+#[derive(Debug)]
+pub enum MockResponse {
+    /// the records returned by a select, or by a statement with a returning clause
+    Rows(Vec<Dao>),
+    /// the number of affected records
+    Affected(usize),
+    Error(DbError),
+}
+
+struct Expectation {
+    /// the normalized sql, None matches any statement
+    sql: Option<String>,
+    response: MockResponse,
+}
+
+/// An in-memory database for unit tests, no server is needed.
+///
+/// The sql is written in the postgresql dialect unless set otherwise, the executed statements are recorded
+/// and answered with the scripted responses in the order they are expected.
+/// The sql of the expectations and assertions is compared with the whitespaces collapsed.
+/// Statements which are not expected return no records and affect no records.
+///
+/// ```ignore
+/// let mut db = MockDatabase::new();
+/// db.expect_rows("SELECT * FROM \"bazaar\".\"product\"", vec![product.to_dao()]);
+/// let products: Vec<Product> = try!(EntityManager::new(&db).get_all());
+/// db.assert_executed("SELECT * FROM \"bazaar\".\"product\"", &[]);
+/// ```
+pub struct MockDatabase {
+    executed: RefCell<Vec<ExecutedStatement>>,
+    expectations: RefCell<Vec<Expectation>>,
+    in_transaction: Cell<bool>,
+    query_log: QueryLog,
+    dialect: Dialect,
+}
+
+impl MockDatabase {
+    pub fn new() -> Self {
+        MockDatabase {
+            executed: RefCell::new(vec![]),
+            expectations: RefCell::new(vec![]),
+            in_transaction: Cell::new(false),
+            query_log: QueryLog::new(),
+            dialect: Dialect::postgres(),
+        }
+    }
+
+    /// answer the next statement matching the sql with the response
+    pub fn expect(&mut self, sql: &str, response: MockResponse) -> &mut Self {
+        self.expectations.borrow_mut().push(Expectation {
+            sql: Some(normalize(sql)),
+            response: response,
+        });
+        self
+    }
+
+    /// answer the next statement, whatever the sql is, with the response
+    pub fn expect_any(&mut self, response: MockResponse) -> &mut Self {
+        self.expectations.borrow_mut().push(Expectation {
+            sql: None,
+            response: response,
+        });
+        self
+    }
+
+    /// the statement returns the records
+    pub fn expect_rows(&mut self, sql: &str, rows: Vec<Dao>) -> &mut Self {
+        self.expect(sql, MockResponse::Rows(rows))
+    }
+
+    /// the statement affects n records
+    pub fn expect_affected(&mut self, sql: &str, affected: usize) -> &mut Self {
+        self.expect(sql, MockResponse::Affected(affected))
+    }
+
+    /// the statement fails with the error
+    pub fn expect_error(&mut self, sql: &str, error: DbError) -> &mut Self {
+        self.expect(sql, MockResponse::Error(error))
+    }
+
+    /// set how the executed statements are logged
+    pub fn set_query_log(&mut self, query_log: QueryLog) -> &mut Self {
+        self.query_log = query_log;
+        self
+    }
+
+    /// write the sql of another platform, postgresql is used by default
+    pub fn set_dialect(&mut self, dialect: Dialect) -> &mut Self {
+        self.dialect = dialect;
+        self
+    }
+
+    /// the statements executed so far, in order
+    pub fn executed(&self) -> Ref<Vec<ExecutedStatement>> {
+        self.executed.borrow()
+    }
+
+    /// forget the executed statements and the remaining expectations
+    pub fn clear(&self) {
+        self.executed.borrow_mut().clear();
+        self.expectations.borrow_mut().clear();
+    }
+
+    /// panics when no statement was executed with the sql and parameters
+    pub fn assert_executed(&self, sql: &str, params: &[Value]) {
+        let sql = normalize(sql);
+        let executed = self.executed.borrow();
+        let found = executed.iter().any(|s| normalize(&s.sql) == sql && s.params == params);
+        if !found {
+            panic!("expected statement was not executed:\n  {} {:?}\nexecuted:\n{}",
+                   sql,
+                   params,
+                   describe(&executed));
+        }
+    }
+
+    /// panics when no statement was executed with the sql, whatever the parameters are
+    pub fn assert_executed_sql(&self, sql: &str) {
+        let sql = normalize(sql);
+        let executed = self.executed.borrow();
+        if !executed.iter().any(|s| normalize(&s.sql) == sql) {
+            panic!("expected statement was not executed:\n  {}\nexecuted:\n{}",
+                   sql,
+                   describe(&executed));
+        }
+    }
+
+    /// panics when some of the expected statements were not executed
+    pub fn assert_expectations_met(&self) {
+        let expectations = self.expectations.borrow();
+        if !expectations.is_empty() {
+            let remaining: Vec<String> = expectations.iter()
+                                                     .map(|e| {
+                                                         e.sql
+                                                          .clone()
+                                                          .unwrap_or("<any statement>".to_owned())
+                                                     })
+                                                     .collect();
+            panic!("expected statements were not executed:\n  {}",
+                   remaining.join("\n  "));
+        }
+    }
+
+    /// record the statement, and take the response of the first expectation matching it
+    fn respond(&self, sql: &str, params: &[Value]) -> Option<MockResponse> {
+        self.executed.borrow_mut().push(ExecutedStatement {
+            sql: sql.to_owned(),
+            params: params.to_vec(),
+        });
+        let normalized = normalize(sql);
+        let mut expectations = self.expectations.borrow_mut();
+        let position = expectations.iter().position(|e| {
+            match e.sql {
+                Some(ref expected) => *expected == normalized,
+                None => true,
+            }
+        });
+        position.map(|i| expectations.remove(i).response)
+    }
+}
+
+impl Database for MockDatabase {
+    fn version(&self) -> Result<String, DbError> {
+        Ok("mock".to_owned())
+    }
+
+    fn begin(&self) -> Result<(), DbError> {
+        try!(self.execute_sql("BEGIN", &[]));
+        self.in_transaction.set(true);
+        Ok(())
+    }
+
+    fn commit(&self) -> Result<(), DbError> {
+        self.in_transaction.set(false);
+        try!(self.execute_sql("COMMIT", &[]));
+        Ok(())
+    }
+
+    fn rollback(&self) -> Result<(), DbError> {
+        self.in_transaction.set(false);
+        try!(self.execute_sql("ROLLBACK", &[]));
+        Ok(())
+    }
+
+    fn is_transacted(&self) -> bool {
+        self.in_transaction.get()
+    }
+
+    fn is_closed(&self) -> bool {
+        false
+    }
+
+    fn is_connected(&self) -> bool {
+        true
+    }
+
+    fn close(&self) {
+    }
+
+    fn is_valid(&self) -> bool {
+        true
+    }
+
+    fn reset(&self) -> Result<(), DbError> {
+        if self.is_transacted() {
+            try!(self.rollback());
+        }
+        Ok(())
+    }
+
+    fn query_log(&self) -> &QueryLog {
+        &self.query_log
+    }
+
+    fn dialect(&self) -> &Dialect {
+        &self.dialect
+    }
+
+    fn execute_sql_with_return(&self, sql: &str, params: &[Value]) -> Result<Vec<Dao>, DbError> {
+        self.query_log.timed(sql, params, || {
+            match self.respond(sql, params) {
+                Some(MockResponse::Rows(rows)) => Ok(rows),
+                Some(MockResponse::Affected(_)) | None => Ok(vec![]),
+                Some(MockResponse::Error(e)) => Err(e),
+            }
+        })
+    }
+
+    fn execute_sql(&self, sql: &str, params: &[Value]) -> Result<usize, DbError> {
+        self.query_log.timed(sql, params, || {
+            match self.respond(sql, params) {
+                Some(MockResponse::Rows(rows)) => Ok(rows.len()),
+                Some(MockResponse::Affected(affected)) => Ok(affected),
+                Some(MockResponse::Error(e)) => Err(e),
+                None => Ok(0),
+            }
+        })
+    }
+}
+
+/// collapse the whitespaces, so the line breaks and indentation of the built sql don't matter
+fn normalize(sql: &str) -> String {
+    sql.split_whitespace().collect::<Vec<&str>>().join(" ")
+}
+
+fn describe(executed: &[ExecutedStatement]) -> String {
+    let statements: Vec<String> = executed.iter()
+                                          .map(|s| format!("  {} {:?}", normalize(&s.sql), s.params))
+                                          .collect();
+    statements.join("\n")
+}
+
+#[test]
+fn test_normalize() {
+    assert_eq!(normalize("
+   SELECT *
+     FROM \"bazaar\".\"product\"\x20
+    WHERE \"name\" = $1 "),
+               "SELECT * FROM \"bazaar\".\"product\" WHERE \"name\" = $1");
+}
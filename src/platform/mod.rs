@@ -1,10 +1,12 @@
 pub mod postgres;
+pub mod mock;
 #[cfg(feature = "sqlite")]
 pub mod sqlite;
 #[cfg(feature = "mysql")]
 pub mod mysql;
 
 pub use self::postgres::Postgres;
+pub use self::mock::MockDatabase;
 #[cfg(feature = "sqlite")]
 pub use self::sqlite::Sqlite;
 #[cfg(feature = "mysql")]
@@ -18,6 +20,10 @@ use postgres::error::ConnectError as PgConnectError;
 use mysql::error::MyError;
 #[cfg(feature = "sqlite")]
 use rusqlite::SqliteError;
+use postgres::error::DbError as PgDbError;
+use database::{ErrorKind, ErrorDetail};
+use regex::Regex;
+use std::io;
 
 #[derive(Debug)]
 pub enum PlatformError {
@@ -91,3 +97,181 @@ impl From<SqliteError> for PlatformError {
         PlatformError::SqliteError(err)
     }
 }
+
+impl PlatformError {
+
+    /// the portable kind of the native error
+    pub fn kind(&self) -> ErrorKind {
+        match *self {
+            PlatformError::PostgresError(PgError::DbError(ref err)) => {
+                ErrorKind::from_sqlstate(err.code().code())
+            }
+            PlatformError::PostgresError(PgError::IoError(ref err)) => io_error_kind(err),
+            PlatformError::PostgresError(_) => ErrorKind::Other,
+            PlatformError::PostgresConnectError(PgConnectError::DbError(ref err)) => {
+                ErrorKind::from_sqlstate(err.code().code())
+            }
+            PlatformError::PostgresConnectError(_) => ErrorKind::ConnectionLost,
+            #[cfg(feature = "mysql")]
+            PlatformError::MySQLError(ref err) => mysql_error_kind(err),
+            #[cfg(feature = "sqlite")]
+            PlatformError::SqliteError(ref err) => sqlite_error_kind(err),
+        }
+    }
+
+    /// the SQLSTATE, native code, constraint, table and column when provided by the driver
+    pub fn detail(&self) -> ErrorDetail {
+        match *self {
+            PlatformError::PostgresError(PgError::DbError(ref err)) => postgres_error_detail(err),
+            PlatformError::PostgresConnectError(PgConnectError::DbError(ref err)) => {
+                postgres_error_detail(err)
+            }
+            PlatformError::PostgresError(_) |
+            PlatformError::PostgresConnectError(_) => ErrorDetail::default(),
+            #[cfg(feature = "mysql")]
+            PlatformError::MySQLError(ref err) => mysql_error_detail(err),
+            #[cfg(feature = "sqlite")]
+            PlatformError::SqliteError(ref err) => sqlite_error_detail(err),
+        }
+    }
+}
+
+fn io_error_kind(err: &io::Error) -> ErrorKind {
+    match err.kind() {
+        io::ErrorKind::TimedOut | io::ErrorKind::WouldBlock => ErrorKind::Timeout,
+        _ => ErrorKind::ConnectionLost,
+    }
+}
+
+fn postgres_error_detail(err: &PgDbError) -> ErrorDetail {
+    ErrorDetail {
+        sqlstate: Some(err.code().code().to_owned()),
+        native_code: None,
+        constraint: err.constraint().map(|c| c.to_owned()),
+        table: err.table().map(|t| t.to_owned()),
+        column: err.column().map(|c| c.to_owned()),
+    }
+}
+
+/// https://dev.mysql.com/doc/refman/8.0/en/server-error-reference.html
+#[cfg(feature = "mysql")]
+fn mysql_error_kind(err: &MyError) -> ErrorKind {
+    match *err {
+        MyError::MySqlError(ref err) => {
+            match err.code {
+                1062 | 1586 => ErrorKind::UniqueViolation,
+                1216 | 1217 | 1451 | 1452 => ErrorKind::ForeignKeyViolation,
+                1048 | 1364 => ErrorKind::NotNullViolation,
+                3819 => ErrorKind::CheckViolation,
+                1213 => ErrorKind::Deadlock,
+                1205 | 3024 => ErrorKind::Timeout,
+                1064 => ErrorKind::SyntaxError,
+                2006 | 2013 => ErrorKind::ConnectionLost,
+                _ => ErrorKind::from_sqlstate(&err.state),
+            }
+        }
+        MyError::MyIoError(ref err) => io_error_kind(err),
+        _ => ErrorKind::Other,
+    }
+}
+
+#[cfg(feature = "mysql")]
+fn mysql_error_detail(err: &MyError) -> ErrorDetail {
+    match *err {
+        MyError::MySqlError(ref err) => {
+            let mut detail = ErrorDetail {
+                sqlstate: Some(err.state.to_owned()),
+                native_code: Some(err.code as i32),
+                ..Default::default()
+            };
+            // Duplicate entry 'x' for key 'name'
+            // Column 'name' cannot be null
+            // ... CONSTRAINT `fk` FOREIGN KEY (`column`) REFERENCES `table` (`column`)
+            lazy_static! {
+                static ref KEY_RE: Regex = Regex::new(r"for key '([^']+)'").unwrap();
+                static ref COLUMN_RE: Regex = Regex::new(r"^Column '([^']+)'").unwrap();
+                static ref FOREIGN_RE: Regex =
+                    Regex::new(r"CONSTRAINT `([^`]+)` FOREIGN KEY \(`([^`]+)`\)").unwrap();
+            }
+            if let Some(cap) = KEY_RE.captures(&err.message) {
+                detail.constraint = cap.at(1).map(|c| c.to_owned());
+            } else if let Some(cap) = COLUMN_RE.captures(&err.message) {
+                detail.column = cap.at(1).map(|c| c.to_owned());
+            } else if let Some(cap) = FOREIGN_RE.captures(&err.message) {
+                detail.constraint = cap.at(1).map(|c| c.to_owned());
+                detail.column = cap.at(2).map(|c| c.to_owned());
+            }
+            detail
+        }
+        _ => ErrorDetail::default(),
+    }
+}
+
+/// https://www.sqlite.org/rescode.html
+/// constraint errors share the same code, the kind is read from the message
+#[cfg(feature = "sqlite")]
+fn sqlite_error_kind(err: &SqliteError) -> ErrorKind {
+    let (code, message) = match *err {
+        SqliteError::SqliteFailure(ref code, ref message) => (code.extended_code, message),
+        _ => return ErrorKind::Other,
+    };
+    let message = message.as_ref().map(|m| m.as_str()).unwrap_or("");
+    match code & 0xff {
+        19 => {
+            if message.starts_with("UNIQUE") || message.starts_with("PRIMARY KEY") {
+                ErrorKind::UniqueViolation
+            } else if message.starts_with("FOREIGN KEY") {
+                ErrorKind::ForeignKeyViolation
+            } else if message.starts_with("NOT NULL") {
+                ErrorKind::NotNullViolation
+            } else if message.starts_with("CHECK") {
+                ErrorKind::CheckViolation
+            } else {
+                ErrorKind::Other
+            }
+        }
+        // SQLITE_BUSY and SQLITE_LOCKED, the lock was not acquired
+        5 | 6 => ErrorKind::Timeout,
+        10 | 14 => ErrorKind::ConnectionLost,
+        1 if message.contains("syntax error") => ErrorKind::SyntaxError,
+        _ => ErrorKind::Other,
+    }
+}
+
+#[cfg(feature = "sqlite")]
+fn sqlite_error_detail(err: &SqliteError) -> ErrorDetail {
+    let (code, message) = match *err {
+        SqliteError::SqliteFailure(ref code, ref message) => (code.extended_code, message),
+        _ => return ErrorDetail::default(),
+    };
+    let mut detail = ErrorDetail {
+        native_code: Some(code as i32),
+        ..Default::default()
+    };
+    let message = match *message {
+        Some(ref message) => message,
+        None => return detail,
+    };
+    // UNIQUE constraint failed: product.name, product.category
+    // CHECK constraint failed: positive_price
+    lazy_static! {
+        static ref COLUMN_RE: Regex =
+            Regex::new(r"^(?:UNIQUE|NOT NULL|PRIMARY KEY) constraint failed: (\w+)\.(\w+)").unwrap();
+        static ref CHECK_RE: Regex = Regex::new(r"^CHECK constraint failed: (\w+)").unwrap();
+    }
+    if let Some(cap) = COLUMN_RE.captures(message) {
+        detail.table = cap.at(1).map(|t| t.to_owned());
+        detail.column = cap.at(2).map(|c| c.to_owned());
+    } else if let Some(cap) = CHECK_RE.captures(message) {
+        detail.constraint = cap.at(1).map(|c| c.to_owned());
+    }
+    detail
+}
+
+#[test]
+fn test_sqlstate_kind() {
+    assert_eq!(ErrorKind::from_sqlstate("23505"), ErrorKind::UniqueViolation);
+    assert_eq!(ErrorKind::from_sqlstate("40P01"), ErrorKind::Deadlock);
+    assert_eq!(ErrorKind::from_sqlstate("08006"), ErrorKind::ConnectionLost);
+    assert_eq!(ErrorKind::from_sqlstate("22012"), ErrorKind::Other);
+}
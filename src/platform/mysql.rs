@@ -1,10 +1,10 @@
-use query::Query;
 use dao::Dao;
 
 use dao::Value;
 use database::Database;
 use writer::SqlFrag;
-use database::{SqlOption, BuildMode};
+use database::BuildMode;
+use query::Error as QueryError;
 
 use mysql::value::Value as MyValue;
 use mysql::consts::ColumnType;
@@ -12,25 +12,120 @@ use mysql::value::FromValue;
 use mysql::value::IntoValue;
 use mysql::error::MyResult;
 use mysql::conn::Stmt;
-use mysql::conn::pool::MyPool;
+use mysql::conn::pool::{MyPool, MyPooledConn};
 use chrono::naive::datetime::NaiveDateTime;
 
-use table::Table;
+use table::{Table, Column, Index};
 use database::DatabaseDDL;
 use database::DbError;
 use time::Timespec;
 use dao::Type;
+use std::cell::RefCell;
+use logger::QueryLog;
+use dialect::Dialect;
 
 pub struct Mysql {
     pool: Option<MyPool>,
+    /// the connection pinned for the duration of a transaction,
+    /// statements are otherwise executed on any connection in the pool
+    conn: RefCell<Option<MyPooledConn>>,
+    /// how the executed statements are logged
+    query_log: QueryLog,
+    /// the sql flavor, the version can be narrowed once connected
+    dialect: Dialect,
 }
 impl Mysql{
     pub fn new() -> Self {
-        Mysql { pool: None }
+        Mysql {
+            pool: None,
+            conn: RefCell::new(None),
+            query_log: QueryLog::new(),
+            dialect: Dialect::mysql(),
+        }
     }
 
     pub fn with_pooled_connection(pool: MyPool) -> Self {
-        Mysql { pool: Some(pool) }
+        Mysql {
+            pool: Some(pool),
+            conn: RefCell::new(None),
+            query_log: QueryLog::new(),
+            dialect: Dialect::mysql(),
+        }
+    }
+
+    /// set how the statements executed on this connection are logged
+    pub fn set_query_log(&mut self, query_log: QueryLog) -> &mut Self {
+        self.query_log = query_log;
+        self
+    }
+
+    /// target the sql features of a specific server version, i.e: CTE since 8.0
+    pub fn set_dialect(&mut self, dialect: Dialect) -> &mut Self {
+        self.dialect = dialect;
+        self
+    }
+
+    /// the indexes of the table other than the primary key, in the order of their columns.
+    /// The functional indexes of mysql 8 have no column name and are left out
+    pub fn get_table_indexes(&self, schema: &str, table: &str) -> Result<Vec<Index>, DbError> {
+        let sql = "
+            SELECT INDEX_NAME AS name,
+                   NON_UNIQUE AS non_unique,
+                   COLUMN_NAME AS column_name,
+                   INDEX_TYPE AS method
+              FROM information_schema.STATISTICS
+             WHERE TABLE_SCHEMA = ?
+               AND TABLE_NAME = ?
+               AND INDEX_NAME <> 'PRIMARY'
+          ORDER BY INDEX_NAME, SEQ_IN_INDEX
+            ";
+        let params = vec![Value::String(schema.to_owned()), Value::String(table.to_owned())];
+        let result = try!(self.execute_sql_with_return(sql, &params));
+        let mut indexes: Vec<Index> = vec![];
+        let mut functional = vec![];
+        for r in result {
+            let name: String = try!(r.try_get("name"));
+            let column: Option<String> = try!(r.try_get_opt("column_name"));
+            let is_new = indexes.last().map(|i| i.name != name).unwrap_or(true);
+            if is_new {
+                let non_unique: i64 = try!(r.try_get("non_unique"));
+                indexes.push(Index {
+                    name: name.to_owned(),
+                    columns: vec![],
+                    is_unique: non_unique == 0,
+                    method: try!(r.try_get_opt("method")),
+                    predicate: None,
+                    expression: None,
+                });
+            }
+            match column {
+                Some(column) => {
+                    if let Some(index) = indexes.last_mut() {
+                        index.columns.push(column);
+                    }
+                }
+                None => functional.push(name),
+            }
+        }
+        indexes.retain(|index| !functional.contains(&index.name));
+        Ok(indexes)
+    }
+
+    /// the start of the statements altering the table
+    fn alter_table(&self, table: &Table) -> Result<SqlFrag, QueryError> {
+        let mut w = SqlFrag::new(self.sql_options(), BuildMode::Standard);
+        w.append("ALTER TABLE ");
+        w.append(&try!(self.quote_identifier(&table.name)));
+        w.append(" ");
+        Ok(w)
+    }
+
+    /// the type, nullability and default are changed together by redefining the column
+    fn build_modify_column(&self, table: &Table, column: &Column) -> Result<SqlFrag, QueryError> {
+        let mut w = try!(self.alter_table(table));
+        w.append("MODIFY COLUMN ");
+        try!(self.build_column_definition(&mut w, column));
+        Ok(w)
     }
 
     fn from_rust_type_tosql(types: &[Value]) -> Vec<MyValue> {
@@ -64,6 +159,10 @@ impl Mysql{
                     let v = x.into_value();
                     params.push(v);
                 },
+                Value::U16(ref x) => {
+                    let v = x.into_value();
+                    params.push(v);
+                },
                 Value::U32(ref x) => {
                     let v = x.into_value();
                     params.push(v);
@@ -80,6 +179,31 @@ impl Mysql{
                     let v = x.into_value();
                     params.push(v);
                 },
+                Value::VecU8(ref x) => {
+                    params.push(MyValue::Bytes(x.to_owned()));
+                },
+                // the rest are passed as text, mysql converts them to the column type
+                Value::Uuid(ref x) => {
+                    params.push(MyValue::Bytes(x.to_hyphenated_string().into_bytes()));
+                },
+                Value::Json(ref x) => {
+                    params.push(MyValue::Bytes(x.to_string().into_bytes()));
+                },
+                Value::DateTime(ref x) => {
+                    params.push(MyValue::Bytes(x.naive_utc().to_string().into_bytes()));
+                },
+                Value::NaiveDateTime(ref x) => {
+                    params.push(MyValue::Bytes(x.to_string().into_bytes()));
+                },
+                Value::NaiveDate(ref x) => {
+                    params.push(MyValue::Bytes(x.to_string().into_bytes()));
+                },
+                Value::NaiveTime(ref x) => {
+                    params.push(MyValue::Bytes(x.to_string().into_bytes()));
+                },
+                Value::None(_) => {
+                    params.push(MyValue::NULL);
+                },
                 _ => panic!("not yet here {:?}", t),
             }
         }
@@ -91,7 +215,7 @@ impl Mysql{
         let value = row.get(index);
         match value {
             Some(value) => {
-                    println!("sql to rust {:?} type: {:?}", value, column_type);
+                    trace!("sql to rust {:?} type: {:?}", value, column_type);
                     match *value{
                         MyValue::NULL => {
                             Value::None(Type::String)// should put Type::Unknown
@@ -127,7 +251,7 @@ impl Mysql{
                                 ColumnType::MYSQL_TYPE_TIMESTAMP => {
                                     let v: Timespec = FromValue::from_value(value.clone());
                                     let t = NaiveDateTime::from_timestamp(v.sec, v.nsec as u32);
-                                    println!("time: {}",t);
+                                    trace!("time: {}",t);
                                     Value::NaiveDateTime(t)
                                 },
                                 ColumnType::MYSQL_TYPE_LONGLONG =>  {
@@ -217,57 +341,9 @@ impl Mysql{
     /// will be used in generating SQL for table creation
     /// FIXME, need to restore the exact data type as before
     fn rust_type_to_dbtype(&self, rust_type: &Type) -> String {
-        match *rust_type {
-            Type::Bool => {
-                "bool".to_owned()
-            }
-            Type::I8 => {
-                "tinyint(1)".to_owned()
-            }
-            Type::I16 => {
-                "integer".to_owned()
-            }
-            Type::I32 => {
-                "integer".to_owned()
-            }
-            Type::U32 => {
-                "integer".to_owned()
-            }
-            Type::I64 => {
-                "integer".to_owned()
-            }
-            Type::F32 => {
-                "real".to_owned()
-            }
-            Type::F64 => {
-                "real".to_owned()
-            }
-            Type::String => {
-                "text".to_owned()
-            }
-            Type::VecU8 => {
-                "blob".to_owned()
-            }
-            Type::Json => {
-                "text".to_owned()
-            }
-            Type::Uuid => {
-                "varchar(36)".to_owned()
-            }
-            Type::NaiveDateTime => {
-                "timestamp".to_owned()
-            }
-            Type::DateTime => {
-                "timestamp".to_owned()
-            }
-            Type::NaiveDate => {
-                "date".to_owned()
-            }
-            Type::NaiveTime => {
-                "time".to_owned()
-            }
-            _ => panic!("Unable to get the equivalent database data type for {:?}",
-                        rust_type),
+        match self.dialect.type_name(rust_type) {
+            Some(name) => name,
+            None => panic!("Unable to get the equivalent database data type for {:?}", rust_type),
         }
     }
 
@@ -278,25 +354,48 @@ impl Mysql{
 
 impl Database for Mysql {
     fn version(&self) -> Result<String, DbError> {
-        let sql = "SELECT version()";
+        let sql = "SELECT version() AS version";
         let dao = try!(self.execute_sql_with_one_return(sql, &vec![]));
         match dao {
-            Some(dao) => Ok(dao.get("version")),
+            Some(dao) => dao.try_get("version"),
             None => Err(DbError::new("Unable to get database version")),
         }
     }
 
-    fn begin(&self) {
-        unimplemented!()
+    /// transactions are bound to a connection,
+    /// so a connection is taken out of the pool and used until commit or rollback
+    fn begin(&self) -> Result<(), DbError> {
+        if self.conn.borrow().is_some() {
+            return Err(DbError::new("There is already a transaction in progress"));
+        }
+        let mut conn = match self.pool {
+            Some(ref pool) => try!(pool.get_conn()),
+            None => return Err(DbError::new("No connection pool to begin the transaction on")),
+        };
+        try!(conn.query("START TRANSACTION"));
+        *self.conn.borrow_mut() = Some(conn);
+        Ok(())
     }
-    fn commit(&self) {
-        unimplemented!()
+    fn commit(&self) -> Result<(), DbError> {
+        match self.conn.borrow_mut().take() {
+            Some(mut conn) => {
+                try!(conn.query("COMMIT"));
+                Ok(())
+            }
+            None => Err(DbError::new("No transaction to commit")),
+        }
     }
-    fn rollback(&self) {
-        unimplemented!()
+    fn rollback(&self) -> Result<(), DbError> {
+        match self.conn.borrow_mut().take() {
+            Some(mut conn) => {
+                try!(conn.query("ROLLBACK"));
+                Ok(())
+            }
+            None => Err(DbError::new("No transaction to rollback")),
+        }
     }
     fn is_transacted(&self) -> bool {
-        false
+        self.conn.borrow().is_some()
     }
     fn is_closed(&self) -> bool {
         false
@@ -309,50 +408,51 @@ impl Database for Mysql {
     fn is_valid(&self) -> bool {
         false
     }
-    fn reset(&self) {
-        unimplemented!()
+    fn reset(&self) -> Result<(), DbError> {
+        if self.is_transacted() {
+            try!(self.rollback());
+        }
+        Ok(())
     }
 
-    /// return this list of options, supported features in the database
-    fn sql_options(&self) -> Vec<SqlOption> {
-        vec![
-            SqlOption::UsesQuestionMark,//mysql uses question mark instead of the numbered params
-        ]
+    fn query_log(&self) -> &QueryLog {
+        &self.query_log
     }
 
-    fn update(&self, _query: &Query) -> Dao {
-        unimplemented!()
-    }
-    fn delete(&self, _query: &Query) -> Result<usize, String> {
-        unimplemented!()
+    fn dialect(&self) -> &Dialect {
+        &self.dialect
     }
 
     fn execute_sql_with_return(&self, sql: &str, params: &[Value]) -> Result<Vec<Dao>, DbError> {
-        println!("SQL: \n{}", sql);
-        println!("param: {:?}", params);
-        assert!(self.pool.is_some());
-        let mut stmt = try!(self.get_prepared_statement(sql));
-        let mut columns = vec![];
-        for col in stmt.columns_ref().unwrap() {
-            let column_name = String::from_utf8(col.name.clone()).unwrap();
-            println!("column type: {:?}", col.column_type);
-            columns.push( (column_name, col.column_type) );
-        }
-        let mut daos = vec![];
-        let param = Mysql::from_rust_type_tosql(params);
-        let rows = try!(stmt.execute(&param));
-        for row in rows {
-            let row = try!(row);
-            let mut index = 0;
-            let mut dao = Dao::new();
-            for &(ref column_name, ref column_type) in &columns {
-                let rtype = Mysql::from_sql_to_rust_type(&row, index, column_type);
-                dao.set_value(&column_name, rtype);
-                index += 1;
+        self.query_log.timed(sql, params, || {
+            assert!(self.pool.is_some());
+            let mut pinned = self.conn.borrow_mut();
+            let mut stmt = match *pinned {
+                Some(ref mut conn) => try!(conn.prepare(sql)),
+                None => try!(self.get_prepared_statement(sql)),
+            };
+            let mut columns = vec![];
+            for col in stmt.columns_ref().unwrap() {
+                let column_name = String::from_utf8(col.name.clone()).unwrap();
+                trace!("column type: {:?}", col.column_type);
+                columns.push( (column_name, col.column_type) );
             }
-            daos.push(dao);
-        }
-        Ok(daos)
+            let mut daos = vec![];
+            let param = Mysql::from_rust_type_tosql(params);
+            let rows = try!(stmt.execute(&param));
+            for row in rows {
+                let row = try!(row);
+                let mut index = 0;
+                let mut dao = Dao::new();
+                for &(ref column_name, ref column_type) in &columns {
+                    let rtype = Mysql::from_sql_to_rust_type(&row, index, column_type);
+                    dao.set_value(&column_name, rtype);
+                    index += 1;
+                }
+                daos.push(dao);
+            }
+            Ok(daos)
+        })
     }
 
     fn execute_sql_with_one_return(&self,
@@ -371,29 +471,33 @@ impl Database for Mysql {
     /// returns only the number of affected records or errors
     /// can be used with DDL operations (CREATE, DELETE, ALTER, DROP)
     fn execute_sql(&self, sql: &str, params: &[Value]) -> Result<usize, DbError> {
-        println!("SQL: \n{}", sql);
-        println!("param: {:?}", params);
-        let to_sql_types = Mysql::from_rust_type_tosql(params);
-        assert!(self.pool.is_some());
-        let result = try!(self.pool.as_ref().unwrap().prep_exec(sql, &to_sql_types));
-        Ok(result.affected_rows() as usize)
+        self.query_log.timed(sql, params, || {
+            let to_sql_types = Mysql::from_rust_type_tosql(params);
+            assert!(self.pool.is_some());
+            let mut pinned = self.conn.borrow_mut();
+            let result = match *pinned {
+                Some(ref mut conn) => try!(conn.prep_exec(sql, &to_sql_types)),
+                None => try!(self.pool.as_ref().unwrap().prep_exec(sql, &to_sql_types)),
+            };
+            Ok(result.affected_rows() as usize)
+        })
     }
 
 }
 
 impl DatabaseDDL for Mysql{
-    fn create_schema(&self, _schema: &str) {
-        unimplemented!()
+    fn create_schema(&self, _schema: &str) -> Result<(), DbError> {
+        Err(DbError::new("mysql schema is a database, it is not created here"))
     }
 
-    fn drop_schema(&self, _schema: &str) {
-        unimplemented!()
+    fn drop_schema(&self, _schema: &str) -> Result<(), DbError> {
+        Err(DbError::new("mysql schema is a database, it is not dropped here"))
     }
 
-    fn build_create_table(&self, table: &Table) -> SqlFrag {
+    fn build_create_table(&self, table: &Table) -> Result<SqlFrag, QueryError> {
         let mut w = SqlFrag::new(self.sql_options(), BuildMode::Standard);
         w.append("CREATE TABLE ");
-        w.append(&table.name);
+        w.append(&try!(self.quote_identifier(&table.name)));
         w.append("(");
         w.ln_tab();
         let mut do_comma = false;
@@ -403,39 +507,205 @@ impl DatabaseDDL for Mysql{
             } else {
                 do_comma = true;
             }
-            w.append(&c.name);
+            w.append(&try!(self.quote_identifier(&c.name)));
             w.append(" ");
-            let dt = self.rust_type_to_dbtype(&c.data_type);
-            w.append(&dt);
+            w.append(&try!(self.data_type_name(&c.data_type)));
             if c.is_primary {
                 w.append(" PRIMARY KEY ");
             }
         }
         w.append(")");
-        w
+        Ok(w)
+    }
+    fn create_table(&self, table: &Table) -> Result<(), DbError> {
+        let frag = try!(self.build_create_table(table));
+        try!(self.execute_sql(&frag.sql, &frag.params));
+        debug!("created table..");
+        for index in &table.indexes {
+            try!(self.create_index(table, index));
+        }
+        Ok(())
+    }
+
+    fn rename_table(&self, table: &Table, new_tablename: String) -> Result<(), DbError> {
+        let frag = try!(self.build_rename_table(table, &new_tablename));
+        try!(self.execute_sql(&frag.sql, &frag.params));
+        debug!("renamed table..");
+        Ok(())
+    }
+
+    fn drop_table(&self, table: &Table) -> Result<(), DbError> {
+        let frag = try!(self.build_drop_table(table));
+        try!(self.execute_sql(&frag.sql, &frag.params));
+        debug!("dropped table..");
+        Ok(())
+    }
+
+    fn set_foreign_constraint(&self, _model: &Table) -> Result<(), DbError> {
+        Err(DbError::new("mysql can not add the foreign keys of an existing table"))
+    }
+
+    fn set_primary_constraint(&self, _model: &Table) -> Result<(), DbError> {
+        Err(DbError::new("mysql can not add the primary key of an existing table"))
+    }
+
+    /// mysql has no partial index, an index with a predicate is an error
+    fn build_create_index(&self, table: &Table, index: &Index) -> Result<SqlFrag, QueryError> {
+        if index.predicate.is_some() {
+            return Err(QueryError::SqlError(format!("mysql can not create the partial index {}",
+                                                    index.name)));
+        }
+        let mut w = SqlFrag::new(self.sql_options(), BuildMode::Standard);
+        if index.is_unique {
+            w.append("CREATE UNIQUE INDEX ");
+        } else {
+            w.append("CREATE INDEX ");
+        }
+        w.append(&try!(self.quote_identifier(&index.name)));
+        w.append(" ON ");
+        w.append(&try!(self.quote_identifier(&table.name)));
+        w.append(" (");
+        match index.expression {
+            Some(ref expression) => {
+                w.append(&format!("({})", expression));
+            }
+            None => {
+                if index.columns.is_empty() {
+                    return Err(QueryError::NoColumnSpecified(format!("index {}", index.name)));
+                }
+                let mut columns = vec![];
+                for c in &index.columns {
+                    columns.push(try!(self.quote_identifier(c)));
+                }
+                w.append(&columns.join(", "));
+            }
+        }
+        w.append(")");
+        if let Some(ref method) = index.method {
+            w.append(" USING ");
+            w.append(method);
+        }
+        Ok(w)
     }
-    fn create_table(&self, table: &Table) {
-        let frag = self.build_create_table(table);
-        match self.execute_sql(&frag.sql, &vec![]) {
-            Ok(_) => println!("created table.."),
-            Err(e) => panic!("table not created {}", e),
+
+    fn create_index(&self, table: &Table, index: &Index) -> Result<(), DbError> {
+        let frag = try!(self.build_create_index(table, index));
+        try!(self.execute_sql(&frag.sql, &frag.params));
+        debug!("created index..");
+        Ok(())
+    }
+
+    fn drop_index(&self, table: &Table, index: &Index) -> Result<(), DbError> {
+        let frag = try!(self.build_drop_index(table, index));
+        try!(self.execute_sql(&frag.sql, &frag.params));
+        debug!("dropped index..");
+        Ok(())
+    }
+
+    fn build_rename_table(&self, table: &Table, new_tablename: &str) -> Result<SqlFrag, QueryError> {
+        let mut w = try!(self.alter_table(table));
+        w.append("RENAME TO ");
+        w.append(&try!(self.quote_identifier(new_tablename)));
+        Ok(w)
+    }
+
+    fn build_drop_table(&self, table: &Table) -> Result<SqlFrag, QueryError> {
+        let mut w = SqlFrag::new(self.sql_options(), BuildMode::Standard);
+        w.append("DROP TABLE ");
+        w.append(&try!(self.quote_identifier(&table.name)));
+        Ok(w)
+    }
+
+    fn build_drop_index(&self, table: &Table, index: &Index) -> Result<SqlFrag, QueryError> {
+        let mut w = SqlFrag::new(self.sql_options(), BuildMode::Standard);
+        w.append("DROP INDEX ");
+        w.append(&try!(self.quote_identifier(&index.name)));
+        w.append(" ON ");
+        w.append(&try!(self.quote_identifier(&table.name)));
+        Ok(w)
+    }
+
+    fn build_add_column(&self, table: &Table, column: &Column) -> Result<SqlFrag, QueryError> {
+        let mut w = try!(self.alter_table(table));
+        w.append("ADD COLUMN ");
+        try!(self.build_column_definition(&mut w, column));
+        Ok(w)
+    }
+
+    fn build_drop_column(&self, table: &Table, column: &Column) -> Result<SqlFrag, QueryError> {
+        let mut w = try!(self.alter_table(table));
+        w.append("DROP COLUMN ");
+        w.append(&try!(self.quote_identifier(&column.name)));
+        Ok(w)
+    }
+
+    /// before mysql 8.0 the column is renamed with its whole definition
+    fn build_rename_column(&self, table: &Table, column: &Column, new_name: &str) -> Result<SqlFrag, QueryError> {
+        let mut w = try!(self.alter_table(table));
+        if self.dialect.is_at_least(8, 0, 0) {
+            w.append("RENAME COLUMN ");
+            w.append(&try!(self.quote_identifier(&column.name)));
+            w.append(" TO ");
+            w.append(&try!(self.quote_identifier(new_name)));
+        } else {
+            let mut renamed = column.clone();
+            renamed.name = new_name.to_owned();
+            w.append("CHANGE COLUMN ");
+            w.append(&try!(self.quote_identifier(&column.name)));
+            w.append(" ");
+            try!(self.build_column_definition(&mut w, &renamed));
         }
+        Ok(w)
     }
 
-    fn rename_table(&self, _table: &Table, _new_tablename: String) {
-        unimplemented!()
+    fn build_alter_column_type(&self, table: &Table, column: &Column) -> Result<SqlFrag, QueryError> {
+        self.build_modify_column(table, column)
     }
 
-    fn drop_table(&self, _table: &Table) {
-        unimplemented!()
+    fn build_alter_column_nullability(&self, table: &Table, column: &Column) -> Result<SqlFrag, QueryError> {
+        self.build_modify_column(table, column)
     }
 
-    fn set_foreign_constraint(&self, _model: &Table) {
-        unimplemented!()
+    fn build_alter_column_default(&self, table: &Table, column: &Column) -> Result<SqlFrag, QueryError> {
+        let mut w = try!(self.alter_table(table));
+        w.append("ALTER COLUMN ");
+        w.append(&try!(self.quote_identifier(&column.name)));
+        match column.default {
+            Some(ref default) => {
+                w.append(" SET DEFAULT ");
+                try!(self.build_column_default(&mut w, default));
+            }
+            None => {
+                w.append(" DROP DEFAULT");
+            }
+        }
+        Ok(w)
+    }
+
+    fn build_add_foreign_key(&self, table: &Table, column: &Column) -> Result<SqlFrag, QueryError> {
+        let foreign = match column.foreign {
+            Some(ref foreign) => foreign,
+            None => return Err(QueryError::SqlError(format!("Column {} has no foreign key", column.name))),
+        };
+        let mut w = try!(self.alter_table(table));
+        w.append("ADD CONSTRAINT ");
+        w.append(&try!(self.quote_identifier(&table.foreign_key_name(column))));
+        w.append(" FOREIGN KEY (");
+        w.append(&try!(self.quote_identifier(&column.name)));
+        w.append(") REFERENCES ");
+        w.append(&try!(self.quote_identifier(&foreign.table)));
+        w.append(" (");
+        w.append(&try!(self.quote_identifier(&foreign.column)));
+        w.append(")");
+        Ok(w)
     }
 
-    fn set_primary_constraint(&self, _model: &Table) {
-        unimplemented!()
+    /// the constraint is dropped by its introspected name, mysql names them `table_ibfk_n`
+    fn build_drop_foreign_key(&self, table: &Table, column: &Column) -> Result<SqlFrag, QueryError> {
+        let mut w = try!(self.alter_table(table));
+        w.append("DROP FOREIGN KEY ");
+        w.append(&try!(self.quote_identifier(try!(column.foreign_constraint_name()))));
+        Ok(w)
     }
 }
 
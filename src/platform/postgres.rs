@@ -1,5 +1,5 @@
 use query::Query;
-use table::{Table, Column, Foreign};
+use table::{Table, Column, Foreign, Index};
 use dao::Dao;
 
 use postgres::Connection;
@@ -8,25 +8,45 @@ use dao::Value;
 use database::{Database, DatabaseDev, DatabaseDDL, DbError};
 use postgres::types::Type as PgType;
 use postgres::types::ToSql;
-use writer::SqlFrag;
+use writer::{SqlFrag, replace_params};
 use postgres::rows::Row;
-use database::SqlOption;
 use r2d2::PooledConnection;
 use r2d2_postgres::PostgresConnectionManager;
 use rustc_serialize::json::Json;
 use dao::Type;
-use postgres::types::IsNull;
+use postgres::types::{IsNull, FromSql, SessionInfo};
+use postgres::Result as PgResult;
+use postgres::error::Error as PgError;
 use uuid::Uuid;
 use query::Operand;
+use query::Error as QueryError;
+use std::cell::Cell;
+use std::io::{self, Read, Write};
+use std::cmp;
+use std::time::Duration;
+use std::collections::{BTreeMap, HashMap};
+use std::ops::Deref;
+use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime, UTC};
+use logger::QueryLog;
+use dialect::Dialect;
+use rustc_serialize::json;
+use dao::IsDao;
+use query::{ToTableName, TableName};
+use database::BuildMode;
 
 pub struct Postgres {
     /// a connection pool is provided
     pub pool: Option<PooledConnection<PostgresConnectionManager>>,
+    /// whether a transaction has been started on this connection
+    in_transaction: Cell<bool>,
+    /// how the executed statements are logged
+    query_log: QueryLog,
+    /// the sql flavor, the version can be narrowed once connected
+    dialect: Dialect,
 }
 
 /// Build the Query into a SQL statements that is a valid
-/// PostgreSQL sql query,
-/// TODO: support version SqlOptions/specific syntax
+/// PostgreSQL sql query, the version specific syntax is in the Dialect
 
 //static none: &'static Option<String> = &None;
 
@@ -36,16 +56,38 @@ impl Postgres {
     /// useful when just building sql queries specific to this platform
     /// inexpensive operation, so can have multiple instances
     pub fn new() -> Self {
-        Postgres { pool: None }
+        Postgres {
+            pool: None,
+            in_transaction: Cell::new(false),
+            query_log: QueryLog::new(),
+            dialect: Dialect::postgres(),
+        }
     }
 
 
     pub fn with_pooled_connection(pool: PooledConnection<PostgresConnectionManager>) -> Self {
-        Postgres { pool: Some(pool) }
+        Postgres {
+            pool: Some(pool),
+            in_transaction: Cell::new(false),
+            query_log: QueryLog::new(),
+            dialect: Dialect::postgres(),
+        }
     }
 
 
 
+    /// set how the statements executed on this connection are logged
+    pub fn set_query_log(&mut self, query_log: QueryLog) -> &mut Self {
+        self.query_log = query_log;
+        self
+    }
+
+    /// target the sql features of a specific server version
+    pub fn set_dialect(&mut self, dialect: Dialect) -> &mut Self {
+        self.dialect = dialect;
+        self
+    }
+
     pub fn get_connection(&self) -> &Connection {
         match self.pool {
             Some(ref pool) => &pool,
@@ -53,57 +95,152 @@ impl Postgres {
         }
     }
 
+    /// bulk load the records into the table using `COPY .. FROM STDIN` in CSV format,
+    /// the values are written in the order of the table columns, missing values are loaded as NULL.
+    /// The records are encoded as they are read, so the iterator can be larger than the memory.
+    /// returns the number of records copied
+    pub fn copy_in<I>(&self, table: &Table, records: I) -> Result<u64, DbError>
+        where I: IntoIterator<Item = Dao>
+    {
+        let mut columns = vec![];
+        for c in &table.columns {
+            columns.push(try!(self.quote_identifier(&c.name)));
+        }
+        let sql = format!("COPY {} ({}) FROM STDIN WITH (FORMAT csv)",
+                          try!(self.quote_table_name(&table.to_table_name())),
+                          columns.join(", "));
+        let mut reader = CopyInReader {
+            records: records.into_iter(),
+            columns: table.columns.iter().map(|c| c.name.to_owned()).collect(),
+            buffer: vec![],
+            position: 0,
+        };
+        self.query_log.timed(&sql, &[], || {
+            let conn = self.get_connection();
+            let stmt = try!(conn.prepare(&sql));
+            let copied = try!(stmt.copy_in(&[], &mut reader));
+            Ok(copied)
+        })
+    }
+
+    /// bulk load the typed records into the table
+    pub fn copy_in_all<T: IsDao>(&self, table: &Table, records: &[T]) -> Result<u64, DbError> {
+        self.copy_in(table, records.iter().map(|r| r.to_dao()))
+    }
+
+    /// write all the records of the table to the writer using `COPY .. TO STDOUT`
+    /// in CSV format with a header line, returns the number of records copied
+    pub fn copy_out<W: Write>(&self, table: &Table, writer: &mut W) -> Result<u64, DbError> {
+        let sql = format!("COPY {} TO STDOUT WITH (FORMAT csv, HEADER)",
+                          try!(self.quote_table_name(&table.to_table_name())));
+        self.copy_sql_out(&sql, &[], &sql, writer)
+    }
+
+    /// write the result of the query to the writer in CSV format with a header line,
+    /// COPY can not take parameters so the values of the query are written as literals,
+    /// the statement is logged with the placeholders and the parameters, as the other statements
+    pub fn copy_query_out<W: Write>(&self, query: &mut Query, writer: &mut W) -> Result<u64, DbError> {
+        let sql_frag = try!(query.build(self));
+        let inlined = try!(inline_params(&sql_frag.sql, &sql_frag.params));
+        let sql = format!("COPY ({}) TO STDOUT WITH (FORMAT csv, HEADER)", inlined.trim());
+        let logged = format!("COPY ({}) TO STDOUT WITH (FORMAT csv, HEADER)", sql_frag.sql.trim());
+        self.copy_sql_out(&logged, &sql_frag.params, &sql, writer)
+    }
+
+    /// run the sql, the logged sql and params are what the query log sees
+    fn copy_sql_out<W: Write>(&self, logged: &str, params: &[Value], sql: &str, writer: &mut W) -> Result<u64, DbError> {
+        self.query_log.timed(logged, params, || {
+            let conn = self.get_connection();
+            let stmt = try!(conn.prepare(sql));
+            let copied = try!(stmt.copy_out(&[], writer));
+            Ok(copied)
+        })
+    }
+
+    /// start receiving the notifications sent to the channel on this connection
+    pub fn listen(&self, channel: &str) -> Result<(), DbError> {
+        let sql = format!("LISTEN {}", try!(self.quote_identifier(channel)));
+        try!(self.execute_sql(&sql, &[]));
+        Ok(())
+    }
+
+    /// stop receiving the notifications sent to the channel
+    pub fn unlisten(&self, channel: &str) -> Result<(), DbError> {
+        let sql = format!("UNLISTEN {}", try!(self.quote_identifier(channel)));
+        try!(self.execute_sql(&sql, &[]));
+        Ok(())
+    }
+
+    /// stop receiving notifications from all the channels
+    pub fn unlisten_all(&self) -> Result<(), DbError> {
+        try!(self.execute_sql("UNLISTEN *", &[]));
+        Ok(())
+    }
+
+    /// send the payload to the listeners of the channel,
+    /// it is delivered when the current transaction is committed
+    pub fn notify(&self, channel: &str, payload: &str) -> Result<(), DbError> {
+        try!(self.execute_sql("SELECT pg_notify($1, $2)",
+                              &[Value::String(channel.to_owned()),
+                                Value::String(payload.to_owned())]));
+        Ok(())
+    }
+
+    /// iterate the notifications received by this connection,
+    /// blocks until the next notification arrives
+    pub fn notifications(&self) -> NotificationIter {
+        NotificationIter {
+            pg: self,
+            timeout: None,
+        }
+    }
+
+    /// iterate the notifications received by this connection,
+    /// the iteration ends when no notification arrives within the timeout
+    pub fn notifications_timeout(&self, timeout: Duration) -> NotificationIter {
+        NotificationIter {
+            pg: self,
+            timeout: Some(timeout),
+        }
+    }
+
     /// convert Type to ToSql (postgresql native types)
-    /// This is used when inserting records to the database
-    /// TODO: put this somewhere organized
-    /// TODO: match all the other filter types
-    /// TODO: need to have a container for PgType contained before being borrowed to actual postgres type
-    fn from_rust_type_tosql<'b>(&self, types: &'b [Value]) -> Vec<&'b ToSql> {
-        let mut params: Vec<&ToSql> = vec![];
+    /// This is used when inserting records to the database,
+    /// the unsigned values are widened to the next signed type since postgresql has no unsigned types
+    fn from_rust_type_tosql<'b>(&self, types: &'b [Value]) -> Result<Vec<Box<ToSql + 'b>>, DbError> {
+        let mut params: Vec<Box<ToSql + 'b>> = vec![];
         for t in types {
             match *t {
-                Value::Bool(ref x) => params.push(x),
-                Value::I8(ref x) => params.push(x),
-                Value::I16(ref x) => params.push(x),
-                Value::I32(ref x) => params.push(x),
-                Value::I64(ref x) => params.push(x),
-                Value::U8(_) => panic!("unsupported/unexpected type! {:?}", t),
-                Value::U16(_) => panic!("unsupported/unexpected type! {:?}", t),
-                Value::U32(ref x) => params.push(x),
-                Value::U64(_) => panic!("unsupported/unexpected type! {:?}", t),
-                Value::F32(ref x) => params.push(x),
-                Value::F64(ref x) => params.push(x),
-                Value::String(ref x) => params.push(x),
-                Value::VecU8(ref x) => params.push(x),
-                Value::Uuid(ref x) => params.push(x),
-                Value::DateTime(ref x) => params.push(x),
-                Value::NaiveDate(ref x) => params.push(x),
-                Value::NaiveTime(ref x) => params.push(x),
-                Value::NaiveDateTime(ref x) => params.push(x),
-                Value::Json(ref x) => {
-//                    panic!("Json is not yet supported!..");
-//                     static NONE: &'static Option<String> = &None;
-                       params.push(x)
+                Value::Bool(ref x) => params.push(Box::new(x)),
+                Value::I8(ref x) => params.push(Box::new(x)),
+                Value::I16(ref x) => params.push(Box::new(x)),
+                Value::I32(ref x) => params.push(Box::new(x)),
+                Value::I64(ref x) => params.push(Box::new(x)),
+                Value::U8(x) => params.push(Box::new(x as i16)),
+                Value::U16(x) => params.push(Box::new(x as i32)),
+                Value::U32(ref x) => params.push(Box::new(x)),
+                Value::U64(x) => {
+                    if x > i64::max_value() as u64 {
+                        return Err(DbError::new(&format!("{} is too large for a postgresql bigint", x)));
+                    }
+                    params.push(Box::new(x as i64))
                 }
-                Value::None(ref v_type) => {
-                        match v_type{
-							&Type::String => {
-								static none: &'static Option<String> = &None;
-								params.push(none)
-							},
-							&Type::Uuid => {
-								static none: &'static Option<Uuid> = &None;
-								params.push(none)
-							}
-							_ => panic!("not yet for Non type of {:?}",v_type),
-						}
-                    },
-                _ => panic!("not yet here {:?}", t),
-            }
-        }
-        params
-    }
-    
+                Value::F32(ref x) => params.push(Box::new(x)),
+                Value::F64(ref x) => params.push(Box::new(x)),
+                Value::String(ref x) => params.push(Box::new(x)),
+                Value::VecU8(ref x) => params.push(Box::new(x)),
+                Value::Object(ref x) => params.push(Box::new(to_hstore(x))),
+                Value::Json(ref x) => params.push(Box::new(JsonParam(x))),
+                Value::Uuid(ref x) => params.push(Box::new(x)),
+                Value::DateTime(ref x) => params.push(Box::new(x)),
+                Value::NaiveDate(ref x) => params.push(Box::new(x)),
+                Value::NaiveTime(ref x) => params.push(Box::new(x)),
+                Value::NaiveDateTime(ref x) => params.push(Box::new(x)),
+                Value::None(ref v_type) => params.push(typed_null(v_type)),
+            }
+        }
+        Ok(params)
+    }
 
     /// convert a record of a row into rust type
     fn from_sql_to_rust_type(&self, dtype: &PgType, row: &Row, index: usize) -> Value {
@@ -160,7 +297,7 @@ impl Postgres {
             PgType::Json => {
                 let value = row.get_opt(index);
                 match value {
-                    Ok(value) => Value::Json(value),
+                    Ok(JsonColumn(value)) => Value::Json(value),
                     Err(_) => Value::None(Type::F64),
                 }
             }
@@ -308,7 +445,7 @@ impl Postgres {
             let is_unique: bool = row.get("is_unique");
 
             let default: Option<Operand> = match row.get_opt("default") {
-                Ok(x) => Some(Operand::Value(Value::String(x))),
+                Ok(x) => Some(Operand::Sql(x)),
                 Err(_) => None,
             };
             let comment: Option<String> = match row.get_opt("comment") {
@@ -336,6 +473,7 @@ impl Postgres {
                     schema: foreign_schema,
                     table: foreign_table.unwrap(),
                     column: foreign_column.unwrap(),
+                    name: row.get_opt("conname").ok(),
                 })
 
             } else {
@@ -388,6 +526,77 @@ impl Postgres {
         None
     }
 
+    /// the indexes of the table, the ones backing the primary key and unique constraints are left to the columns
+    fn get_table_indexes(&self, schema: &str, table: &str) -> Vec<Index> {
+        let sql = "
+            SELECT
+                index_class.relname AS name,
+                pg_index.indisunique AS is_unique,
+                pg_am.amname AS method,
+                pg_get_expr(pg_index.indpred, pg_index.indrelid) AS predicate,
+                CASE
+                WHEN pg_index.indexprs IS NOT NULL THEN
+                    array_to_string(ARRAY(
+                        SELECT pg_get_indexdef(pg_index.indexrelid, k, true)
+                            FROM generate_series(1, pg_index.indnatts) AS k
+                            ORDER BY k
+                    ), ', ')
+                END AS expression,
+                array_to_string(ARRAY(
+                    SELECT pg_attribute.attname
+                        FROM generate_subscripts(pg_index.indkey::int2[], 1) AS k
+                        JOIN pg_attribute
+                            ON pg_attribute.attrelid = pg_index.indrelid
+                            AND pg_attribute.attnum = (pg_index.indkey::int2[])[k]
+                        ORDER BY k
+                ), ',') AS columns
+            FROM pg_index
+                JOIN pg_class AS index_class
+                    ON index_class.oid = pg_index.indexrelid
+                JOIN pg_class
+                    ON pg_class.oid = pg_index.indrelid
+                JOIN pg_namespace
+                    ON pg_namespace.oid = pg_class.relnamespace
+                JOIN pg_am
+                    ON pg_am.oid = index_class.relam
+                LEFT JOIN pg_constraint
+                    ON pg_constraint.conindid = pg_index.indexrelid
+            WHERE pg_namespace.nspname = $1
+                AND pg_class.relname = $2
+                AND pg_constraint.oid IS NULL
+            ORDER BY name
+            ";
+        let conn = self.get_connection();
+        let stmt = conn.prepare(&sql).unwrap();
+        let mut indexes = Vec::new();
+        for row in stmt.query(&[&schema, &table]).unwrap() {
+            let expression: Option<String> = match row.get_opt("expression") {
+                Ok(x) => Some(x),
+                Err(_) => None,
+            };
+            let columns: String = row.get("columns");
+            // the columns are part of the expression
+            let columns = if expression.is_some() {
+                vec![]
+            } else {
+                columns.split(',').map(|c| c.to_owned()).collect()
+            };
+            let predicate: Option<String> = match row.get_opt("predicate") {
+                Ok(x) => Some(x),
+                Err(_) => None,
+            };
+            indexes.push(Index {
+                name: row.get("name"),
+                columns: columns,
+                is_unique: row.get("is_unique"),
+                method: Some(row.get("method")),
+                predicate: predicate,
+                expression: expression,
+            });
+        }
+        indexes
+    }
+
     /// column that is both primary and foreign should be unified
     fn unify_primary_and_foreign_column(&self, columns: &[Column]) -> Vec<Column> {
         let mut unified_columns = Vec::new();
@@ -425,19 +634,28 @@ impl Database for Postgres {
         let sql = "SHOW server_version";
         let dao = try!(self.execute_sql_with_one_return(sql, &vec![]));
         match dao {
-            Some(dao) => Ok(dao.get("server_version")),
+            Some(dao) => dao.try_get("server_version"),
             None => Err(DbError::new("Unable to get database version")),
         }
     }
 
-    fn begin(&self) {
+    fn begin(&self) -> Result<(), DbError> {
+        try!(self.get_connection().batch_execute("BEGIN"));
+        self.in_transaction.set(true);
+        Ok(())
     }
-    fn commit(&self) {
+    fn commit(&self) -> Result<(), DbError> {
+        self.in_transaction.set(false);
+        try!(self.get_connection().batch_execute("COMMIT"));
+        Ok(())
     }
-    fn rollback(&self) {
+    fn rollback(&self) -> Result<(), DbError> {
+        self.in_transaction.set(false);
+        try!(self.get_connection().batch_execute("ROLLBACK"));
+        Ok(())
     }
     fn is_transacted(&self) -> bool {
-        false
+        self.in_transaction.get()
     }
     fn is_closed(&self) -> bool {
         false
@@ -450,101 +668,438 @@ impl Database for Postgres {
     fn is_valid(&self) -> bool {
         false
     }
-    fn reset(&self) {
+    fn reset(&self) -> Result<(), DbError> {
+        if self.is_transacted() {
+            try!(self.rollback());
+        }
+        Ok(())
     }
 
-    /// return this list of options, supported features in the database
-    /// TODO: make this features version specific
-    /// http://www.postgresql.org/about/featurematrix/
-    /// writer CTE  >= 9.1
-    /// Inheritance  >= 9.0
-    /// JSON >= 9.2
-    /// JSONB >= 9.4
-    /// Returning >= 8.2
-    fn sql_options(&self) -> Vec<SqlOption> {
-        vec![
-            SqlOption::UsesNumberedParam,  // uses numbered parameters
-            SqlOption::SupportsReturningClause, // supports returning clause, feature
-            SqlOption::SupportsCTE,
-            SqlOption::SupportsInheritance,
-            SqlOption::UsesSchema,
-            SqlOption::ReturnMetaColumns,// whether to use the column names returned in a statement
-        ]
+    fn query_log(&self) -> &QueryLog {
+        &self.query_log
     }
 
-
-    fn update(&self, _query: &Query) -> Dao {
-        unimplemented!()
-    }
-    fn delete(&self, _query: &Query) -> Result<usize, String> {
-        unimplemented!()
+    fn dialect(&self) -> &Dialect {
+        &self.dialect
     }
 
     fn execute_sql_with_return(&self, sql: &str, params: &[Value]) -> Result<Vec<Dao>, DbError> {
-        println!("SQL: \n{}", sql);
-        println!("param: {:?}", params);
-        let conn = self.get_connection();
-        let stmt = try!(conn.prepare(sql));
-        let mut daos = vec![];
-        let param = self.from_rust_type_tosql(params);
-        let rows = try!(stmt.query(&param));
-        for row in rows {
-            let columns = row.columns();
-            let mut index = 0;
-            let mut dao = Dao::new();
-            for c in columns {
-                let column_name = c.name();
-                let dtype = c.type_();
-                let rtype = self.from_sql_to_rust_type(&dtype, &row, index);
-                dao.set_value(column_name, rtype);
-                index += 1;
-            }
-            daos.push(dao);
-        }
-        Ok(daos)
+        self.query_log.timed(sql, params, || {
+            let conn = self.get_connection();
+            let stmt = try!(conn.prepare(sql));
+            let mut daos = vec![];
+            let param = try!(self.from_rust_type_tosql(params));
+            let param: Vec<&ToSql> = param.iter().map(|p| &**p).collect();
+            let rows = try!(stmt.query(&param));
+            for row in rows {
+                let columns = row.columns();
+                let mut index = 0;
+                let mut dao = Dao::new();
+                for c in columns {
+                    let column_name = c.name();
+                    let dtype = c.type_();
+                    let rtype = self.from_sql_to_rust_type(&dtype, &row, index);
+                    dao.set_value(column_name, rtype);
+                    index += 1;
+                }
+                daos.push(dao);
+            }
+            Ok(daos)
+        })
     }
 
     /// generic execute sql which returns not much information,
     /// returns only the number of affected records or errors
     /// can be used with DDL operations (CREATE, DELETE, ALTER, DROP)
     fn execute_sql(&self, sql: &str, params: &[Value]) -> Result<usize, DbError> {
-        println!("SQL: \n{}", sql);
-        println!("param: {:?}", params);
-        let to_sql_types = self.from_rust_type_tosql(params);
-        let conn = self.get_connection();
-        let result = try!(conn.execute(sql, &to_sql_types));
-        Ok(result as usize)
+        self.query_log.timed(sql, params, || {
+            let to_sql_types = try!(self.from_rust_type_tosql(params));
+            let to_sql_types: Vec<&ToSql> = to_sql_types.iter().map(|p| &**p).collect();
+            let conn = self.get_connection();
+            let result = try!(conn.execute(sql, &to_sql_types));
+            Ok(result as usize)
+        })
+    }
+
+}
+
+/// the DDL statements, built without executing them
+impl Postgres {
+
+    /// the quoted name of the foreign table, qualified with its schema
+    fn quote_foreign_table(&self, foreign: &Foreign) -> Result<String, QueryError> {
+        let table_name = TableName {
+            schema: foreign.schema.clone(),
+            name: foreign.table.to_owned(),
+            columns: vec![],
+        };
+        self.quote_table_name(&table_name)
     }
 
+    /// the quoted primary columns, in the order of the table columns
+    fn quote_primary_columns(&self, table: &Table) -> Result<Vec<String>, QueryError> {
+        let mut primary = vec![];
+        for c in &table.columns {
+            if c.is_primary {
+                primary.push(try!(self.quote_identifier(&c.name)));
+            }
+        }
+        Ok(primary)
+    }
+
+    /// `FOREIGN KEY ("column") REFERENCES "schema"."table" ("column")`
+    fn foreign_key_clause(&self, column: &Column, foreign: &Foreign) -> Result<String, QueryError> {
+        Ok(format!("FOREIGN KEY ({}) REFERENCES {} ({})",
+                   try!(self.quote_identifier(&column.name)),
+                   try!(self.quote_foreign_table(foreign)),
+                   try!(self.quote_identifier(&foreign.column))))
+    }
+
+    /// the columns of the table which are not inherited from the parent table
+    fn own_columns<'t>(&self, table: &'t Table) -> Vec<&'t Column> {
+        table.columns
+             .iter()
+             .filter(|c| table.parent_table.is_none() || !c.is_inherited)
+             .collect()
+    }
+
+    /// `ALTER TABLE "schema"."table" `, the start of the statements altering the table
+    fn alter_table(&self, table: &Table) -> Result<SqlFrag, QueryError> {
+        let mut w = SqlFrag::new(self.sql_options(), BuildMode::Standard);
+        w.append("ALTER TABLE ");
+        w.append(&try!(self.quote_table_name(&table.to_table_name())));
+        w.append(" ");
+        Ok(w)
+    }
+
+    pub fn build_create_schema(&self, schema: &str) -> Result<SqlFrag, QueryError> {
+        let mut w = SqlFrag::new(self.sql_options(), BuildMode::Standard);
+        w.append("CREATE SCHEMA IF NOT EXISTS ");
+        w.append(&try!(self.quote_identifier(schema)));
+        Ok(w)
+    }
+
+    pub fn build_drop_schema(&self, schema: &str) -> Result<SqlFrag, QueryError> {
+        let mut w = SqlFrag::new(self.sql_options(), BuildMode::Standard);
+        w.append("DROP SCHEMA IF EXISTS ");
+        w.append(&try!(self.quote_identifier(schema)));
+        Ok(w)
+    }
+
+    /// add the primary key of an existing table, None when the table has no primary columns
+    pub fn build_primary_constraint(&self, table: &Table) -> Result<Option<SqlFrag>, QueryError> {
+        let primary = try!(self.quote_primary_columns(table));
+        if primary.is_empty() {
+            return Ok(None);
+        }
+        let mut w = SqlFrag::new(self.sql_options(), BuildMode::Standard);
+        w.append("ALTER TABLE ");
+        w.append(&try!(self.quote_table_name(&table.to_table_name())));
+        w.append(&format!(" ADD PRIMARY KEY ({})", primary.join(", ")));
+        Ok(Some(w))
+    }
+
+    /// add the foreign keys of an existing table, 1 statement for each of the foreign columns
+    pub fn build_foreign_constraints(&self, table: &Table) -> Result<Vec<SqlFrag>, QueryError> {
+        let mut statements = vec![];
+        for c in self.own_columns(table) {
+            if let Some(ref foreign) = c.foreign {
+                let mut w = SqlFrag::new(self.sql_options(), BuildMode::Standard);
+                w.append("ALTER TABLE ");
+                w.append(&try!(self.quote_table_name(&table.to_table_name())));
+                w.append(" ADD ");
+                w.append(&try!(self.foreign_key_clause(c, foreign)));
+                statements.push(w);
+            }
+        }
+        Ok(statements)
+    }
+
+    /// `COMMENT ON` the table and its columns
+    pub fn build_comments(&self, table: &Table) -> Result<Vec<SqlFrag>, QueryError> {
+        let table_name = try!(self.quote_table_name(&table.to_table_name()));
+        let mut statements = vec![];
+        if let Some(ref comment) = table.comment {
+            let mut w = SqlFrag::new(self.sql_options(), BuildMode::Standard);
+            w.append(&format!("COMMENT ON TABLE {} IS {}", table_name, string_literal(comment)));
+            statements.push(w);
+        }
+        for c in self.own_columns(table) {
+            if let Some(ref comment) = c.comment {
+                let mut w = SqlFrag::new(self.sql_options(), BuildMode::Standard);
+                w.append(&format!("COMMENT ON COLUMN {}.{} IS {}",
+                                  table_name,
+                                  try!(self.quote_identifier(&c.name)),
+                                  string_literal(comment)));
+                statements.push(w);
+            }
+        }
+        Ok(statements)
+    }
+
+    /// execute the DDL statement
+    fn execute_ddl(&self, frag: SqlFrag) -> Result<(), DbError> {
+        try!(self.execute_sql(&frag.sql, &frag.params));
+        Ok(())
+    }
 }
 
 impl DatabaseDDL for Postgres {
 
-    fn create_schema(&self, _schema: &str) {
-        unimplemented!()
+    fn create_schema(&self, schema: &str) -> Result<(), DbError> {
+        self.execute_ddl(try!(self.build_create_schema(schema)))
+    }
+
+    fn drop_schema(&self, schema: &str) -> Result<(), DbError> {
+        self.execute_ddl(try!(self.build_drop_schema(schema)))
+    }
+
+    /// create the table, then set the comments of the table and its columns and create its indexes
+    fn create_table(&self, table: &Table) -> Result<(), DbError> {
+        try!(self.execute_ddl(try!(self.build_create_table(table))));
+        for comment in try!(self.build_comments(table)) {
+            try!(self.execute_ddl(comment));
+        }
+        for index in &table.indexes {
+            try!(self.create_index(table, index));
+        }
+        Ok(())
+    }
+
+    /// the columns with their types, NOT NULL, defaults and unique,
+    /// the primary and foreign keys, and the parent table when inheriting.
+    /// The inherited columns are left to the parent table.
+    fn build_create_table(&self, table: &Table) -> Result<SqlFrag, QueryError> {
+        let mut w = SqlFrag::new(self.sql_options(), BuildMode::Standard);
+        w.append("CREATE TABLE ");
+        w.append(&try!(self.quote_table_name(&table.to_table_name())));
+        w.append(" (");
+        let mut do_comma = false;
+        for c in self.own_columns(table) {
+            if do_comma {
+                w.comma();
+            } else {
+                do_comma = true;
+            }
+            w.ln_tab();
+            try!(self.build_column_definition(&mut w, c));
+        }
+        let primary = try!(self.quote_primary_columns(table));
+        if !primary.is_empty() {
+            if do_comma {
+                w.comma();
+            } else {
+                do_comma = true;
+            }
+            w.ln_tab();
+            w.append(&format!("PRIMARY KEY ({})", primary.join(", ")));
+        }
+        for c in self.own_columns(table) {
+            if let Some(ref foreign) = c.foreign {
+                if do_comma {
+                    w.comma();
+                } else {
+                    do_comma = true;
+                }
+                w.ln_tab();
+                w.append(&try!(self.foreign_key_clause(c, foreign)));
+            }
+        }
+        w.ln();
+        w.append(")");
+        if let Some(ref parent) = table.parent_table {
+            let mut parent = TableName::from_str(parent);
+            if parent.schema.is_none() {
+                parent.schema = table.schema.clone();
+            }
+            w.append(" INHERITS (");
+            w.append(&try!(self.quote_table_name(&parent)));
+            w.append(")");
+        }
+        Ok(w)
+    }
+
+    fn rename_table(&self, table: &Table, new_tablename: String) -> Result<(), DbError> {
+        self.execute_ddl(try!(self.build_rename_table(table, &new_tablename)))
+    }
+
+    fn drop_table(&self, table: &Table) -> Result<(), DbError> {
+        self.execute_ddl(try!(self.build_drop_table(table)))
+    }
+
+    fn set_foreign_constraint(&self, table: &Table) -> Result<(), DbError> {
+        for statement in try!(self.build_foreign_constraints(table)) {
+            try!(self.execute_ddl(statement));
+        }
+        Ok(())
+    }
+
+    fn set_primary_constraint(&self, table: &Table) -> Result<(), DbError> {
+        match try!(self.build_primary_constraint(table)) {
+            Some(statement) => self.execute_ddl(statement),
+            None => Ok(()),
+        }
+    }
+
+    /// `CREATE UNIQUE INDEX "name" ON "schema"."table" USING method (columns) WHERE predicate`
+    fn build_create_index(&self, table: &Table, index: &Index) -> Result<SqlFrag, QueryError> {
+        let mut w = SqlFrag::new(self.sql_options(), BuildMode::Standard);
+        if index.is_unique {
+            w.append("CREATE UNIQUE INDEX ");
+        } else {
+            w.append("CREATE INDEX ");
+        }
+        w.append(&try!(self.quote_identifier(&index.name)));
+        w.append(" ON ");
+        w.append(&try!(self.quote_table_name(&table.to_table_name())));
+        if let Some(ref method) = index.method {
+            w.append(" USING ");
+            w.append(method);
+        }
+        w.append(" (");
+        match index.expression {
+            Some(ref expression) => {
+                w.append(expression);
+            }
+            None => {
+                if index.columns.is_empty() {
+                    return Err(QueryError::NoColumnSpecified(format!("index {}", index.name)));
+                }
+                let mut columns = vec![];
+                for c in &index.columns {
+                    columns.push(try!(self.quote_identifier(c)));
+                }
+                w.append(&columns.join(", "));
+            }
+        }
+        w.append(")");
+        if let Some(ref predicate) = index.predicate {
+            w.append(" WHERE ");
+            w.append(predicate);
+        }
+        Ok(w)
+    }
+
+    fn create_index(&self, table: &Table, index: &Index) -> Result<(), DbError> {
+        self.execute_ddl(try!(self.build_create_index(table, index)))
     }
-    fn drop_schema(&self, _schema: &str) {
-        unimplemented!()
+
+    fn drop_index(&self, table: &Table, index: &Index) -> Result<(), DbError> {
+        self.execute_ddl(try!(self.build_drop_index(table, index)))
     }
-    fn create_table(&self, _model: &Table) {
-        unimplemented!()
+
+    fn build_rename_table(&self, table: &Table, new_tablename: &str) -> Result<SqlFrag, QueryError> {
+        let mut w = try!(self.alter_table(table));
+        w.append("RENAME TO ");
+        w.append(&try!(self.quote_identifier(new_tablename)));
+        Ok(w)
+    }
+
+    fn build_drop_table(&self, table: &Table) -> Result<SqlFrag, QueryError> {
+        let mut w = SqlFrag::new(self.sql_options(), BuildMode::Standard);
+        w.append("DROP TABLE ");
+        w.append(&try!(self.quote_table_name(&table.to_table_name())));
+        Ok(w)
     }
-    fn build_create_table(&self, _table: &Table) -> SqlFrag {
-        unimplemented!()
+
+    /// the index lives in the schema of its table
+    fn build_drop_index(&self, table: &Table, index: &Index) -> Result<SqlFrag, QueryError> {
+        let index_name = TableName {
+            schema: table.schema.clone(),
+            name: index.name.to_owned(),
+            columns: vec![],
+        };
+        let mut w = SqlFrag::new(self.sql_options(), BuildMode::Standard);
+        w.append("DROP INDEX ");
+        w.append(&try!(self.quote_table_name(&index_name)));
+        Ok(w)
     }
-    fn rename_table(&self, _table: &Table, _new_tablename: String) {
-        unimplemented!()
+
+    fn build_add_column(&self, table: &Table, column: &Column) -> Result<SqlFrag, QueryError> {
+        let mut w = try!(self.alter_table(table));
+        w.append("ADD COLUMN ");
+        try!(self.build_column_definition(&mut w, column));
+        Ok(w)
     }
-    fn drop_table(&self, _table: &Table) {
-        unimplemented!()
+
+    fn build_drop_column(&self, table: &Table, column: &Column) -> Result<SqlFrag, QueryError> {
+        let mut w = try!(self.alter_table(table));
+        w.append("DROP COLUMN ");
+        w.append(&try!(self.quote_identifier(&column.name)));
+        Ok(w)
     }
-    fn set_foreign_constraint(&self, _model: &Table) {
-        unimplemented!()
+
+    fn build_rename_column(&self, table: &Table, column: &Column, new_name: &str) -> Result<SqlFrag, QueryError> {
+        let mut w = try!(self.alter_table(table));
+        w.append("RENAME COLUMN ");
+        w.append(&try!(self.quote_identifier(&column.name)));
+        w.append(" TO ");
+        w.append(&try!(self.quote_identifier(new_name)));
+        Ok(w)
     }
-    fn set_primary_constraint(&self, _model: &Table) {
-        unimplemented!()
+
+    /// the existing values are cast to the new type
+    fn build_alter_column_type(&self, table: &Table, column: &Column) -> Result<SqlFrag, QueryError> {
+        let name = try!(self.quote_identifier(&column.name));
+        let data_type = try!(self.data_type_name(&column.data_type));
+        let mut w = try!(self.alter_table(table));
+        w.append(&format!("ALTER COLUMN {} TYPE {} USING {}::{}", name, data_type, name, data_type));
+        Ok(w)
     }
 
+    fn build_alter_column_nullability(&self, table: &Table, column: &Column) -> Result<SqlFrag, QueryError> {
+        let mut w = try!(self.alter_table(table));
+        w.append("ALTER COLUMN ");
+        w.append(&try!(self.quote_identifier(&column.name)));
+        if column.not_null {
+            w.append(" SET NOT NULL");
+        } else {
+            w.append(" DROP NOT NULL");
+        }
+        Ok(w)
+    }
+
+    fn build_alter_column_default(&self, table: &Table, column: &Column) -> Result<SqlFrag, QueryError> {
+        let mut w = try!(self.alter_table(table));
+        w.append("ALTER COLUMN ");
+        w.append(&try!(self.quote_identifier(&column.name)));
+        match column.default {
+            Some(ref default) => {
+                w.append(" SET DEFAULT ");
+                try!(self.build_column_default(&mut w, default));
+            }
+            None => {
+                w.append(" DROP DEFAULT");
+            }
+        }
+        Ok(w)
+    }
+
+    fn build_add_foreign_key(&self, table: &Table, column: &Column) -> Result<SqlFrag, QueryError> {
+        let foreign = match column.foreign {
+            Some(ref foreign) => foreign,
+            None => return Err(QueryError::SqlError(format!("Column {} has no foreign key", column.name))),
+        };
+        let mut w = try!(self.alter_table(table));
+        w.append("ADD CONSTRAINT ");
+        w.append(&try!(self.quote_identifier(&table.foreign_key_name(column))));
+        w.append(" ");
+        w.append(&try!(self.foreign_key_clause(column, foreign)));
+        Ok(w)
+    }
+
+    /// the constraint is dropped by its introspected name
+    fn build_drop_foreign_key(&self, table: &Table, column: &Column) -> Result<SqlFrag, QueryError> {
+        let mut w = try!(self.alter_table(table));
+        w.append("DROP CONSTRAINT ");
+        w.append(&try!(self.quote_identifier(try!(column.foreign_constraint_name()))));
+        Ok(w)
+    }
+}
+
+/// the text as sql string literal, i.e: `'it''s'`
+fn string_literal(text: &str) -> String {
+    format!("'{}'", text.replace("'", "''"))
 }
 
 /// this can be condensed with using just extracting the table definition
@@ -631,6 +1186,7 @@ impl DatabaseDev for Postgres {
             comment: comment,
             columns: columns,
             is_view: is_view,
+            indexes: self.get_table_indexes(schema, table),
         }
     }
 
@@ -793,61 +1349,357 @@ impl DatabaseDev for Postgres {
     /// will be used in generating SQL for table creation
     /// FIXME, need to restore the exact data type as before
     fn rust_type_to_dbtype(&self, rust_type: &Type) -> String {
-        match *rust_type {
-            Type::Bool => {
-                "boolean".to_owned()
-            }
-            Type::I8 => {
-                "char".to_owned()
-            }
-            Type::I16 => {
-                "smallint".to_owned()
-            }
-            Type::I32 => {
-                "integer".to_owned()
-            }
-            Type::U32 => {
-                "oid".to_owned()
-            }
-            Type::I64 => {
-                "bigint".to_owned()
-            }
-            Type::F32 => {
-                "real".to_owned()
-            }
-            Type::F64 => {
-                "double precision".to_owned()
-            }
-            Type::String => {
-                "character varying".to_owned()
-            }
-            Type::VecU8 => {
-                "bytea".to_owned()
-            }
-            Type::Json => {
-                "json".to_owned()
-            }
-            Type::Uuid => {
-                "uuid".to_owned()
-            }
-            Type::NaiveDateTime => {
-                "timestamp".to_owned()
-            }
-            Type::DateTime => {
-                "timestamp with time zone".to_owned()
-            }
-            Type::NaiveDate => {
-                "date".to_owned()
+        match self.dialect.type_name(rust_type) {
+            Some(name) => name,
+            None => panic!("Unable to get the equivalent database data type for {:?}", rust_type),
+        }
+    }
+
+}
+
+/// a notification sent with NOTIFY to one of the listened channels
+#[derive(Debug, Clone, PartialEq)]
+pub struct Notification {
+    /// the process id of the sending backend
+    pub pid: u32,
+    pub channel: String,
+    pub payload: String,
+}
+
+impl Notification {
+
+    /// the payload as Dao, when it is a JSON object
+    pub fn dao(&self) -> Option<Dao> {
+        match Json::from_str(&self.payload) {
+            Ok(Json::Object(_)) => Dao::from_str_one(&self.payload).ok(),
+            _ => None,
+        }
+    }
+}
+
+/// a json parameter, the json support of the postgres crate is behind a `rustc-serialize` feature
+/// which can't be enabled since the dependency is not optional, so it is written the same way here
+#[derive(Debug)]
+struct JsonParam<'a>(&'a Json);
+
+impl<'a> ToSql for JsonParam<'a> {
+    to_sql_checked!();
+
+    fn to_sql<W: Write + ?Sized>(&self, ty: &PgType, out: &mut W, _: &SessionInfo) -> PgResult<IsNull> {
+        if let PgType::Jsonb = *ty {
+            // version 1 of the jsonb binary format
+            try!(out.write_all(&[1]));
+        }
+        try!(write!(out, "{}", self.0));
+        Ok(IsNull::No)
+    }
+
+    accepts!(PgType::Json, PgType::Jsonb);
+}
+
+/// a NULL of an unknown type, accepted for any column
+#[derive(Debug)]
+struct UntypedNull;
+
+impl ToSql for UntypedNull {
+    to_sql_checked!();
+
+    fn to_sql<W: Write + ?Sized>(&self, _: &PgType, _: &mut W, _: &SessionInfo) -> PgResult<IsNull> {
+        Ok(IsNull::Yes)
+    }
+
+    fn accepts(_: &PgType) -> bool {
+        true
+    }
+}
+
+/// the NULL parameter of the type, bound as the same postgresql type as the values of that type
+fn typed_null(data_type: &Type) -> Box<ToSql> {
+    match *data_type {
+        Type::Bool => Box::new(None::<bool>),
+        Type::I8 => Box::new(None::<i8>),
+        Type::I16 | Type::U8 => Box::new(None::<i16>),
+        Type::I32 | Type::U16 => Box::new(None::<i32>),
+        Type::I64 | Type::U64 => Box::new(None::<i64>),
+        Type::U32 => Box::new(None::<u32>),
+        Type::F32 => Box::new(None::<f32>),
+        Type::F64 => Box::new(None::<f64>),
+        Type::String => Box::new(None::<String>),
+        Type::VecU8 => Box::new(None::<Vec<u8>>),
+        Type::Object => Box::new(None::<HashMap<String, Option<String>>>),
+        Type::Json => Box::new(None::<JsonParam<'static>>),
+        Type::Uuid => Box::new(None::<Uuid>),
+        Type::DateTime => Box::new(None::<DateTime<UTC>>),
+        Type::NaiveDate => Box::new(None::<NaiveDate>),
+        Type::NaiveTime => Box::new(None::<NaiveTime>),
+        Type::NaiveDateTime => Box::new(None::<NaiveDateTime>),
+        Type::None => Box::new(UntypedNull),
+    }
+}
+
+/// an object is stored in an hstore column, the values are written as text
+fn to_hstore(object: &BTreeMap<String, Value>) -> HashMap<String, Option<String>> {
+    object.iter()
+          .map(|(k, v)| {
+              let v = match *v {
+                  Value::None(_) => None,
+                  Value::String(ref s) => Some(s.to_owned()),
+                  ref v => Some(format!("{}", v)),
+              };
+              (k.to_owned(), v)
+          })
+          .collect()
+}
+
+/// a json column, read as `JsonParam` is written
+struct JsonColumn(Json);
+
+impl FromSql for JsonColumn {
+    fn from_sql<R: Read>(ty: &PgType, raw: &mut R, _: &SessionInfo) -> PgResult<Self> {
+        if let PgType::Jsonb = *ty {
+            let mut version = [0; 1];
+            try!(raw.read_exact(&mut version));
+            if version[0] != 1 {
+                return Err(PgError::Conversion("unsupported JSONB encoding version".into()));
             }
-            Type::NaiveTime => {
-                "time".to_owned()
+        }
+        Json::from_reader(raw).map(JsonColumn).map_err(|e| PgError::Conversion(Box::new(e)))
+    }
+
+    accepts!(PgType::Json, PgType::Jsonb);
+}
+
+/// a pooled connection listening to channels,
+/// stops listening and discards the pending notifications when dropped,
+/// so the connection goes back to the pool as it was taken
+pub struct Listener {
+    pg: Postgres,
+}
+
+impl Listener {
+
+    pub fn new(pg: Postgres) -> Self {
+        Listener { pg: pg }
+    }
+}
+
+impl Deref for Listener {
+    type Target = Postgres;
+
+    fn deref(&self) -> &Postgres {
+        &self.pg
+    }
+}
+
+impl Drop for Listener {
+    fn drop(&mut self) {
+        if let Err(e) = self.pg.unlisten_all() {
+            warn!("unable to stop listening before returning the connection: {}", e);
+        }
+        for _ in self.pg.get_connection().notifications().iter() {
+        }
+    }
+}
+
+/// iterates the notifications of a connection
+pub struct NotificationIter<'a> {
+    pg: &'a Postgres,
+    timeout: Option<Duration>,
+}
+
+impl <'a>Iterator for NotificationIter<'a> {
+    type Item = Result<Notification, DbError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let notifications = self.pg.get_connection().notifications();
+        let next = match self.timeout {
+            Some(timeout) => notifications.timeout_iter(timeout).next(),
+            None => notifications.blocking_iter().next(),
+        };
+        next.map(|n| {
+            n.map(|n| {
+                 Notification {
+                     pid: n.pid,
+                     channel: n.channel,
+                     payload: n.payload,
+                 }
+             })
+             .map_err(DbError::from)
+        })
+    }
+}
+
+/// encodes the records into CSV lines as `COPY .. FROM STDIN` reads them
+struct CopyInReader<I> {
+    records: I,
+    columns: Vec<String>,
+    /// the encoded line being read
+    buffer: Vec<u8>,
+    position: usize,
+}
+
+impl <I>Read for CopyInReader<I>
+    where I: Iterator<Item = Dao>
+{
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.position >= self.buffer.len() {
+            match self.records.next() {
+                Some(dao) => {
+                    self.buffer = csv_line(&dao, &self.columns).into_bytes();
+                    self.position = 0;
+                }
+                None => return Ok(0),
             }
-            Type::Object => {
-                "hstore".to_owned()
+        }
+        let n = cmp::min(buf.len(), self.buffer.len() - self.position);
+        buf[..n].copy_from_slice(&self.buffer[self.position..self.position + n]);
+        self.position += n;
+        Ok(n)
+    }
+}
+
+/// the text representation of the value, as accepted by postgresql input functions,
+/// None when the value is NULL
+fn to_text(value: &Value) -> Option<String> {
+    match *value {
+        Value::Bool(x) => Some(if x { "t" } else { "f" }.to_owned()),
+        Value::I8(x) => Some(x.to_string()),
+        Value::I16(x) => Some(x.to_string()),
+        Value::I32(x) => Some(x.to_string()),
+        Value::I64(x) => Some(x.to_string()),
+        Value::U8(x) => Some(x.to_string()),
+        Value::U16(x) => Some(x.to_string()),
+        Value::U32(x) => Some(x.to_string()),
+        Value::U64(x) => Some(x.to_string()),
+        Value::F32(x) => Some(float_text(x as f64)),
+        Value::F64(x) => Some(float_text(x)),
+        Value::String(ref x) => Some(x.to_owned()),
+        Value::VecU8(ref x) => {
+            let mut hex = String::from("\\x");
+            for b in x {
+                hex.push_str(&format!("{:02x}", b));
             }
-            _ => panic!("Unable to get the equivalent database data type for {:?}",
-                        rust_type),
+            Some(hex)
         }
+        Value::Object(ref x) => Some(json::encode(x).unwrap_or(String::new())),
+        Value::Json(ref x) => Some(x.to_string()),
+        Value::Uuid(ref x) => Some(x.to_hyphenated_string()),
+        Value::DateTime(ref x) => Some(x.to_rfc3339()),
+        Value::NaiveDate(ref x) => Some(x.to_string()),
+        Value::NaiveTime(ref x) => Some(x.to_string()),
+        Value::NaiveDateTime(ref x) => Some(x.to_string()),
+        Value::None(_) => None,
+    }
+}
+
+fn float_text(x: f64) -> String {
+    if x.is_nan() {
+        "NaN".to_owned()
+    } else if x.is_infinite() {
+        if x > 0.0 { "Infinity" } else { "-Infinity" }.to_owned()
+    } else {
+        x.to_string()
     }
+}
+
+/// a line of the CSV format of COPY, NULL is an unquoted empty field,
+/// the rest are quoted so empty strings are kept
+fn csv_line(dao: &Dao, columns: &[String]) -> String {
+    let mut line = String::new();
+    let mut do_comma = false;
+    for column in columns {
+        if do_comma {
+            line.push(',');
+        } else {
+            do_comma = true;
+        }
+        let text = dao.values.get(column).and_then(to_text);
+        if let Some(text) = text {
+            line.push('"');
+            line.push_str(&text.replace("\"", "\"\""));
+            line.push('"');
+        }
+    }
+    line.push('\n');
+    line
+}
+
+/// the value as SQL literal, i.e: `'it''s'`
+fn to_literal(value: &Value) -> String {
+    match to_text(value) {
+        Some(text) => format!("'{}'", text.replace("'", "''")),
+        None => "NULL".to_owned(),
+    }
+}
+
+/// replace the numbered parameters `$n` of the sql with the literal of their values
+fn inline_params(sql: &str, params: &[Value]) -> Result<String, QueryError> {
+    replace_params(sql, |n| {
+        if n > 0 && n <= params.len() {
+            Ok(to_literal(&params[n - 1]))
+        } else {
+            Err(QueryError::SqlError(format!("No value for parameter ${}", n)))
+        }
+    })
+}
+
+#[test]
+fn test_csv_line() {
+    let mut dao = Dao::new();
+    dao.set_value("name", Value::String("GTX660 \"Ti\", videocard".to_owned()));
+    dao.set_value("photo", Value::VecU8(vec![1, 171]));
+    dao.set_value("is_active", Value::Bool(true));
+    dao.set_value("description", Value::None(Type::String));
+    dao.set_value("note", Value::String(String::new()));
+    let columns = vec!["name".to_owned(), "photo".to_owned(), "is_active".to_owned(),
+                       "description".to_owned(), "note".to_owned(), "price".to_owned()];
+    assert_eq!(csv_line(&dao, &columns),
+               "\"GTX660 \"\"Ti\"\", videocard\",\"\\x01ab\",\"t\",,\"\",\n");
+}
+
+#[test]
+fn test_inline_params() {
+    let params = vec![Value::String("it's".to_owned()), Value::I32(10)];
+    assert_eq!(inline_params("SELECT * FROM \"product\" WHERE \"name\" = $1 AND \"price\" > $2 ", &params)
+                   .unwrap(),
+               "SELECT * FROM \"product\" WHERE \"name\" = 'it''s' AND \"price\" > '10' ");
+    assert_eq!(inline_params("SELECT '$2' FROM \"product\" WHERE \"name\" = $1", &params).unwrap(),
+               "SELECT '$2' FROM \"product\" WHERE \"name\" = 'it''s'");
+    assert!(inline_params("SELECT * FROM \"product\" WHERE \"price\" > $3", &params).is_err());
+}
+
+#[test]
+fn test_notification_dao() {
+    let mut notification = Notification {
+        pid: 1,
+        channel: "product_changed".to_owned(),
+        payload: "{\"name\": \"GTX660\", \"price\": 100}".to_owned(),
+    };
+    let dao = notification.dao().unwrap();
+    assert_eq!(dao.get::<String>("name"), "GTX660");
+    notification.payload = "product 1 changed".to_owned();
+    assert!(notification.dao().is_none());
+    notification.payload = "[1, 2]".to_owned();
+    assert!(notification.dao().is_none());
+}
+
+#[test]
+fn test_from_rust_type_tosql() {
+    let pg = Postgres::new();
+    let mut object = BTreeMap::new();
+    object.insert("color".to_owned(), Value::String("red".to_owned()));
+    let params = vec![Value::U8(1), Value::U16(2), Value::U64(3), Value::Object(object)];
+    assert_eq!(pg.from_rust_type_tosql(&params).unwrap().len(), 4);
+    assert!(pg.from_rust_type_tosql(&[Value::U64(u64::max_value())]).is_err());
+}
 
+#[test]
+fn test_typed_null_params() {
+    let pg = Postgres::new();
+    let types = vec![Type::Bool, Type::I8, Type::I16, Type::I32, Type::I64, Type::U8, Type::U16,
+                     Type::U32, Type::U64, Type::F32, Type::F64, Type::String, Type::VecU8,
+                     Type::Object, Type::Json, Type::Uuid, Type::DateTime, Type::NaiveDate,
+                     Type::NaiveTime, Type::NaiveDateTime, Type::None];
+    let params: Vec<Value> = types.into_iter().map(Value::None).collect();
+    assert_eq!(pg.from_rust_type_tosql(&params).unwrap().len(), params.len());
 }
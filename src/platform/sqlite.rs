@@ -4,44 +4,172 @@ use dao::Dao;
 use dao::Value;
 use database::{Database, DatabaseDev, BuildMode};
 use writer::SqlFrag;
-use database::SqlOption;
 use rusqlite::SqliteConnection;
 use rusqlite::types::ToSql;
 use rusqlite::SqliteRow;
-use table::{Table, Column, Foreign};
+use table::{Table, Column, Foreign, Index};
 use database::DatabaseDDL;
 use database::DbError;
 use r2d2::PooledConnection;
 use r2d2_sqlite::SqliteConnectionManager;
 use regex::Regex;
 use std::collections::BTreeMap;
+use rusqlite::types::Null;
 use dao::Type;
+use dao::DaoResult;
 use query::Operand;
+use query::Error as QueryError;
+use std::cell::Cell;
+use std::i64;
+use uuid::Uuid;
+use rustc_serialize::json::{Json, ToJson};
+use chrono::datetime::DateTime;
+use chrono::naive::date::NaiveDate;
+use chrono::naive::time::NaiveTime;
+use chrono::naive::datetime::NaiveDateTime;
+use chrono::offset::utc::UTC;
+use chrono::offset::fixed::FixedOffset;
+use logger::QueryLog;
+use dialect::Dialect;
+
+/// how dates and timestamps are stored, since sqlite has no date type
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(Copy)]
+#[derive(PartialEq)]
+pub enum DateStorage {
+    /// ISO-8601 text, i.e: `2016-01-25 13:45:00`, works with the sqlite date functions
+    Text,
+    /// the number of seconds since 1970-01-01 00:00:00 UTC, stored as integer
+    /// time of day values are still stored as text
+    UnixEpoch,
+}
+
+/// the sqlite storage classes a Value is bound as
+enum SqliteParam {
+    Integer(i64),
+    Real(f64),
+    Text(String),
+    Blob(Vec<u8>),
+    Null(Null),
+}
+
+const DATETIME_FORMAT: &'static str = "%Y-%m-%d %H:%M:%S%.f";
+const DATE_FORMAT: &'static str = "%Y-%m-%d";
+const TIME_FORMAT: &'static str = "%H:%M:%S%.f";
 
 pub struct Sqlite {
     pool: Option<PooledConnection<SqliteConnectionManager>>,
+    /// whether a transaction has been started on this connection
+    in_transaction: Cell<bool>,
+    /// the storage convention of date and timestamp values
+    date_storage: DateStorage,
+    /// how the executed statements are logged
+    query_log: QueryLog,
+    /// the sql flavor, the version can be narrowed once connected
+    dialect: Dialect,
 }
 
 impl Sqlite {
     pub fn new() -> Self {
-        Sqlite { pool: None }
+        Sqlite {
+            pool: None,
+            in_transaction: Cell::new(false),
+            date_storage: DateStorage::Text,
+            query_log: QueryLog::new(),
+            dialect: Dialect::sqlite(),
+        }
     }
 
     pub fn with_pooled_connection(pool: PooledConnection<SqliteConnectionManager>) -> Self {
-        Sqlite { pool: Some(pool) }
+        Sqlite {
+            pool: Some(pool),
+            in_transaction: Cell::new(false),
+            date_storage: DateStorage::Text,
+            query_log: QueryLog::new(),
+            dialect: Dialect::sqlite(),
+        }
     }
 
-    fn from_rust_type_tosql<'a>(&self, types: &'a [Value]) -> Vec<&'a ToSql> {
-        let mut params: Vec<&ToSql> = vec![];
-        for t in types {
-            match t {
-                &Value::String(ref x) => {
-                    params.push(x);
+    /// dates are stored as ISO-8601 text by default
+    pub fn set_date_storage(&mut self, date_storage: DateStorage) -> &mut Self {
+        self.date_storage = date_storage;
+        self
+    }
+
+    pub fn get_date_storage(&self) -> DateStorage {
+        self.date_storage
+    }
+
+    /// set how the statements executed on this connection are logged
+    pub fn set_query_log(&mut self, query_log: QueryLog) -> &mut Self {
+        self.query_log = query_log;
+        self
+    }
+
+    /// target the sql features of a specific sqlite version
+    pub fn set_dialect(&mut self, dialect: Dialect) -> &mut Self {
+        self.dialect = dialect;
+        self
+    }
+
+    /// convert the values into the storage classes of sqlite
+    fn to_sqlite_params(&self, values: &[Value]) -> Result<Vec<SqliteParam>, DbError> {
+        let epoch = self.date_storage == DateStorage::UnixEpoch;
+        let mut params = vec![];
+        for v in values {
+            let param = match *v {
+                Value::Bool(x) => SqliteParam::Integer(if x { 1 } else { 0 }),
+                Value::I8(x) => SqliteParam::Integer(x as i64),
+                Value::I16(x) => SqliteParam::Integer(x as i64),
+                Value::I32(x) => SqliteParam::Integer(x as i64),
+                Value::I64(x) => SqliteParam::Integer(x),
+                Value::U8(x) => SqliteParam::Integer(x as i64),
+                Value::U16(x) => SqliteParam::Integer(x as i64),
+                Value::U32(x) => SqliteParam::Integer(x as i64),
+                Value::U64(x) if x > i64::MAX as u64 => {
+                    return Err(DbError::new(&format!("{} does not fit in a sqlite integer", x)));
+                }
+                Value::U64(x) => SqliteParam::Integer(x as i64),
+                Value::F32(x) => SqliteParam::Real(x as f64),
+                Value::F64(x) => SqliteParam::Real(x),
+                Value::String(ref x) => SqliteParam::Text(x.to_owned()),
+                Value::VecU8(ref x) => SqliteParam::Blob(x.clone()),
+                Value::Uuid(ref x) => SqliteParam::Text(x.to_hyphenated_string()),
+                Value::Json(ref x) => SqliteParam::Text(x.to_string()),
+                Value::Object(_) => SqliteParam::Text(v.to_json().to_string()),
+                Value::DateTime(ref x) if epoch => SqliteParam::Integer(x.timestamp()),
+                Value::DateTime(ref x) => {
+                    SqliteParam::Text(x.naive_utc().format(DATETIME_FORMAT).to_string())
+                }
+                Value::NaiveDateTime(ref x) if epoch => SqliteParam::Integer(x.timestamp()),
+                Value::NaiveDateTime(ref x) => {
+                    SqliteParam::Text(x.format(DATETIME_FORMAT).to_string())
+                }
+                Value::NaiveDate(ref x) if epoch => {
+                    SqliteParam::Integer(x.and_hms(0, 0, 0).timestamp())
                 }
-                _ => panic!("not yet here {:?}", t),
+                Value::NaiveDate(ref x) => SqliteParam::Text(x.format(DATE_FORMAT).to_string()),
+                Value::NaiveTime(ref x) => SqliteParam::Text(x.format(TIME_FORMAT).to_string()),
+                Value::None(_) => SqliteParam::Null(Null),
+            };
+            params.push(param);
+        }
+        Ok(params)
+    }
+
+    fn from_rust_type_tosql<'a>(&self, params: &'a [SqliteParam]) -> Vec<&'a ToSql> {
+        let mut to_sql: Vec<&ToSql> = vec![];
+        for p in params {
+            match *p {
+                SqliteParam::Integer(ref x) => to_sql.push(x),
+                SqliteParam::Real(ref x) => to_sql.push(x),
+                SqliteParam::Text(ref x) => to_sql.push(x),
+                SqliteParam::Blob(ref x) => to_sql.push(x),
+                SqliteParam::Null(ref x) => to_sql.push(x),
             }
         }
-        params
+        to_sql
     }
 
     pub fn get_connection(&self) -> &SqliteConnection {
@@ -51,96 +179,186 @@ impl Sqlite {
         }
     }
 
-    /// convert a record of a row into rust type
-    fn from_sql_to_rust_type(&self, row: &SqliteRow, index: usize) -> Value {
-        let value = row.get_checked(index as i32);
-        match value {
-            Ok(value) => Value::String(value),
-            Err(_) => Value::None(Type::String),
-        }
-    }
-
-    ///
-    /// convert rust data type names to database data type names
-    /// will be used in generating SQL for table creation
-    /// FIXME, need to restore the exact data type as before
-    fn rust_type_to_dbtype(&self, rust_type: &Type) -> String {
-
-        let rust_type = match *rust_type {
-            Type::Bool => {
-                "boolean".to_owned()
+    /// convert a record of a row into rust type,
+    /// the declared type of the column determines how the stored value is interpreted,
+    /// when not known (Type::None), the value is read as its storage class.
+    /// An integer out of the range of the declared type is an error
+    fn from_sql_to_rust_type(&self, row: &SqliteRow, index: usize, declared: &Type) -> Result<Value, DbError> {
+        let index = index as i32;
+        let integer = || row.get_checked::<i64>(index).ok();
+        let in_range = |min: i64, max: i64| -> Result<Option<i64>, DbError> {
+            match integer() {
+                Some(x) if x < min || x > max => {
+                    Err(DbError::new(&format!("{} in column {} is out of the range of {:?}", x, index, declared)))
+                }
+                x => Ok(x),
             }
-           Type::I8 => {
-                "integer".to_owned()
+        };
+        let real = || {
+            row.get_checked::<f64>(index)
+               .ok()
+               .or(row.get_checked::<i64>(index).ok().map(|x| x as f64))
+        };
+        let text = || row.get_checked::<String>(index).ok();
+        let value = match *declared {
+            Type::Bool => integer().map(|x| Value::Bool(x != 0)),
+            Type::I8 => {
+                try!(in_range(i8::min_value() as i64, i8::max_value() as i64)).map(|x| Value::I8(x as i8))
             }
             Type::I16 => {
-                "integer".to_owned()
+                try!(in_range(i16::min_value() as i64, i16::max_value() as i64)).map(|x| Value::I16(x as i16))
             }
             Type::I32 => {
-                "integer".to_owned()
-            }
-            Type::U32 => {
-                "integer".to_owned()
-            }
-            Type::I64 => {
-                "integer".to_owned()
-            }
-            Type::F32 => {
-                "real".to_owned()
-            }
-            Type::F64 => {
-                "real".to_owned()
-            }
-            Type::String => {
-                "text".to_owned()
-            }
-            Type::VecU8 => {
-                "blob".to_owned()
-            }
-            Type::Json => {
-                "text".to_owned()
-            }
-            Type::Uuid => {
-                "text".to_owned()
+                try!(in_range(i32::min_value() as i64, i32::max_value() as i64)).map(|x| Value::I32(x as i32))
             }
+            Type::I64 => integer().map(|x| Value::I64(x)),
+            Type::U8 => try!(in_range(0, u8::max_value() as i64)).map(|x| Value::U8(x as u8)),
+            Type::U16 => try!(in_range(0, u16::max_value() as i64)).map(|x| Value::U16(x as u16)),
+            Type::U32 => try!(in_range(0, u32::max_value() as i64)).map(|x| Value::U32(x as u32)),
+            Type::U64 => try!(in_range(0, i64::max_value())).map(|x| Value::U64(x as u64)),
+            Type::F32 => real().map(|x| Value::F32(x as f32)),
+            Type::F64 => real().map(|x| Value::F64(x)),
+            Type::String => text().map(|x| Value::String(x)),
+            Type::VecU8 => row.get_checked::<Vec<u8>>(index).ok().map(|x| Value::VecU8(x)),
+            Type::Uuid => text().and_then(|x| Uuid::parse_str(&x).ok()).map(|x| Value::Uuid(x)),
+            Type::Json => text().and_then(|x| Json::from_str(&x).ok()).map(|x| Value::Json(x)),
             Type::NaiveDateTime => {
-                "numeric".to_owned()
+                match integer() {
+                    Some(x) => Some(Value::NaiveDateTime(NaiveDateTime::from_timestamp(x, 0))),
+                    None => text().and_then(|x| parse_datetime(&x)).map(|x| Value::NaiveDateTime(x)),
+                }
             }
             Type::DateTime => {
-                "numeric".to_owned()
+                let naive = match integer() {
+                    Some(x) => Some(NaiveDateTime::from_timestamp(x, 0)),
+                    None => text().and_then(|x| parse_datetime(&x)),
+                };
+                naive.map(|x| Value::DateTime(DateTime::from_utc(x, UTC)))
             }
             Type::NaiveDate => {
-                "numeric".to_owned()
+                match integer() {
+                    Some(x) => Some(Value::NaiveDate(NaiveDateTime::from_timestamp(x, 0).date())),
+                    None => {
+                        text().and_then(|x| NaiveDate::parse_from_str(&x, DATE_FORMAT).ok())
+                              .map(|x| Value::NaiveDate(x))
+                    }
+                }
             }
             Type::NaiveTime => {
-                "numeric".to_owned()
+                text().and_then(|x| NaiveTime::parse_from_str(&x, TIME_FORMAT).ok())
+                      .map(|x| Value::NaiveTime(x))
+            }
+            Type::Object | Type::None => {
+                integer().map(|x| Value::I64(x))
+                         .or(row.get_checked::<f64>(index).ok().map(|x| Value::F64(x)))
+                         .or(text().map(|x| Value::String(x)))
+                         .or(row.get_checked::<Vec<u8>>(index).ok().map(|x| Value::VecU8(x)))
             }
-            _ => panic!("Unable to get the equivalent database data type for {:?}",
-                        rust_type),
         };
-        rust_type
+        match value {
+            Some(value) => Ok(value),
+            None => Ok(Value::None(declared.clone())),
+        }
+    }
 
+    /// the declared types of the columns of the result, in the order of the enumerated fields,
+    /// used to interpret the values read from the result.
+    /// Fields which are not columns of the involved tables, ie: functions, are read as their storage class
+    fn get_declared_types(&self, query: &Query) -> Result<Vec<Type>, DbError> {
+        let mut tables: Vec<(String, Vec<(String, Type)>)> = vec![];
+        for table in query.get_involved_tables() {
+            let mut columns = vec![];
+            for r in try!(self.pragma("table_info", &table.name)) {
+                let column: String = try!(r.try_get("name"));
+                let db_data_type: String = try!(r.try_get("type"));
+                let (_, data_type) = self.dbtype_to_rust_type(&db_data_type);
+                columns.push((column, data_type));
+            }
+            tables.push((table.name, columns));
+        }
+        let mut declared_types = vec![];
+        for field in &query.enumerated_fields {
+            let column = match field.operand {
+                Operand::ColumnName(ref column) => column,
+                _ => {
+                    declared_types.push(Type::None);
+                    continue;
+                }
+            };
+            let in_table = |table: &str| column.table.as_ref().map(|t| t == table).unwrap_or(true);
+            if column.column == "*" {
+                for &(ref table, ref columns) in &tables {
+                    if in_table(table) {
+                        declared_types.extend(columns.iter().map(|&(_, ref data_type)| data_type.clone()));
+                    }
+                }
+            } else {
+                let data_type = tables.iter()
+                                      .filter(|&&(ref table, _)| in_table(table))
+                                      .flat_map(|&(_, ref columns)| columns.iter())
+                                      .find(|&&(ref name, _)| *name == column.column)
+                                      .map(|&(_, ref data_type)| data_type.clone());
+                declared_types.push(data_type.unwrap_or(Type::None));
+            }
+        }
+        Ok(declared_types)
+    }
+
+    /// the declared types are in the order of the columns of the result,
+    /// the remaining columns are read as their storage class
+    fn execute_sql_with_declared_types(&self,
+                                       sql: &str,
+                                       params: &[Value],
+                                       declared_types: &[Type])
+                                       -> Result<Vec<Dao>, DbError> {
+        self.query_log.timed(sql, params, || {
+            let conn = self.get_connection();
+            let mut stmt = try!(conn.prepare(sql));
+            let mut daos = vec![];
+            let sqlite_params = try!(self.to_sqlite_params(params));
+            let param = self.from_rust_type_tosql(&sqlite_params);
+            let mut columns = vec![];
+            for c in stmt.column_names() {
+                columns.push(c.to_owned());
+            }
+            trace!("columns : {:?}", columns);
+            let rows = try!(stmt.query(&param));
+            for row in rows {
+                let row = try!(row);
+                let mut index = 0;
+                let mut dao = Dao::new();
+                for col in &columns {
+                    let declared = declared_types.get(index).unwrap_or(&Type::None);
+                    let rtype = try!(self.from_sql_to_rust_type(&row, index, declared));
+                    dao.set_value(col, rtype);
+                    index += 1;
+                }
+                daos.push(dao);
+            }
+            Ok(daos)
+        })
     }
 
     /// get the foreign keys of table
     fn get_foreign_keys(&self, _schema: &str, table: &str) -> Vec<Foreign> {
-        println!("Extracting foreign keys...");
-        let sql = format!("PRAGMA foreign_key_list({});", table);
-        let result = self.execute_sql_with_return(&sql, &vec![]).unwrap();
-        println!("result: {:#?}", result);
+        trace!("Extracting foreign keys...");
+        let result = self.pragma("foreign_key_list", table).unwrap();
+        trace!("result: {:#?}", result);
         let mut foreigns = vec![];
         for r in result {
             let table: String = r.get("table");
             let from: String = r.get("from");
             let to: String = r.get("to");
-            println!("table: {}", table);
-            println!("from: {}", from);
-            println!("to: {}", to);
+            trace!("table: {}", table);
+            trace!("from: {}", from);
+            trace!("to: {}", to);
 
             let foreign = Foreign {
                 schema: None,
                 table: table.to_owned(),
                 column: to.to_owned(),
+                // the pragma does not name the constraint, sqlite can not drop it anyway
+                name: None,
             };
             foreigns.push(foreign);
         }
@@ -151,21 +369,21 @@ impl Sqlite {
                             (create_sql: &str)
                              -> Result<(Option<String>, BTreeMap<String, Option<String>>), DbError> {
         let re = try!(Regex::new(r".*CREATE\s+TABLE\s+(\S+)\s*\((?s)(.*)\).*"));
-        println!("create_sql: {:?}", create_sql);
+        trace!("create_sql: {:?}", create_sql);
         if re.is_match(&create_sql) {
-            println!("matched...");
+            trace!("matched...");
             let cap = re.captures(&create_sql).unwrap();
             let all_columns = cap.at(2).unwrap();
 
             let line_comma_re = try!(Regex::new(r"[,\n]"));
-            println!("All columns.. {}", all_columns);
+            trace!("All columns.. {}", all_columns);
             let splinters: Vec<&str> = line_comma_re.split(all_columns).collect();
-            println!("splinters: {:#?}", splinters);
+            trace!("splinters: {:#?}", splinters);
             let splinters: Vec<&str> = splinters.into_iter()
                                                 .map(|i| i.trim())
                                                 .filter(|&i| i != "")
                                                 .collect();
-            println!("filtered: {:#?}", splinters);
+            trace!("filtered: {:#?}", splinters);
             let mut columns: Vec<String> = vec![];
             let mut comments: Vec<Option<String>> = vec![];
             let mut index = 0;
@@ -183,14 +401,15 @@ impl Sqlite {
 
                 } else {
                     let line: Vec<&str> = splinter.split_whitespace().collect();
-                    let column = line[0];
-                    println!("column: {}", column);
+                    // the column may have been quoted
+                    let column = line[0].trim_matches(|c| c == '"' || c == '`');
+                    trace!("column: {}", column);
                     columns.push(column.to_owned());
                     index += 1
                 }
             }
-            println!("columns: {:#?}", columns);
-            println!("comments: {:#?}", comments);
+            trace!("columns: {:#?}", columns);
+            trace!("comments: {:#?}", comments);
             let table_comment = if comments.len() > 0 {
                 comments[0].clone() //first comment is the table comment
             } else {
@@ -223,7 +442,7 @@ impl Sqlite {
         let create_sql: String = dao.get("sql");
         match Sqlite::extract_comments(&create_sql) {
             Ok((table_comment, _column_comments)) => {
-                println!("table_comment: {:?}", table_comment);
+                trace!("table_comment: {:?}", table_comment);
                 table_comment
             }
             Err(_) => {
@@ -242,7 +461,7 @@ impl Sqlite {
         let create_sql: String = dao.get("sql");
         match Sqlite::extract_comments(&create_sql) {
             Ok((_table_comment, column_comments)) => {
-                println!("column_comments: {:?}", column_comments);
+                trace!("column_comments: {:?}", column_comments);
                 column_comments
             }
             Err(_) => {
@@ -262,7 +481,7 @@ impl Sqlite {
 
     }
     fn get_column_foreign(&self, all_foreign: &[Foreign], column: &str) -> Option<Foreign> {
-        println!("foreign: {:#?} ", all_foreign);
+        trace!("foreign: {:#?} ", all_foreign);
         for foreign in all_foreign {
             if foreign.column == column {
                 return Some(foreign.clone());
@@ -270,6 +489,75 @@ impl Sqlite {
         }
         None
     }
+
+    /// `ALTER TABLE "table" `, the start of the statements altering the table
+    fn alter_table(&self, table: &Table) -> Result<SqlFrag, QueryError> {
+        let mut w = SqlFrag::new(self.sql_options(), BuildMode::Standard);
+        w.append("ALTER TABLE ");
+        w.append(&try!(self.quote_identifier(&table.name)));
+        w.append(" ");
+        Ok(w)
+    }
+
+    /// most of the column changes need the table to be recreated in sqlite
+    fn unable_to_alter(&self, action: &str, column: &Column) -> QueryError {
+        QueryError::SqlError(format!("Unable to {} column {} in sqlite, the table has to be recreated",
+                                     action,
+                                     column.name))
+    }
+
+    /// the indexes created with `CREATE INDEX`,
+    /// the ones sqlite creates for the primary key and unique constraints have no sql and are left out
+    fn get_table_indexes(&self, table: &str) -> Result<Vec<Index>, DbError> {
+        let result = try!(self.pragma("index_list", table));
+        trace!("result: {:#?}", result);
+        let mut indexes = vec![];
+        for r in result {
+            let name: String = try!(r.try_get("name"));
+            let unique: i64 = try!(r.try_get("unique"));
+            let index_sql = match try!(self.get_index_sql(&name)) {
+                Some(index_sql) => index_sql,
+                None => continue,
+            };
+            let (keys, predicate) = parse_index_sql(&index_sql);
+            let info = try!(self.pragma("index_info", &name));
+            let mut columns: Vec<Option<String>> = vec![];
+            for i in &info {
+                columns.push(try!(i.try_get_opt("name")));
+            }
+            // an expression has no column name
+            let (columns, expression) = if columns.iter().any(|c| c.is_none()) {
+                (vec![], keys)
+            } else {
+                (columns.into_iter().filter_map(|c| c).collect(), None)
+            };
+            indexes.push(Index {
+                name: name,
+                columns: columns,
+                is_unique: unique != 0,
+                method: None,
+                predicate: predicate,
+                expression: expression,
+            });
+        }
+        Ok(indexes)
+    }
+
+    /// `PRAGMA pragma("name")`, the table or index name is quoted
+    /// since the pragma argument is parsed as an identifier
+    fn pragma(&self, pragma: &str, name: &str) -> Result<Vec<Dao>, DbError> {
+        let sql = format!("PRAGMA {}({});", pragma, try!(self.quote_identifier(name)));
+        self.execute_sql_with_return(&sql, &vec![])
+    }
+
+    fn get_index_sql(&self, index: &str) -> Result<Option<String>, DbError> {
+        let sql = "SELECT sql FROM sqlite_master WHERE type = 'index' AND name = ?";
+        let result = try!(self.execute_sql_with_return(sql, &[Value::String(index.to_owned())]));
+        match result.get(0) {
+            Some(dao) => dao.try_get_opt("sql"),
+            None => Ok(None),
+        }
+    }
 }
 
 impl Database for Sqlite {
@@ -277,21 +565,27 @@ impl Database for Sqlite {
         let sql = "SELECT sqlite_version() AS version";
         let dao = try!(self.execute_sql_with_one_return(sql, &vec![]));
         match dao {
-            Some(dao) => Ok(dao.get("version")),
+            Some(dao) => dao.try_get("version"),
             None => Err(DbError::new("Unable to get database version")),
         }
     }
-    fn begin(&self) {
-        unimplemented!()
+    fn begin(&self) -> Result<(), DbError> {
+        try!(self.execute_sql("BEGIN", &[]));
+        self.in_transaction.set(true);
+        Ok(())
     }
-    fn commit(&self) {
-        unimplemented!()
+    fn commit(&self) -> Result<(), DbError> {
+        self.in_transaction.set(false);
+        try!(self.execute_sql("COMMIT", &[]));
+        Ok(())
     }
-    fn rollback(&self) {
-        unimplemented!()
+    fn rollback(&self) -> Result<(), DbError> {
+        self.in_transaction.set(false);
+        try!(self.execute_sql("ROLLBACK", &[]));
+        Ok(())
     }
     fn is_transacted(&self) -> bool {
-        false
+        self.in_transaction.get()
     }
     fn is_closed(&self) -> bool {
         false
@@ -305,63 +599,57 @@ impl Database for Sqlite {
     fn is_valid(&self) -> bool {
         false
     }
-    fn reset(&self) {
-        unimplemented!()
+    fn reset(&self) -> Result<(), DbError> {
+        if self.is_transacted() {
+            try!(self.rollback());
+        }
+        Ok(())
+    }
+
+    fn query_log(&self) -> &QueryLog {
+        &self.query_log
     }
 
-    /// return this list of options, supported features in the database
-    fn sql_options(&self) -> Vec<SqlOption> {
-        vec![
-            SqlOption::UsesNumberedParam,  // uses numbered parameters
-            SqlOption::SupportsCTE,
-        ]
+    fn dialect(&self) -> &Dialect {
+        &self.dialect
     }
 
     fn insert(&self, query: &Query) -> Result<Dao, DbError> {
-        let sql_frag = self.build_insert(query, BuildMode::Standard);
+        let sql_frag = try!(self.build_insert(query, BuildMode::Standard));
         match self.execute_sql_with_one_return(&sql_frag.sql, &sql_frag.params) {
             Ok(Some(result)) => Ok(result),
             Ok(None) => Err(DbError::new("No result from insert")),
             Err(e) => Err(e),
         }
     }
-    fn update(&self, _query: &Query) -> Dao {
-        unimplemented!()
+
+    /// the values of the query are read according to the declared types of the involved tables
+    fn execute_with_return(&self, query: &Query) -> Result<DaoResult, DbError> {
+        try!(self.check_query_support(query));
+        let declared_types = try!(self.get_declared_types(query));
+        let sql_frag = &try!(self.build_query(query, BuildMode::Standard));
+        let result = try!(self.execute_sql_with_declared_types(&sql_frag.sql,
+                                                               &sql_frag.params,
+                                                               &declared_types));
+        let dao_result = DaoResult {
+            dao: result,
+            renamed_columns: query.get_renamed_columns(),
+            total: None,
+            page: None,
+            page_size: None,
+        };
+        Ok(dao_result)
     }
-    fn delete(&self, _query: &Query) -> Result<usize, String> {
-        unimplemented!()
+
+    fn execute_with_one_return(&self, query: &Query) -> Result<Option<Dao>, DbError> {
+        let result = try!(self.execute_with_return(query));
+        Ok(result.dao.into_iter().next())
     }
 
-    /// sqlite does not return the columns mentioned in the query,
-    /// you have to specify it yourself
-    /// TODO: found this
-    /// http://jgallagher.github.io/rusqlite/rusqlite/struct.SqliteStatement.html#method.column_names
+    /// the declared types of the columns are not known in plain sql,
+    /// values are read as their storage class: I64, F64, String or VecU8
     fn execute_sql_with_return(&self, sql: &str, params: &[Value]) -> Result<Vec<Dao>, DbError> {
-        println!("SQL: \n{}", sql);
-        println!("param: {:?}", params);
-        let conn = self.get_connection();
-        let mut stmt = conn.prepare(sql).unwrap();
-        let mut daos = vec![];
-        let param = self.from_rust_type_tosql(params);
-        let mut columns = vec![];
-        for c in stmt.column_names() {
-            columns.push(c.to_owned());
-        }
-        println!("columns : {:?}", columns);
-        let rows = try!(stmt.query(&param));
-        for row in rows {
-            let row = try!(row);
-            let mut index = 0;
-            let mut dao = Dao::new();
-            for col in &columns {
-                let rtype = self.from_sql_to_rust_type(&row, index);
-                println!("{:?}", rtype);
-                dao.set_value(col, rtype);
-                index += 1;
-            }
-            daos.push(dao);
-        }
-        Ok(daos)
+        self.execute_sql_with_declared_types(sql, params, &[])
     }
 
 
@@ -381,38 +669,32 @@ impl Database for Sqlite {
     /// returns only the number of affected records or errors
     /// can be used with DDL operations (CREATE, DELETE, ALTER, DROP)
     fn execute_sql(&self, sql: &str, params: &[Value]) -> Result<usize, DbError> {
-        println!("SQL: \n{}", sql);
-        println!("param: {:?}", params);
-        let to_sql_types = self.from_rust_type_tosql(params);
-        let conn = self.get_connection();
-        let result = conn.execute(sql, &to_sql_types);
-        match result {
-            Ok(result) => {
-                Ok(result as usize)
-            }
-            Err(e) => {
-                Err(DbError::new(&format!("Something is wrong, {}", e)))
-            }
-        }
+        self.query_log.timed(sql, params, || {
+            let sqlite_params = try!(self.to_sqlite_params(params));
+            let to_sql_types = self.from_rust_type_tosql(&sqlite_params);
+            let conn = self.get_connection();
+            let result = try!(conn.execute(sql, &to_sql_types));
+            Ok(result as usize)
+        })
     }
 
 }
 
 impl DatabaseDDL for Sqlite {
-    fn create_schema(&self, _schema: &str) {
-        panic!("sqlite does not support schema")
+    fn create_schema(&self, _schema: &str) -> Result<(), DbError> {
+        Err(DbError::new("sqlite does not support schema"))
     }
 
-    fn drop_schema(&self, _schema: &str) {
-        panic!("sqlite does not support schema")
+    fn drop_schema(&self, _schema: &str) -> Result<(), DbError> {
+        Err(DbError::new("sqlite does not support schema"))
     }
 
-    fn build_create_table(&self, table: &Table) -> SqlFrag {
+    fn build_create_table(&self, table: &Table) -> Result<SqlFrag, QueryError> {
 
 
         let mut w = SqlFrag::new(self.sql_options(), BuildMode::Standard);
         w.append("CREATE TABLE ");
-        w.append(&table.name);
+        w.append(&try!(self.quote_identifier(&table.name)));
         w.append("(");
         w.ln_tab();
         let mut do_comma = false;
@@ -423,13 +705,16 @@ impl DatabaseDDL for Sqlite {
             } else {
                 do_comma = true;
             }
-            w.append(&c.name);
+            w.append(&try!(self.quote_identifier(&c.name)));
             w.append(" ");
-            let dt = self.rust_type_to_dbtype(&c.data_type);
-            w.append(&dt);
+            w.append(&try!(self.data_type_name(&c.data_type)));
             if c.is_primary {
                 w.append(" PRIMARY KEY ");
             }
+            if let Some(ref default) = c.default {
+                w.append(" DEFAULT ");
+                try!(self.build_column_default(&mut w, default));
+            }
         }
 		let mut do_comma = true;//there has been colcommentsumns mentioned
 		for c in &table.columns {
@@ -441,39 +726,205 @@ impl DatabaseDDL for Sqlite {
 				}
 				w.ln_tab();
 				w.append("FOREIGN KEY");
-				w.append(&format!("({})", c.name));
+				w.append(&format!("({})", try!(self.quote_identifier(&c.name))));
 				w.append(" REFERENCES ");
-				w.append(&format!("{}", foreign.table));
-				w.append(&format!("({})", foreign.column));
+				w.append(&try!(self.quote_identifier(&foreign.table)));
+				w.append(&format!("({})", try!(self.quote_identifier(&foreign.column))));
 			}
 		}
         w.ln();
         w.append(")");
-        w
+        Ok(w)
     }
-    fn create_table(&self, table: &Table) {
-        let frag = self.build_create_table(table);
-        match self.execute_sql(&frag.sql, &vec![]) {
-            Ok(_) => println!("created table.."),
-            Err(e) => panic!("table not created {}", e),
+    fn create_table(&self, table: &Table) -> Result<(), DbError> {
+        let frag = try!(self.build_create_table(table));
+        try!(self.execute_sql(&frag.sql, &frag.params));
+        debug!("created table..");
+        for index in &table.indexes {
+            try!(self.create_index(table, index));
         }
+        Ok(())
     }
 
-    fn rename_table(&self, _table: &Table, _new_tablename: String) {
-        unimplemented!()
+    fn rename_table(&self, table: &Table, new_tablename: String) -> Result<(), DbError> {
+        let frag = try!(self.build_rename_table(table, &new_tablename));
+        try!(self.execute_sql(&frag.sql, &frag.params));
+        debug!("renamed table..");
+        Ok(())
     }
 
-    fn drop_table(&self, _table: &Table) {
-        unimplemented!()
+    fn drop_table(&self, table: &Table) -> Result<(), DbError> {
+        let frag = try!(self.build_drop_table(table));
+        try!(self.execute_sql(&frag.sql, &frag.params));
+        debug!("dropped table..");
+        Ok(())
     }
 
-    fn set_foreign_constraint(&self, _model: &Table) {
-        unimplemented!()
+    fn set_foreign_constraint(&self, _model: &Table) -> Result<(), DbError> {
+        Err(DbError::new("sqlite can not add the foreign keys of an existing table"))
     }
 
-    fn set_primary_constraint(&self, _model: &Table) {
-        unimplemented!()
+    fn set_primary_constraint(&self, _model: &Table) -> Result<(), DbError> {
+        Err(DbError::new("sqlite can not add the primary key of an existing table"))
+    }
+
+    /// sqlite has only one index method, so it is ignored
+    fn build_create_index(&self, table: &Table, index: &Index) -> Result<SqlFrag, QueryError> {
+        let mut w = SqlFrag::new(self.sql_options(), BuildMode::Standard);
+        if index.is_unique {
+            w.append("CREATE UNIQUE INDEX ");
+        } else {
+            w.append("CREATE INDEX ");
+        }
+        w.append(&try!(self.quote_identifier(&index.name)));
+        w.append(" ON ");
+        w.append(&try!(self.quote_identifier(&table.name)));
+        w.append(" (");
+        match index.expression {
+            Some(ref expression) => {
+                w.append(expression);
+            }
+            None => {
+                if index.columns.is_empty() {
+                    return Err(QueryError::NoColumnSpecified(format!("index {}", index.name)));
+                }
+                let mut columns = vec![];
+                for c in &index.columns {
+                    columns.push(try!(self.quote_identifier(c)));
+                }
+                w.append(&columns.join(", "));
+            }
+        }
+        w.append(")");
+        if let Some(ref predicate) = index.predicate {
+            w.append(" WHERE ");
+            w.append(predicate);
+        }
+        Ok(w)
+    }
+
+    fn create_index(&self, table: &Table, index: &Index) -> Result<(), DbError> {
+        let frag = try!(self.build_create_index(table, index));
+        try!(self.execute_sql(&frag.sql, &frag.params));
+        debug!("created index..");
+        Ok(())
+    }
+
+    fn drop_index(&self, table: &Table, index: &Index) -> Result<(), DbError> {
+        let frag = try!(self.build_drop_index(table, index));
+        try!(self.execute_sql(&frag.sql, &frag.params));
+        debug!("dropped index..");
+        Ok(())
+    }
+
+    fn build_rename_table(&self, table: &Table, new_tablename: &str) -> Result<SqlFrag, QueryError> {
+        let mut w = try!(self.alter_table(table));
+        w.append("RENAME TO ");
+        w.append(&try!(self.quote_identifier(new_tablename)));
+        Ok(w)
+    }
+
+    fn build_drop_table(&self, table: &Table) -> Result<SqlFrag, QueryError> {
+        let mut w = SqlFrag::new(self.sql_options(), BuildMode::Standard);
+        w.append("DROP TABLE ");
+        w.append(&try!(self.quote_identifier(&table.name)));
+        Ok(w)
+    }
+
+    fn build_drop_index(&self, _table: &Table, index: &Index) -> Result<SqlFrag, QueryError> {
+        let mut w = SqlFrag::new(self.sql_options(), BuildMode::Standard);
+        w.append("DROP INDEX ");
+        w.append(&try!(self.quote_identifier(&index.name)));
+        Ok(w)
+    }
+
+    fn build_add_column(&self, table: &Table, column: &Column) -> Result<SqlFrag, QueryError> {
+        let mut w = try!(self.alter_table(table));
+        w.append("ADD COLUMN ");
+        try!(self.build_column_definition(&mut w, column));
+        Ok(w)
+    }
+
+    /// since sqlite 3.35
+    fn build_drop_column(&self, table: &Table, column: &Column) -> Result<SqlFrag, QueryError> {
+        if !self.dialect.is_at_least(3, 35, 0) {
+            return Err(self.unable_to_alter("drop", column));
+        }
+        let mut w = try!(self.alter_table(table));
+        w.append("DROP COLUMN ");
+        w.append(&try!(self.quote_identifier(&column.name)));
+        Ok(w)
+    }
+
+    /// since sqlite 3.25
+    fn build_rename_column(&self, table: &Table, column: &Column, new_name: &str) -> Result<SqlFrag, QueryError> {
+        if !self.dialect.is_at_least(3, 25, 0) {
+            return Err(self.unable_to_alter("rename", column));
+        }
+        let mut w = try!(self.alter_table(table));
+        w.append("RENAME COLUMN ");
+        w.append(&try!(self.quote_identifier(&column.name)));
+        w.append(" TO ");
+        w.append(&try!(self.quote_identifier(new_name)));
+        Ok(w)
+    }
+
+    fn build_alter_column_type(&self, _table: &Table, column: &Column) -> Result<SqlFrag, QueryError> {
+        Err(self.unable_to_alter("change the type of", column))
+    }
+
+    fn build_alter_column_nullability(&self, _table: &Table, column: &Column) -> Result<SqlFrag, QueryError> {
+        Err(self.unable_to_alter("change the nullability of", column))
+    }
+
+    fn build_alter_column_default(&self, _table: &Table, column: &Column) -> Result<SqlFrag, QueryError> {
+        Err(self.unable_to_alter("change the default of", column))
+    }
+
+    fn build_add_foreign_key(&self, _table: &Table, column: &Column) -> Result<SqlFrag, QueryError> {
+        Err(self.unable_to_alter("add the foreign key of", column))
+    }
+
+    fn build_drop_foreign_key(&self, _table: &Table, column: &Column) -> Result<SqlFrag, QueryError> {
+        Err(self.unable_to_alter("drop the foreign key of", column))
+    }
+}
+
+/// the key list and the predicate of a `CREATE INDEX` statement,
+/// ie: `CREATE INDEX idx ON product (lower(name)) WHERE active` gives `lower(name)` and `active`
+fn parse_index_sql(sql: &str) -> (Option<String>, Option<String>) {
+    let upper = sql.to_ascii_uppercase();
+    let start = match upper.find(" ON ").and_then(|on| sql[on..].find('(').map(|p| on + p)) {
+        Some(start) => start,
+        None => return (None, None),
+    };
+    let mut depth = 0;
+    let mut end = None;
+    for (i, c) in sql[start..].char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => {
+                depth -= 1;
+                if depth == 0 {
+                    end = Some(start + i);
+                    break;
+                }
+            }
+            _ => (),
+        }
     }
+    let end = match end {
+        Some(end) => end,
+        None => return (None, None),
+    };
+    let keys = sql[start + 1..end].trim().to_owned();
+    let rest = sql[end + 1..].trim();
+    let predicate = if rest.to_ascii_uppercase().starts_with("WHERE") {
+        Some(rest[5..].trim().to_owned())
+    } else {
+        None
+    };
+    (Some(keys), predicate)
 }
 
 impl DatabaseDev for Sqlite {
@@ -486,10 +937,9 @@ impl DatabaseDev for Sqlite {
     }
 
     fn get_table_metadata(&self, schema: &str, table: &str, _is_view: bool) -> Table {
-        println!("extracting table meta data in sqlite");
-        let sql = format!("PRAGMA table_info({});", table);
-        let result = self.execute_sql_with_return(&sql, &vec![]);
-        println!("result: {:#?}", result);
+        trace!("extracting table meta data in sqlite");
+        let result = self.pragma("table_info", table);
+        trace!("result: {:#?}", result);
         match result {
             Ok(result) => {
                 let foreign = self.get_foreign_keys(schema, table);
@@ -500,14 +950,14 @@ impl DatabaseDev for Sqlite {
                 for r in result {
                     let column: String = r.get("name");
                     let db_data_type: String = r.get("type");
-                    let default_value: String = r.get("dflt_value");
-                    let not_null: String = r.get("notnull");
-                    let pk: String = r.get("pk");
-                    println!("column: {}", column);
-                    println!("data_type: {}", db_data_type);
-                    println!("not null: {}", not_null);
-                    println!("pk: {}", pk);
-                    println!("default_value: {}", default_value);
+                    let default_value: Option<String> = r.get_opt("dflt_value");
+                    let not_null: i64 = r.get("notnull");
+                    let pk: i64 = r.get("pk");
+                    trace!("column: {}", column);
+                    trace!("data_type: {}", db_data_type);
+                    trace!("not null: {}", not_null);
+                    trace!("pk: {}", pk);
+                    trace!("default_value: {:?}", default_value);
 
                     let column_comment = self.get_column_comment(&column_comments, &column);
                     let column_foreign = self.get_column_foreign(&foreign, &column);
@@ -516,11 +966,11 @@ impl DatabaseDev for Sqlite {
                         name: column,
                         data_type: data_type,
                         db_data_type: db_data_type,
-                        is_primary: pk != "0",
+                        is_primary: pk != 0,
                         is_unique: false,
-                        default: Some(Operand::Value(Value::String(default_value))),
+                        default: default_value.map(Operand::Sql),
                         comment: column_comment,
-                        not_null: not_null != "0",
+                        not_null: not_null != 0,
                         is_inherited: false,
                         foreign: column_foreign,
                     };
@@ -534,6 +984,13 @@ impl DatabaseDev for Sqlite {
                     comment: table_comment,
                     columns: columns,
                     is_view: false,
+                    indexes: match self.get_table_indexes(table) {
+                        Ok(indexes) => indexes,
+                        Err(e) => {
+                            warn!("Unable to get the indexes of {}, leaving them out: {}", table, e);
+                            vec![]
+                        }
+                    },
                 }
             }
             Err(e) => {
@@ -566,15 +1023,142 @@ impl DatabaseDev for Sqlite {
         vec![]
     }
 
-    fn dbtype_to_rust_type(&self, _db_type: &str) -> (Vec<String>, Type) {
-        unimplemented!()
+    /// the well known type names are matched first,
+    /// the rest follows the sqlite column affinity rules
+    /// https://www.sqlite.org/datatype3.html#determination_of_column_affinity
+    fn dbtype_to_rust_type(&self, db_type: &str) -> (Vec<String>, Type) {
+        let db_type = db_type.trim().to_lowercase();
+        match &*db_type {
+            "boolean" | "bool" => {
+                (vec![], Type::Bool)
+            }
+            "tinyint" => {
+                (vec![], Type::I8)
+            }
+            "smallint" | "int2" => {
+                (vec![], Type::I16)
+            }
+            "int" | "int4" | "mediumint" => {
+                (vec![], Type::I32)
+            }
+            "float" => {
+                (vec![], Type::F32)
+            }
+            "json" | "jsonb" => {
+                (vec!["rustc_serialize::json::Json".to_owned()], Type::Json)
+            }
+            "uuid" => {
+                (vec!["uuid::Uuid".to_owned()], Type::Uuid)
+            }
+            "timestamp with time zone" | "timestamptz" => {
+                (vec!["chrono::datetime::DateTime".to_owned(),
+                      "chrono::offset::utc::UTC".to_owned()],
+                 Type::DateTime)
+            }
+            "datetime" | "timestamp" | "timestamp without time zone" => {
+                (vec!["chrono::naive::datetime::NaiveDateTime".to_owned()],
+                 Type::NaiveDateTime)
+            }
+            "date" => {
+                (vec!["chrono::naive::date::NaiveDate".to_owned()], Type::NaiveDate)
+            }
+            "time" | "time without time zone" | "time with time zone" => {
+                (vec!["chrono::naive::time::NaiveTime".to_owned()], Type::NaiveTime)
+            }
+            _ => {
+                if db_type.contains("int") {
+                    (vec![], Type::I64)
+                } else if db_type.contains("char") || db_type.contains("clob") ||
+                   db_type.contains("text") {
+                    (vec![], Type::String)
+                } else if db_type.contains("blob") || db_type.is_empty() {
+                    (vec![], Type::VecU8)
+                } else {
+                    // REAL and NUMERIC affinity
+                    (vec![], Type::F64)
+                }
+            }
+        }
     }
 
-    fn rust_type_to_dbtype(&self, _rust_type: &Type) -> String {
-        unimplemented!()
+    /// convert rust data type names to database data type names
+    /// will be used in generating SQL for table creation,
+    /// the names are chosen such that dbtype_to_rust_type restores the same type
+    fn rust_type_to_dbtype(&self, rust_type: &Type) -> String {
+        match self.dialect.type_name(rust_type) {
+            Some(name) => name,
+            None => panic!("Unable to get the equivalent database data type for {:?}", rust_type),
+        }
+    }
+
+}
+
+/// parse the ISO-8601 text of a timestamp, with or without the `T` separator and fraction of seconds
+fn parse_datetime(text: &str) -> Option<NaiveDateTime> {
+    if let Ok(dt) = DateTime::<FixedOffset>::parse_from_rfc3339(text) {
+        return Some(dt.with_timezone(&UTC).naive_utc());
+    }
+    let formats = ["%Y-%m-%d %H:%M:%S%.f",
+                   "%Y-%m-%dT%H:%M:%S%.f",
+                   "%Y-%m-%d %H:%M:%S",
+                   "%Y-%m-%dT%H:%M:%S",
+                   "%Y-%m-%d %H:%M"];
+    for format in &formats {
+        if let Ok(dt) = NaiveDateTime::parse_from_str(text, format) {
+            return Some(dt);
+        }
+    }
+    NaiveDate::parse_from_str(text, DATE_FORMAT).ok().map(|d| d.and_hms(0, 0, 0))
+}
+
+
+#[test]
+fn test_declared_type_affinity() {
+    let lite = Sqlite::new();
+    assert_eq!(lite.dbtype_to_rust_type("INTEGER").1, Type::I64);
+    assert_eq!(lite.dbtype_to_rust_type("varchar(255)").1, Type::String);
+    assert_eq!(lite.dbtype_to_rust_type("").1, Type::VecU8);
+    assert_eq!(lite.dbtype_to_rust_type("double precision").1, Type::F64);
+    assert_eq!(lite.dbtype_to_rust_type("decimal(10,2)").1, Type::F64);
+    for t in &[Type::Bool, Type::I8, Type::I16, Type::I32, Type::I64, Type::F32, Type::F64,
+               Type::String, Type::VecU8, Type::Json, Type::Uuid, Type::NaiveDateTime,
+               Type::DateTime, Type::NaiveDate, Type::NaiveTime] {
+        let db_type = lite.rust_type_to_dbtype(t);
+        assert_eq!(&lite.dbtype_to_rust_type(&db_type).1, t);
+    }
+}
+
+#[test]
+fn test_to_sqlite_params() {
+    use std::u64;
+
+    let lite = Sqlite::new();
+    let mut object = BTreeMap::new();
+    object.insert("name".to_owned(), Value::String("Toys".to_owned()));
+    match lite.to_sqlite_params(&[Value::Object(object), Value::U64(i64::MAX as u64)]) {
+        Ok(params) => {
+            match params[0] {
+                SqliteParam::Text(ref json) => assert_eq!(json, "{\"name\":\"Toys\"}"),
+                _ => panic!("expecting the object as json text"),
+            }
+            match params[1] {
+                SqliteParam::Integer(x) => assert_eq!(x, i64::MAX),
+                _ => panic!("expecting an integer"),
+            }
+        }
+        Err(e) => panic!("{}", e),
     }
+    assert!(lite.to_sqlite_params(&[Value::U64(u64::MAX)]).is_err());
 }
 
+#[test]
+fn test_parse_datetime() {
+    let expected = NaiveDate::from_ymd(2016, 1, 25).and_hms(13, 45, 0);
+    assert_eq!(parse_datetime("2016-01-25 13:45:00"), Some(expected));
+    assert_eq!(parse_datetime("2016-01-25T13:45:00.000"), Some(expected));
+    assert_eq!(parse_datetime("2016-01-25T21:45:00+08:00"), Some(expected));
+    assert_eq!(parse_datetime("not a date"), None);
+}
 
 #[test]
 fn test_comment_extract() {
@@ -595,3 +1179,42 @@ CREATE TABLE product_availability (
     ";
     let _ = Sqlite::extract_comments(create_sql);
 }
+
+#[test]
+fn test_parse_index_sql() {
+    assert_eq!(parse_index_sql("CREATE INDEX idx_name ON product (lower(name), price) WHERE active = 1"),
+               (Some("lower(name), price".to_owned()), Some("active = 1".to_owned())));
+    assert_eq!(parse_index_sql("CREATE UNIQUE INDEX \"idx_sku\" ON \"product\"(\"sku\")"),
+               (Some("\"sku\"".to_owned()), None));
+}
+
+#[test]
+fn test_alter_column_by_version() {
+    use table::Table;
+    use dialect::Version;
+
+    let table = Table {
+        name: "product".to_owned(),
+        ..Default::default()
+    };
+    let column = Column {
+        name: "price".to_owned(),
+        data_type: Type::F64,
+        db_data_type: "real".to_owned(),
+        is_primary: false,
+        is_unique: false,
+        default: None,
+        comment: None,
+        not_null: false,
+        foreign: None,
+        is_inherited: false,
+    };
+    let mut lite = Sqlite::new();
+    assert!(lite.build_drop_column(&table, &column).is_err());
+    assert!(lite.build_rename_column(&table, &column, "cost").is_err());
+    let mut dialect = Dialect::sqlite();
+    dialect.version(Version::new(3, 35, 0));
+    lite.set_dialect(dialect);
+    assert!(lite.build_drop_column(&table, &column).is_ok());
+    assert!(lite.build_rename_column(&table, &column, "cost").is_ok());
+}
@@ -5,6 +5,7 @@ use postgres::SslMode;
 use config::DbConfig;
 use database::{Database, DatabaseDDL, DatabaseDev};
 use platform::Postgres;
+use platform::postgres::Listener;
 #[cfg(feature = "sqlite")]
 use platform::Sqlite;
 #[cfg(feature = "mysql")]
@@ -14,11 +15,23 @@ use mysql::conn::pool::MyPool;
 #[cfg(feature = "mysql")]
 use mysql::conn::MyOpts;
 use database::DbError;
+use transaction::Transaction;
+use logger::QueryLog;
+use dialect::{Dialect, Version};
 
 #[cfg(feature = "sqlite")]
 use r2d2_sqlite::SqliteConnectionManager;
 
 
+/// the dialect of the connected database, targeting the version reported by the server
+fn server_dialect(db: &Database) -> Result<Dialect, DbError> {
+    let mut dialect = db.dialect().clone();
+    if let Some(version) = Version::parse(&try!(db.version())) {
+        dialect.version(version);
+    }
+    Ok(dialect)
+}
+
 /// the sql builder for each of the database platform
 pub enum Platform {
     Postgres(Postgres),
@@ -52,6 +65,31 @@ impl Platform {
         }
     }
 
+    /// begin a transaction on this connection,
+    /// it is rolled back when dropped without being committed
+    pub fn transaction(&self) -> Result<Transaction, DbError> {
+        Transaction::begin(self.as_ref())
+    }
+
+    /// set how the statements executed on this connection are logged
+    pub fn set_query_log(&mut self, query_log: QueryLog) -> Result<(), DbError> {
+        match *self {
+            Platform::Postgres(ref mut pg) => {
+                pg.set_query_log(query_log);
+            }
+            #[cfg(feature = "sqlite")]
+            Platform::Sqlite(ref mut lite) => {
+                lite.set_query_log(query_log);
+            }
+            #[cfg(feature = "mysql")]
+            Platform::Mysql(ref mut my) => {
+                my.set_query_log(query_log);
+            }
+            Platform::Oracle => return Err(DbError::new("Oracle is not yet supported")),
+        }
+        Ok(())
+    }
+
     pub fn as_dev(&self) -> &DatabaseDev {
         match *self {
             Platform::Postgres(ref pg) => pg,
@@ -83,7 +121,7 @@ impl ManagedPool {
                 match platform {
                     "postgres" => {
                         let manager = try!(PostgresConnectionManager::new(url, SslMode::None));
-                        println!("Creating a connection with a pool size of {}", pool_size);
+                        info!("Creating a connection with a pool size of {}", pool_size);
                         let config = Config::builder().pool_size(pool_size as u32).build();
                         let pool = try!(Pool::new(config, manager));
                         Ok(ManagedPool::Postgres(pool))
@@ -114,45 +152,70 @@ impl ManagedPool {
                 }
             }
             None => {
-                println!("Unable to parse url");
+                error!("Unable to parse url");
                 Err(DbError::new("Error parsing url"))
             }
         }
 
     }
 
-    /// a conection is created here
+    /// a conection is created here,
+    /// the sql is written for the version of the server
     pub fn connect(&self) -> Result<Platform, DbError> {
         match *self {
             ManagedPool::Postgres(ref pool) => {
-                match pool.get() {
-                    Ok(conn) => {
-                        let pg = Postgres::with_pooled_connection(conn);
-                        Ok(Platform::Postgres(pg))
-                    }
-                    Err(e) => {
-                        Err(DbError::new(&format!("Unable to connect due to {}", e)))
-                    }
-                }
+                let conn = try!(pool.get());
+                let mut pg = Postgres::with_pooled_connection(conn);
+                let dialect = try!(server_dialect(&pg));
+                pg.set_dialect(dialect);
+                Ok(Platform::Postgres(pg))
             }
             #[cfg(feature = "sqlite")]
             ManagedPool::Sqlite(ref pool) => {
-                match pool.get() {
-                    Ok(conn) => {
-                        let lite = Sqlite::with_pooled_connection(conn);
-                        Ok(Platform::Sqlite(lite))
-                    }
-                    Err(e) => {
-                        Err(DbError::new(&format!("Unable to connect due to {}", e)))
-                    }
-                }
+                let conn = try!(pool.get());
+                let mut lite = Sqlite::with_pooled_connection(conn);
+                let dialect = try!(server_dialect(&lite));
+                lite.set_dialect(dialect);
+                Ok(Platform::Sqlite(lite))
             }
             #[cfg(feature = "mysql")]
             ManagedPool::Mysql(ref pool) => {
-                let my = Mysql::with_pooled_connection(pool.clone().unwrap());// I hope cloning doesn't really clone the pool, just the Arc
+                let mut my = Mysql::with_pooled_connection(pool.clone().unwrap());// I hope cloning doesn't really clone the pool, just the Arc
+                let dialect = try!(server_dialect(&my));
+                my.set_dialect(dialect);
                 Ok(Platform::Mysql(my))
             }
             _ => Err(DbError::new("Any other database is not yet supported")),
         }
     }
+
+    /// a connection which logs the executed statements with the query log
+    pub fn connect_with_log(&self, query_log: &QueryLog) -> Result<Platform, DbError> {
+        let mut platform = try!(self.connect());
+        try!(platform.set_query_log(query_log.clone()));
+        Ok(platform)
+    }
+
+    /// take a dedicated connection out of the pool listening to the channels,
+    /// the connection stops listening and goes back to the pool when dropped.
+    /// Only postgresql supports LISTEN/NOTIFY
+    pub fn listen(&self, channels: &[&str]) -> Result<Listener, DbError> {
+        match *self {
+            ManagedPool::Postgres(ref pool) => {
+                let conn = try!(pool.get());
+                let listener = Listener::new(Postgres::with_pooled_connection(conn));
+                for channel in channels {
+                    try!(listener.listen(channel));
+                }
+                Ok(listener)
+            }
+            _ => Err(DbError::new("LISTEN/NOTIFY is only supported in postgresql")),
+        }
+    }
+}
+
+#[test]
+fn test_query_log_on_unsupported_platform() {
+    let mut platform = Platform::Oracle;
+    assert!(platform.set_query_log(QueryLog::new()).is_err());
 }
@@ -1,6 +1,5 @@
 use dao::{Value, ToValue};
 use table::Table;
-use std::collections::BTreeMap;
 use database::Database;
 use dao::DaoResult;
 use dao::IsDao;
@@ -8,8 +7,10 @@ use dao::Dao;
 use table::IsTable;
 use writer::SqlFrag;
 use std::fmt;
+use std::error;
 use database::DbError;
 use database::BuildMode;
+use stream::{DaoStream, DaoStreamCast, DEFAULT_BATCH_SIZE};
 
 #[derive(Debug)]
 #[derive(Clone)]
@@ -84,6 +85,8 @@ pub enum Equality {
     ILIKE, //add ILIKE
     IS_NOT_NULL, // NOT_NULL,
     IS_NULL, // IS_NULL,
+    EXISTS, // EXISTS (subquery), the left operand is not used
+    NOT_EXISTS, // NOT EXISTS (subquery)
 }
 
 /// function in a sql statement
@@ -94,6 +97,220 @@ pub struct Function {
     pub params: Vec<Operand>,
 }
 
+/// the unit a timestamp is truncated to in `Function::date_trunc`
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(PartialEq)]
+pub enum DateUnit {
+    Year,
+    Month,
+    Day,
+    Hour,
+    Minute,
+    Second,
+}
+
+impl DateUnit {
+
+    pub fn as_str(&self) -> &'static str {
+        match *self {
+            DateUnit::Year => "year",
+            DateUnit::Month => "month",
+            DateUnit::Day => "day",
+            DateUnit::Hour => "hour",
+            DateUnit::Minute => "minute",
+            DateUnit::Second => "second",
+        }
+    }
+
+    pub fn from_str(unit: &str) -> Option<Self> {
+        match &*unit.to_lowercase() {
+            "year" => Some(DateUnit::Year),
+            "month" => Some(DateUnit::Month),
+            "day" => Some(DateUnit::Day),
+            "hour" => Some(DateUnit::Hour),
+            "minute" => Some(DateUnit::Minute),
+            "second" => Some(DateUnit::Second),
+            _ => None,
+        }
+    }
+}
+
+/// common functions, the database translates
+/// NOW, CONCAT and DATE_TRUNC to its own dialect
+impl Function {
+
+    pub fn new(function: &str, params: Vec<Operand>) -> Self {
+        Function {
+            function: function.to_owned(),
+            params: params,
+        }
+    }
+
+    fn on_column(function: &str, column: &str) -> Self {
+        Function::new(function, vec![Operand::ColumnName(ColumnName::from_str(column))])
+    }
+
+    /// use "*" to count all rows
+    pub fn count(column: &str) -> Self {
+        Function::on_column("COUNT", column)
+    }
+
+    pub fn sum(column: &str) -> Self {
+        Function::on_column("SUM", column)
+    }
+
+    pub fn avg(column: &str) -> Self {
+        Function::on_column("AVG", column)
+    }
+
+    pub fn min(column: &str) -> Self {
+        Function::on_column("MIN", column)
+    }
+
+    pub fn max(column: &str) -> Self {
+        Function::on_column("MAX", column)
+    }
+
+    pub fn lower(column: &str) -> Self {
+        Function::on_column("LOWER", column)
+    }
+
+    pub fn upper(column: &str) -> Self {
+        Function::on_column("UPPER", column)
+    }
+
+    /// the first non-null of the operands
+    pub fn coalesce(params: Vec<Operand>) -> Self {
+        Function::new("COALESCE", params)
+    }
+
+    /// current timestamp
+    pub fn now() -> Self {
+        Function::new("NOW", vec![])
+    }
+
+    /// string concatenation of the operands
+    pub fn concat(params: Vec<Operand>) -> Self {
+        Function::new("CONCAT", params)
+    }
+
+    /// ranking functions, used in a `Window`
+    pub fn row_number() -> Self {
+        Function::new("ROW_NUMBER", vec![])
+    }
+
+    pub fn rank() -> Self {
+        Function::new("RANK", vec![])
+    }
+
+    pub fn dense_rank() -> Self {
+        Function::new("DENSE_RANK", vec![])
+    }
+
+    /// truncate the timestamp column to the specified unit
+    pub fn date_trunc(unit: DateUnit, column: &str) -> Self {
+        Function::new("DATE_TRUNC",
+                      vec![Operand::Value(Value::String(unit.as_str().to_owned())),
+                           Operand::ColumnName(ColumnName::from_str(column))])
+    }
+}
+
+/// the unit of the window frame
+#[derive(Debug)]
+#[derive(Clone)]
+pub enum FrameUnit {
+    ROWS,
+    RANGE,
+}
+
+/// the start or end of the window frame
+#[derive(Debug)]
+#[derive(Clone)]
+#[allow(non_camel_case_types)]
+pub enum FrameBound {
+    UNBOUNDED_PRECEDING,
+    PRECEDING(usize),
+    CURRENT_ROW,
+    FOLLOWING(usize),
+    UNBOUNDED_FOLLOWING,
+}
+
+/// the set of rows within the partition the window function operates on
+#[derive(Debug)]
+#[derive(Clone)]
+pub struct Frame {
+    pub unit: FrameUnit,
+    pub start: FrameBound,
+    /// when not specified, the frame ends at the current row
+    pub end: Option<FrameBound>,
+}
+
+/// window function, i.e: `ROW_NUMBER() OVER (PARTITION BY category ORDER BY price)`
+/// supported in postgresql, sqlite >= 3.25 and mysql >= 8.0
+#[derive(Debug)]
+#[derive(Clone)]
+pub struct Window {
+    pub function: Function,
+    pub partition_by: Vec<Operand>,
+    pub order_by: Vec<Order>,
+    pub frame: Option<Frame>,
+}
+
+impl Window {
+
+    pub fn new(function: Function) -> Self {
+        Window {
+            function: function,
+            partition_by: vec![],
+            order_by: vec![],
+            frame: None,
+        }
+    }
+
+    pub fn partition_by(&mut self, columns: Vec<&str>) -> &mut Self {
+        for c in columns {
+            self.partition_by.push(Operand::ColumnName(ColumnName::from_str(c)));
+        }
+        self
+    }
+
+    pub fn order_by(&mut self, column: &str, direction: Option<Direction>, nulls_where: Option<NullsWhere>) -> &mut Self {
+        self.order_by.push(Order {
+            operand: Operand::ColumnName(ColumnName::from_str(column)),
+            direction: direction,
+            nulls_where: nulls_where,
+        });
+        self
+    }
+
+    pub fn asc(&mut self, column: &str) -> &mut Self {
+        self.order_by(column, Some(Direction::ASC), None)
+    }
+
+    pub fn desc(&mut self, column: &str) -> &mut Self {
+        self.order_by(column, Some(Direction::DESC), None)
+    }
+
+    pub fn rows_between(&mut self, start: FrameBound, end: FrameBound) -> &mut Self {
+        self.frame = Some(Frame {
+            unit: FrameUnit::ROWS,
+            start: start,
+            end: Some(end),
+        });
+        self
+    }
+
+    pub fn range_between(&mut self, start: FrameBound, end: FrameBound) -> &mut Self {
+        self.frame = Some(Frame {
+            unit: FrameUnit::RANGE,
+            start: start,
+            end: Some(end),
+        });
+        self
+    }
+}
+
 /// Operands can be columns, functions, query or value types
 #[derive(Debug)]
 #[derive(Clone)]
@@ -101,9 +318,12 @@ pub enum Operand {
     ColumnName(ColumnName),
     TableName(TableName),
     Function(Function),
+    Window(Window),
     Query(Query),
     Value(Value),
     Vec(Vec<Operand>),
+    /// sql expression written as is, i.e: the introspected column default `now()`
+    Sql(String),
 }
 
 /// expression has left operand,
@@ -170,6 +390,38 @@ impl Filter {
     }
 
 
+    /// compare the column to another column,
+    /// the other column may refer to the table of the outer query in a correlated subquery
+    pub fn with_column(column: &str, equality: Equality, other_column: &str) -> Self {
+        Filter::bare_new(Operand::ColumnName(ColumnName::from_str(column)),
+                         equality,
+                         Operand::ColumnName(ColumnName::from_str(other_column)))
+    }
+
+    /// column IN (query)
+    pub fn in_query(column: &str, query: Query) -> Self {
+        Filter::bare_new(Operand::ColumnName(ColumnName::from_str(column)),
+                         Equality::IN,
+                         Operand::Query(query))
+    }
+
+    /// column NOT IN (query)
+    pub fn not_in_query(column: &str, query: Query) -> Self {
+        Filter::bare_new(Operand::ColumnName(ColumnName::from_str(column)),
+                         Equality::NOT_IN,
+                         Operand::Query(query))
+    }
+
+    /// EXISTS (query)
+    pub fn exists(query: Query) -> Self {
+        Filter::bare_new(Operand::Vec(vec![]), Equality::EXISTS, Operand::Query(query))
+    }
+
+    /// NOT EXISTS (query)
+    pub fn not_exists(query: Query) -> Self {
+        Filter::bare_new(Operand::Vec(vec![]), Equality::NOT_EXISTS, Operand::Query(query))
+    }
+
     pub fn is_null(column: &str) -> Self {
         Filter::new(column, Equality::IS_NULL, &())
     }
@@ -177,6 +429,18 @@ impl Filter {
         Filter::new(column, Equality::IS_NOT_NULL, &())
     }
 
+    /// whether the column is compared in this filter or its sub filters
+    pub fn uses_column(&self, column: &str) -> bool {
+        let is_column = |operand: &Operand| {
+            match *operand {
+                Operand::ColumnName(ref c) => c.column == column,
+                _ => false,
+            }
+        };
+        is_column(&self.condition.left) || is_column(&self.condition.right) ||
+        self.sub_filters.iter().any(|f| f.uses_column(column))
+    }
+
     pub fn and(&mut self, column: &str, equality: Equality, value: &ToValue) -> &mut Self {
         let mut filter = Filter::new(column, equality, value);
         filter.connector = Connector::And;
@@ -205,6 +469,47 @@ impl Filter {
     }
 }
 
+/// a query declared in the WITH clause (CTE) and referred to by its name
+#[derive(Debug)]
+#[derive(Clone)]
+pub struct DeclaredQuery {
+    pub name: String,
+    pub query: Query,
+    /// the recursive term which refers to this declared query itself,
+    /// joined with the query using UNION ALL
+    pub recursive: Option<Query>,
+}
+
+/// set operations combining the records of two selects
+#[derive(Debug)]
+#[derive(Clone)]
+#[allow(non_camel_case_types)]
+pub enum SetOperation {
+    UNION,
+    UNION_ALL,
+    INTERSECT,
+    EXCEPT,
+}
+
+/// a query combined with the records of the main query
+#[derive(Debug)]
+#[derive(Clone)]
+pub struct CombinedQuery {
+    pub operation: SetOperation,
+    pub query: Query,
+}
+
+/// what to do when an inserted record conflicts with an existing record
+#[derive(Debug)]
+#[derive(Clone)]
+pub struct OnConflict {
+    /// the columns of the unique constraint being violated, usually the primary columns
+    pub columns: Vec<ColumnName>,
+    /// the columns that are updated with the values of the inserted record,
+    /// nothing is done when there are no update columns
+    pub update_columns: Vec<ColumnName>,
+}
+
 /// Could have been SqlAction
 #[derive(Debug)]
 #[derive(Clone)]
@@ -237,47 +542,33 @@ pub struct Field {
 
 impl Field {
 
+    /// rename the column to `table.column`,
+    /// fields that are not table qualified columns are left as is
     fn rename(&self) -> Field {
         match self.operand {
-            Operand::ColumnName(ref column_name) => {
-                let rename = column_name.default_rename();
+            Operand::ColumnName(ref column_name) if column_name.table.is_some() => {
                 Field {
                     operand: Operand::ColumnName(column_name.clone()),
-                    name: Some(rename),
+                    name: Some(column_name.complete_name()),
                 }
             }
-            _ => unimplemented!(),
+            _ => self.clone(),
         }
     }
 }
 
 impl ColumnName {
 
+    /// parse `column`, `table.column` or `schema.table.column`
     pub fn from_str(column: &str) -> Self {
-        if column.contains(".") {
-            let splinters = column.split(".").collect::<Vec<&str>>();
-            assert!(splinters.len() == 2, "There should only be 2 splinters");
-            let table_split = splinters[0].to_owned();
-            let column_split = splinters[1].to_owned();
-            ColumnName {
-                column: column_split.to_owned(),
-                table: Some(table_split.to_owned()),
-                schema: None,
-            }
-        } else {
-            ColumnName {
-                column: column.to_owned(),
-                table: None,
-                schema: None,
-            }
-        }
-    }
-
-    fn default_rename(&self) -> String {
-        match self.table {
-            Some(ref s) => format!("{}.{}", s, self.column),
-            None => panic!("Unable to rename {} since table is not specified",
-                           self.column),
+        let mut splinters = column.rsplitn(3, '.');
+        let column_split = splinters.next().unwrap_or("");
+        let table_split = splinters.next();
+        let schema_split = splinters.next();
+        ColumnName {
+            column: column_split.to_owned(),
+            table: table_split.map(|t| t.to_owned()),
+            schema: schema_split.map(|s| s.to_owned()),
         }
     }
 
@@ -331,25 +622,15 @@ pub struct TableName {
 
 impl TableName {
 
+    /// parse `table` or `schema.table`, anything before the last dot is the schema
     pub fn from_str(str: &str) -> Self {
-        if str.contains(".") {
-            let splinters = str.split(".").collect::<Vec<&str>>();
-            assert!(splinters.len() == 2, "There should only be 2 splinters");
-            let schema_split = splinters[0].to_owned();
-            let table_split = splinters[1].to_owned();
-
-            TableName {
-                schema: Some(schema_split),
-                name: table_split,
-                columns: vec![],
-            }
-
-        } else {
-            TableName {
-                schema: None,
-                name: str.to_owned(),
-                columns: vec![],
-            }
+        let mut splinters = str.rsplitn(2, '.');
+        let table_split = splinters.next().unwrap_or("");
+        let schema_split = splinters.next();
+        TableName {
+            schema: schema_split.map(|s| s.to_owned()),
+            name: table_split.to_owned(),
+            columns: vec![],
         }
     }
 
@@ -427,10 +708,45 @@ impl ToTableName for Table {
 }
 
 /// Query Error
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(PartialEq)]
 pub enum Error {
     NoTableSpecified(String),
     NoColumnSpecified(String),
     SqlError(String),
+    /// the identifier can not be safely quoted
+    InvalidIdentifier(String),
+    /// there is no filter to attach the AND or OR clause to
+    NoFilterSpecified(String),
+    /// the page and page size are mixed with a limit and offset
+    MixedRange(String),
+}
+
+impl error::Error for Error {
+    fn description(&self) -> &str {
+        match *self {
+            Error::NoTableSpecified(ref description) => description,
+            Error::NoColumnSpecified(ref description) => description,
+            Error::SqlError(ref description) => description,
+            Error::InvalidIdentifier(ref description) => description,
+            Error::NoFilterSpecified(ref description) => description,
+            Error::MixedRange(ref description) => description,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::NoTableSpecified(ref description) => write!(f, "No table specified: {}", description),
+            Error::NoColumnSpecified(ref description) => write!(f, "No column specified: {}", description),
+            Error::SqlError(ref description) => write!(f, "{}", description),
+            Error::InvalidIdentifier(ref description) => write!(f, "Invalid identifier: {}", description),
+            Error::NoFilterSpecified(ref description) => write!(f, "No filter specified: {}", description),
+            Error::MixedRange(ref description) => write!(f, "Mixed range: {}", description),
+        }
+    }
 }
 
 
@@ -504,7 +820,8 @@ pub struct Query {
     /// whether to enumate all columns in involved models
     pub enumerate_all: bool,
 
-    pub declared_query: BTreeMap<String, Query>,
+    /// queries declared in the WITH clause, in the order they are declared
+    pub declared_query: Vec<DeclaredQuery>,
 
     ///fields can be functions, column sql query, and even columns
     /// TODO; merge enumerated column to this, add a builder for fields
@@ -549,6 +866,13 @@ pub struct Query {
 
     /// the returning clause of the query when supported,
     pub enumerated_returns: Vec<Field>,
+
+    /// upsert, update the existing record when the inserted record conflicts
+    pub on_conflict: Option<OnConflict>,
+
+    /// queries combined with this query via UNION, INTERSECT, EXCEPT,
+    /// the order by and limit of this query applies to the combined records
+    pub combined_queries: Vec<CombinedQuery>,
 }
 
 impl Query {
@@ -559,7 +883,7 @@ impl Query {
             sql_type: SqlType::SELECT,
             distinct: false,
             enumerate_all: false,
-            declared_query: BTreeMap::new(),
+            declared_query: vec![],
             enumerated_fields: vec![],
             distinct_on_columns: vec![],
             filters: vec![],
@@ -572,6 +896,8 @@ impl Query {
             from: None,
             values: vec![],
             enumerated_returns: vec![],
+            on_conflict: None,
+            combined_queries: vec![],
         }
     }
 
@@ -647,6 +973,26 @@ impl Query {
         self
     }
 
+    /// enumerate a window function, renamed as alias
+    pub fn window(&mut self, window: Window, alias: &str) -> &mut Self {
+        let field = Field {
+            operand: Operand::Window(window),
+            name: Some(alias.to_owned()),
+        };
+        self.enumerated_fields.push(field);
+        self
+    }
+
+    /// enumerate a function call, renamed as alias
+    pub fn function(&mut self, function: Function, alias: &str) -> &mut Self {
+        let field = Field {
+            operand: Operand::Function(function),
+            name: Some(alias.to_owned()),
+        };
+        self.enumerated_fields.push(field);
+        self
+    }
+
     pub fn group_by(&mut self, columns: Vec<&str>) -> &mut Self {
         for c in columns {
             let column_name = ColumnName::from_str(c);
@@ -656,8 +1002,19 @@ impl Query {
         self
     }
 
+    /// the column can be an aggregate of a column, i.e: `count(*)`, `sum(product.price)`,
+    /// other expressions are rejected when the query is built, use `add_having` for those
     pub fn having(&mut self, column: &str, equality: Equality, value: &ToValue) -> &mut Self {
-        let filter = Filter::new(column, equality, value);
+        let filter = match parse_aggregate(column) {
+            Some(function) => {
+                Filter::bare_new(Operand::Function(function), equality, Operand::Value(value.to_db_type()))
+            }
+            None => Filter::new(column, equality, value),
+        };
+        self.add_having(filter)
+    }
+
+    pub fn add_having(&mut self, filter: Filter) -> &mut Self {
         self.having.push(filter);
         self
     }
@@ -686,7 +1043,8 @@ impl Query {
     }
 
     /// when paging multiple records
-    pub fn set_page(&mut self, page: usize) -> &mut Self {
+    /// errors when the query already has a limit and offset, pages and offsets don't mix
+    pub fn set_page(&mut self, page: usize) -> Result<&mut Self, Error> {
         let new_range = match self.range{
             Some(ref range) => {
                 match range{
@@ -695,7 +1053,7 @@ impl Query {
                         Some(Range::Page(Page{page:Some(page), page_size: p.page_size}))
                     },
                     &Range::Limit(_) => {
-                        panic!("Do not mix page->page_size with limit and offset");
+                        return Err(Error::MixedRange("Do not mix page->page_size with limit and offset".to_owned()));
                     }
                 }
             },
@@ -705,10 +1063,11 @@ impl Query {
             }
         };
         self.range = new_range;
-        self
+        Ok(self)
     }
     
-    pub fn set_page_size(&mut self, page_size: usize) -> &mut Self {
+    /// errors when the query already has a limit and offset, pages and offsets don't mix
+    pub fn set_page_size(&mut self, page_size: usize) -> Result<&mut Self, Error> {
         let new_range = match self.range{
             Some(ref range) => {
                 match range{
@@ -717,9 +1076,8 @@ impl Query {
                         Some(Range::Page(Page{page:p.page, page_size: Some(page_size)}))
                     },
                     &Range::Limit(_) => {
-                        panic!("Do not mix page->page_size with limit and offset");
+                        return Err(Error::MixedRange("Do not mix page->page_size with limit and offset".to_owned()));
                     }
-                    
                 }
             },
             None => {
@@ -728,11 +1086,18 @@ impl Query {
             }
         };
         self.range = new_range;
-        self
+        Ok(self)
     }
     
+    /// the page size, or the limit when the query has a limit and offset
     pub fn limit(&mut self, n: usize)->&mut Self{
-        self.set_page_size(n)
+        let new_range = match self.range{
+            Some(Range::Limit(ref l)) => Range::Limit(Limit{limit: Some(n), offset: l.offset}),
+            Some(Range::Page(ref p)) => Range::Page(Page{page: p.page, page_size: Some(n)}),
+            None => Range::Page(Page{page: None, page_size: Some(n)}),
+        };
+        self.range = Some(new_range);
+        self
     }
 
     pub fn get_limit(&self)->Option<Limit>{
@@ -777,16 +1142,62 @@ impl Query {
         self.from(table)
     }
 
-    /// if the database support CTE declareted query i.e WITH,
-    /// then this query will be declared
-    /// if database doesn't support WITH queries, then this query will be
-    /// wrapped in the from_query
-    /// build a builder for this
+    /// declare the query in the WITH clause (CTE), it can then be used as a table by its alias
+    /// the database needs to support CTE, otherwise an error is returned when executed
     pub fn declare_query(&mut self, query: Query, alias: &str) -> &mut Self {
-        self.declared_query.insert(alias.to_owned(), query);
+        self.declared_query.push(DeclaredQuery {
+            name: alias.to_owned(),
+            query: query,
+            recursive: None,
+        });
+        self
+    }
+
+    /// declare a recursive query in the WITH RECURSIVE clause,
+    /// the recursive query refers to the alias, to walk self-referencing tables such as
+    /// organization hierarchies
+    /// ie. WITH RECURSIVE alias AS ( base UNION ALL recursive )
+    pub fn declare_recursive_query(&mut self,
+                                   base: Query,
+                                   recursive: Query,
+                                   alias: &str)
+                                   -> &mut Self {
+        self.declared_query.push(DeclaredQuery {
+            name: alias.to_owned(),
+            query: base,
+            recursive: Some(recursive),
+        });
+        self
+    }
+
+    fn combine(&mut self, operation: SetOperation, query: Query) -> &mut Self {
+        self.combined_queries.push(CombinedQuery {
+            operation: operation,
+            query: query,
+        });
         self
     }
 
+    /// the distinct records of this query and the other query
+    pub fn union(&mut self, query: Query) -> &mut Self {
+        self.combine(SetOperation::UNION, query)
+    }
+
+    /// all the records of this query and the other query, including duplicates
+    pub fn union_all(&mut self, query: Query) -> &mut Self {
+        self.combine(SetOperation::UNION_ALL, query)
+    }
+
+    /// the records that are in both this query and the other query
+    pub fn intersect(&mut self, query: Query) -> &mut Self {
+        self.combine(SetOperation::INTERSECT, query)
+    }
+
+    /// the records of this query that are not in the other query
+    pub fn except(&mut self, query: Query) -> &mut Self {
+        self.combine(SetOperation::EXCEPT, query)
+    }
+
     /// a query to query from
     /// use WITH (query) t1 SELECT from t1 declaration in postgresql, sqlite
     /// use SELECT FROM (query) in oracle, mysql, others
@@ -805,6 +1216,25 @@ impl Query {
         self
     }
 
+    /// whether the table name or alias refers to the table or query this query selects from
+    /// or to one of its joined tables,
+    /// columns qualified with other tables refer to the outer query of a correlated subquery
+    pub fn is_local_table(&self, table: &str) -> bool {
+        if let Some(ref field) = self.from {
+            if let Some(ref alias) = field.name {
+                if alias == table {
+                    return true;
+                }
+            }
+            if let Operand::TableName(ref table_name) = field.operand {
+                if table_name.name == table || table_name.complete_name() == table {
+                    return true;
+                }
+            }
+        }
+        self.joins.iter().any(|j| j.table_name.name == table || j.table_name.complete_name() == table)
+    }
+
     pub fn get_from_table(&self) -> Option<&TableName> {
         if let Some(ref field) = self.from {
             if let Operand::TableName(ref table_name) = field.operand {
@@ -1101,15 +1531,27 @@ impl Query {
     }
 
     // attach and clause
-    pub fn and(&mut self, column: &str, equality: Equality, value: &ToValue) -> &mut Self {
-        self.filters.last_mut().unwrap().and(column, equality, value);
-        self
+    /// errors when there is no filter yet
+    pub fn and(&mut self, column: &str, equality: Equality, value: &ToValue) -> Result<&mut Self, Error> {
+        match self.filters.last_mut() {
+            Some(filter) => {
+                filter.and(column, equality, value);
+            }
+            None => return Err(Error::NoFilterSpecified(format!("AND {}", column))),
+        }
+        Ok(self)
     }
 
     // attach or clause
-    pub fn or(&mut self, column: &str, equality: Equality, value: &ToValue) -> &mut Self {
-        self.filters.last_mut().unwrap().or(column, equality, value);
-        self
+    /// errors when there is no filter yet
+    pub fn or(&mut self, column: &str, equality: Equality, value: &ToValue) -> Result<&mut Self, Error> {
+        match self.filters.last_mut() {
+            Some(filter) => {
+                filter.or(column, equality, value);
+            }
+            None => return Err(Error::NoFilterSpecified(format!("OR {}", column))),
+        }
+        Ok(self)
     }
 
     /// column = value
@@ -1149,6 +1591,15 @@ impl Query {
         self.add_value(&value)
     }
 
+    /// add a row of values when inserting multiple records,
+    /// the values are in the same order as the enumerated columns
+    pub fn add_row(&mut self, row: &[Value]) -> &mut Self {
+        for value in row {
+            self.add_value(value);
+        }
+        self
+    }
+
     /// set a value of a column when inserting/updating records
     pub fn set(&mut self, column: &str, value: &ToValue) -> &mut Self {
         self.column(column);
@@ -1159,6 +1610,22 @@ impl Query {
 		self.add_value(value)
 	}
 
+    /// when the inserted record conflicts with an existing record on these columns,
+    /// update the existing record with the values of the update_columns instead
+    pub fn on_conflict(&mut self, columns: Vec<&str>, update_columns: Vec<&str>) -> &mut Self {
+        let on_conflict = OnConflict {
+            columns: columns.iter().map(|c| ColumnName::from_str(c)).collect(),
+            update_columns: update_columns.iter().map(|c| ColumnName::from_str(c)).collect(),
+        };
+        self.on_conflict = Some(on_conflict);
+        self
+    }
+
+    /// ignore the inserted record when it conflicts with an existing record on these columns
+    pub fn on_conflict_do_nothing(&mut self, columns: Vec<&str>) -> &mut Self {
+        self.on_conflict(columns, vec![])
+    }
+
     pub fn return_all(&mut self) -> &mut Self {
         self.enumerate_column_as_return("*")
     }
@@ -1182,17 +1649,35 @@ impl Query {
     }
 
     /// build the query only, not executed, useful when debugging
-    pub fn build(&mut self, db: &Database) -> SqlFrag {
+    pub fn build(&mut self, db: &Database) -> Result<SqlFrag, Error> {
         self.finalize();
         db.build_query(self, BuildMode::Standard)
     }
 
 	/// Warning: don't use this in production
-	pub fn debug_build(&mut self, db: &Database)-> SqlFrag{
+	pub fn debug_build(&mut self, db: &Database)-> Result<SqlFrag, Error>{
 		self.finalize();
 		db.build_query(self, BuildMode::Debug)
 	}
 
+    /// iterate the records in batches, instead of loading them all in memory,
+    /// without a server side cursor the query has to be ordered
+    pub fn stream<'a>(&mut self, db: &'a Database) -> Result<DaoStream<'a>, DbError> {
+        self.stream_batch(db, DEFAULT_BATCH_SIZE)
+    }
+
+    /// iterate the records, fetching this number of records in each round trip
+    pub fn stream_batch<'a>(&mut self, db: &'a Database, batch_size: usize) -> Result<DaoStream<'a>, DbError> {
+        self.finalize();
+        DaoStream::new(db, self, batch_size)
+    }
+
+    /// iterate the records casted into the type
+    pub fn stream_as<'a, T: IsDao>(&mut self, db: &'a Database) -> Result<DaoStreamCast<'a, T>, DbError> {
+        let stream = try!(self.stream(db));
+        Ok(stream.cast())
+    }
+
 	///retrieve a generic types, type is unknown at runtime
     /// expects a return, such as select, insert/update with returning clause
     pub fn retrieve(&mut self, db: &Database) -> Result<DaoResult, DbError> {
@@ -1214,6 +1699,12 @@ impl Query {
         db.execute(self)
     }
 
+    /// insert the rows of values in bulk, returns the number of inserted records
+    pub fn insert_bulk(&mut self, db: &Database) -> Result<usize, DbError> {
+        self.finalize();
+        db.insert_bulk(self)
+    }
+
     /// execute the query, then convert the result
     pub fn collect<T: IsDao + IsTable>(&mut self, db: &Database) -> Result<Vec<T>, DbError> {
         let result = try!(self.retrieve(db));
@@ -1223,9 +1714,31 @@ impl Query {
     /// execute the query then collect only 1 record
     pub fn collect_one<T: IsDao + IsTable>(&mut self, db: &Database) -> Result<T, DbError> {
         let result = try!(self.retrieve(db));
-        match result.cast_one() {
+        match try!(result.cast_one()) {
             Some(res) => Ok(res),
             None => Err(DbError::new("No entry to collect found.")),
         }
     }
 }
+
+/// an aggregate written as `name(*)` or `name(column)`
+fn parse_aggregate(expression: &str) -> Option<Function> {
+    let expression = expression.trim();
+    if !expression.ends_with(')') {
+        return None;
+    }
+    let open = match expression.find('(') {
+        Some(open) => open,
+        None => return None,
+    };
+    let name = expression[..open].trim();
+    let arg = expression[open + 1..expression.len() - 1].trim();
+    let is_word = |c: char| c.is_alphanumeric() || c == '_';
+    let valid_name = !name.is_empty() && name.chars().all(is_word);
+    let valid_arg = arg == "*" || (!arg.is_empty() && arg.chars().all(|c| is_word(c) || c == '.'));
+    if valid_name && valid_arg {
+        Some(Function::new(name, vec![Operand::ColumnName(ColumnName::from_str(arg))]))
+    } else {
+        None
+    }
+}
@@ -0,0 +1,372 @@
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicUsize, Ordering, ATOMIC_USIZE_INIT};
+use database::{Database, DbError, SqlOption, BuildMode};
+use dao::{Dao, IsDao, Value};
+use query::{Query, Range, Limit, Filter, Condition, Connector, Operand, ColumnName, Equality, Direction, NullsWhere};
+
+/// the number of records fetched in each round trip, when not specified
+pub const DEFAULT_BATCH_SIZE: usize = 1000;
+
+/// used for naming the cursors, so several streams can be open on the same connection
+static CURSOR_COUNT: AtomicUsize = ATOMIC_USIZE_INIT;
+
+/// Iterates the records of a select query, fetching them in batches
+/// instead of loading the whole result in memory.
+///
+/// Databases supporting server side cursors (postgresql) fetch from a cursor,
+/// which needs a transaction, one is started when there is none and ended when the stream is done.
+/// The rest (sqlite, mysql) step through the records in pages, each page continues after
+/// the ORDER BY columns of the last record (keyset paging), so the query has to be ordered
+/// by selected columns which are unique together, ie: ending with the primary key.
+///
+/// ```ignore
+/// let mut stream = try!(Query::select_all().from_table("bazaar.product").stream(db.as_ref()));
+/// stream.batch_size(500);
+/// for dao in stream {
+///     let dao = try!(dao);
+/// }
+/// ```
+pub struct DaoStream<'a> {
+    db: &'a Database,
+    query: Query,
+    batch_size: usize,
+    /// the records fetched, but not yet iterated
+    buffer: VecDeque<Dao>,
+    /// the server side cursor, when the database supports it
+    cursor: Option<String>,
+    /// whether the transaction was started by this stream
+    owns_transaction: bool,
+    /// the ORDER BY columns, when paging
+    keys: Vec<Key>,
+    /// the values of the ORDER BY columns of the last record fetched, the next page continues after them
+    last: Option<Vec<Value>>,
+    /// the offset of the original query, skipped on the first page
+    offset: Option<usize>,
+    /// the limit of the original query, the stream stops there
+    remaining: Option<usize>,
+    finished: bool,
+}
+
+impl <'a>DaoStream<'a> {
+
+    /// open the stream, the query is expected to be finalized
+    pub fn new(db: &'a Database, query: &Query, batch_size: usize) -> Result<Self, DbError> {
+        try!(db.check_query_support(query));
+        let limit = query.get_limit().unwrap_or(Limit::default());
+        let mut stream = DaoStream {
+            db: db,
+            query: query.clone(),
+            batch_size: if batch_size > 0 { batch_size } else { DEFAULT_BATCH_SIZE },
+            buffer: VecDeque::new(),
+            cursor: None,
+            owns_transaction: false,
+            keys: vec![],
+            last: None,
+            offset: limit.offset,
+            remaining: limit.limit,
+            finished: false,
+        };
+        if db.sql_options().contains(&SqlOption::SupportsCursor) {
+            try!(stream.declare_cursor());
+        } else if query.combined_queries.is_empty() {
+            stream.keys = try!(keys(query));
+        } else {
+            stream.query = try!(derived(query));
+            stream.keys = try!(keys(&stream.query));
+        }
+        Ok(stream)
+    }
+
+    /// the number of records to fetch on the next round trips
+    pub fn batch_size(&mut self, batch_size: usize) -> &mut Self {
+        if batch_size > 0 {
+            self.batch_size = batch_size;
+        }
+        self
+    }
+
+    /// convert each of the records into the type
+    pub fn cast<T: IsDao>(self) -> DaoStreamCast<'a, T> {
+        DaoStreamCast {
+            stream: self,
+            cast: T::try_from_dao,
+        }
+    }
+
+    fn declare_cursor(&mut self) -> Result<(), DbError> {
+        let sql_frag = try!(self.db.build_query(&self.query, BuildMode::Standard));
+        let count = CURSOR_COUNT.fetch_add(1, Ordering::SeqCst);
+        let name = try!(self.db.quote_identifier(&format!("rustorm_cursor_{}", count)));
+        if !self.db.is_transacted() {
+            try!(self.db.begin());
+            self.owns_transaction = true;
+        }
+        let declare = format!("DECLARE {} NO SCROLL CURSOR FOR {}", name, sql_frag.sql);
+        match self.db.execute_sql(&declare, &sql_frag.params) {
+            Ok(_) => {
+                self.cursor = Some(name);
+                Ok(())
+            }
+            Err(e) => {
+                self.finish();
+                Err(e)
+            }
+        }
+    }
+
+    /// fetch the next batch of records, returns false when there are no more records
+    fn fetch(&mut self) -> Result<bool, DbError> {
+        let batch_size = match self.remaining {
+            Some(remaining) if remaining < self.batch_size => remaining,
+            _ => self.batch_size,
+        };
+        if batch_size == 0 {
+            return Ok(false);
+        }
+        let records = match self.cursor {
+            Some(ref name) => {
+                let fetch = format!("FETCH FORWARD {} FROM {}", batch_size, name);
+                try!(self.db.execute_sql_with_return(&fetch, &[]))
+            }
+            None => {
+                let mut page = self.query.clone();
+                if let Some(ref last) = self.last {
+                    match after(&self.keys, last) {
+                        Some(filter) => page.filters.push(filter),
+                        None => return Ok(false),
+                    }
+                }
+                page.range = Some(Range::Limit(Limit {
+                    limit: Some(batch_size),
+                    offset: self.offset.take(),
+                }));
+                let records = try!(self.db.execute_with_return(&page)).dao;
+                if let Some(dao) = records.last() {
+                    self.last = Some(try!(key_values(&self.keys, dao)));
+                }
+                records
+            }
+        };
+        let fetched = records.len();
+        if let Some(remaining) = self.remaining {
+            self.remaining = Some(remaining - fetched);
+        }
+        self.buffer.extend(records);
+        Ok(fetched == batch_size)
+    }
+
+    /// close the cursor, and end the transaction started by this stream
+    fn finish(&mut self) {
+        if self.finished {
+            return;
+        }
+        self.finished = true;
+        if let Some(name) = self.cursor.take() {
+            let _ = self.db.execute_sql(&format!("CLOSE {}", name), &[]);
+        }
+        if self.owns_transaction {
+            // the transaction is only needed by the cursor, nothing is kept from it
+            let _ = self.db.rollback();
+            self.owns_transaction = false;
+        }
+    }
+}
+
+impl <'a>Iterator for DaoStream<'a> {
+    type Item = Result<Dao, DbError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(dao) = self.buffer.pop_front() {
+            return Some(Ok(dao));
+        }
+        if self.finished {
+            return None;
+        }
+        match self.fetch() {
+            Ok(has_more) => {
+                if !has_more {
+                    self.finish();
+                }
+                self.buffer.pop_front().map(Ok)
+            }
+            Err(e) => {
+                self.finish();
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+impl <'a>Drop for DaoStream<'a> {
+
+    /// release the cursor when the stream is not iterated to the end
+    fn drop(&mut self) {
+        self.finish();
+    }
+}
+
+/// a stream of records converted into the type
+pub struct DaoStreamCast<'a, T> {
+    stream: DaoStream<'a>,
+    cast: fn(&Dao) -> Result<T, DbError>,
+}
+
+impl <'a, T>Iterator for DaoStreamCast<'a, T> {
+    type Item = Result<T, DbError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let cast = self.cast;
+        self.stream.next().map(|dao| dao.and_then(|dao| cast(&dao)))
+    }
+}
+
+/// an ORDER BY column of the query, the NULLs sort first in ascending order unless specified,
+/// as in sqlite and mysql
+struct Key {
+    column: ColumnName,
+    descending: bool,
+    nulls_first: bool,
+}
+
+/// the ORDER BY columns of the query, the pages are read after the last of them
+fn keys(query: &Query) -> Result<Vec<Key>, DbError> {
+    if query.order_by.is_empty() {
+        return Err(DbError::new("Unable to stream the records without an ORDER BY, \
+                                 the pages would skip or repeat records"));
+    }
+    let mut keys = vec![];
+    for order in &query.order_by {
+        let column = match order.operand {
+            Operand::ColumnName(ref column) => column.clone(),
+            _ => return Err(DbError::new("Unable to stream the records ordered by an expression, \
+                                          only columns can be paged through")),
+        };
+        let descending = match order.direction {
+            Some(Direction::DESC) => true,
+            _ => false,
+        };
+        let nulls_first = match order.nulls_where {
+            Some(NullsWhere::FIRST) => true,
+            Some(NullsWhere::LAST) => false,
+            None => !descending,
+        };
+        keys.push(Key {
+            column: column,
+            descending: descending,
+            nulls_first: nulls_first,
+        });
+    }
+    Ok(keys)
+}
+
+/// the combined selects as a derived table ordered by the same columns,
+/// so the pages are filtered after the selects are combined
+fn derived(query: &Query) -> Result<Query, DbError> {
+    let mut combined = query.clone();
+    combined.order_by = vec![];
+    combined.range = None;
+    let mut outer = Query::select_all();
+    outer.from_query(combined, "combined");
+    for order in &query.order_by {
+        let mut order = order.clone();
+        order.operand = match order.operand {
+            Operand::ColumnName(ref column) => Operand::ColumnName(ColumnName::from_str(&column.column)),
+            _ => return Err(DbError::new("Unable to stream the records ordered by an expression, \
+                                          only columns can be paged through")),
+        };
+        outer.order_by.push(order);
+    }
+    Ok(outer)
+}
+
+/// the values of the ORDER BY columns of the record
+fn key_values(keys: &[Key], dao: &Dao) -> Result<Vec<Value>, DbError> {
+    let mut values = vec![];
+    for key in keys {
+        match dao.values.get(&key.column.column) {
+            Some(value) => values.push(value.clone()),
+            None => {
+                return Err(DbError::new(&format!("Unable to stream the records, the ORDER BY column {} \
+                                                  is not selected",
+                                                 key.column.column)))
+            }
+        }
+    }
+    Ok(values)
+}
+
+/// the records after the last one, in the order of the keys:
+/// `a > x OR (a = x AND b > y) ...`, None when no record can come after it
+fn after(keys: &[Key], last: &[Value]) -> Option<Filter> {
+    let mut terms: Vec<Filter> = vec![];
+    for (i, (key, value)) in keys.iter().zip(last).enumerate() {
+        let next = match next_value(key, value) {
+            Some(next) => next,
+            None => continue,
+        };
+        let mut term = match i {
+            0 => next,
+            _ => {
+                let mut equal = same_value(&keys[0], &last[0]);
+                for j in 1..i {
+                    equal.sub_filters.push(same_value(&keys[j], &last[j]));
+                }
+                let mut next = next;
+                next.connector = Connector::And;
+                equal.sub_filters.push(next);
+                equal
+            }
+        };
+        term.connector = Connector::Or;
+        terms.push(term);
+    }
+    let mut terms = terms.into_iter();
+    // AND is evaluated before OR, the first term can be merged into the filter
+    terms.next().map(|mut filter| {
+        filter.sub_filters.extend(terms);
+        filter
+    })
+}
+
+/// the column is past the value, None when nothing sorts after it
+fn next_value(key: &Key, value: &Value) -> Option<Filter> {
+    if let Value::None(_) = *value {
+        return if key.nulls_first {
+            Some(condition(&key.column, Equality::IS_NOT_NULL, value))
+        } else {
+            None
+        };
+    }
+    let equality = if key.descending {
+        Equality::LT
+    } else {
+        Equality::GT
+    };
+    let mut filter = condition(&key.column, equality, value);
+    if !key.nulls_first {
+        let mut is_null = condition(&key.column, Equality::IS_NULL, value);
+        is_null.connector = Connector::Or;
+        filter.sub_filters.push(is_null);
+    }
+    Some(filter)
+}
+
+/// the column is the same as the value
+fn same_value(key: &Key, value: &Value) -> Filter {
+    match *value {
+        Value::None(_) => condition(&key.column, Equality::IS_NULL, value),
+        _ => condition(&key.column, Equality::EQ, value),
+    }
+}
+
+fn condition(column: &ColumnName, equality: Equality, value: &Value) -> Filter {
+    Filter {
+        connector: Connector::And,
+        condition: Condition {
+            left: Operand::ColumnName(column.clone()),
+            equality: equality,
+            right: Operand::Value(value.clone()),
+        },
+        sub_filters: vec![],
+    }
+}
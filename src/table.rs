@@ -1,12 +1,15 @@
 use std::fmt;
 use dao::Type;
 use query::Operand;
+use query::Error as QueryError;
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct Foreign {
     pub schema: Option<String>,
     pub table: String,
     pub column: String, 
+    /// the name of the constraint, when introspected
+    pub name: Option<String>,
 }
 
 impl Foreign{
@@ -21,12 +24,14 @@ impl Foreign{
 				schema: Some(schema),
 				table: table,
 				column: column.to_owned(),
+				name: None,
 			}
 		}else{
 			Foreign{
 				schema: None,
 				table: schema_table.to_owned(),
 				column: column.to_owned(),
+				name: None,
 			}
 		}
 	}
@@ -38,6 +43,11 @@ impl Foreign{
         }
     }
 
+    /// whether both reference the same column, the constraint name is not compared
+    pub fn same_reference(&self, other: &Foreign) -> bool {
+        self.schema == other.schema && self.table == other.table && self.column == other.column
+    }
+
 }
 
 #[derive(Debug, Clone)]
@@ -59,6 +69,17 @@ pub struct Column {
 
 impl Column {
 
+    /// the introspected name of the foreign key constraint, needed to drop it
+    pub fn foreign_constraint_name(&self) -> Result<&str, QueryError> {
+        match self.foreign {
+            Some(Foreign { name: Some(ref name), .. }) => Ok(name),
+            _ => {
+                Err(QueryError::SqlError(format!("The foreign key constraint name of column {} is not known",
+                                                 self.name)))
+            }
+        }
+    }
+
     fn is_keyword(str: &str) -> bool {
         let keyword = ["type", "yield", "macro"];
         keyword.contains(&str)
@@ -72,7 +93,7 @@ impl Column {
     ///some column names may be a rust reserve keyword, so have to correct them
     pub fn corrected_name(&self) -> String {
         if Self::is_keyword(&self.name) {
-            println!("Warning: {} is rust reserved keyword", self.name);
+            warn!("{} is rust reserved keyword", self.name);
             return format!("{}_", self.name);
         }
         self.name.to_owned()
@@ -125,6 +146,36 @@ impl PartialEq for Column {
     }
 }
 
+/// an index of the table, other than the one of the primary key
+#[derive(Debug, Clone, PartialEq)]
+pub struct Index {
+    pub name: String,
+    /// the indexed columns, in order
+    pub columns: Vec<String>,
+    pub is_unique: bool,
+    /// the index method, ie: btree, hash, gin, gist
+    pub method: Option<String>,
+    /// the predicate of a partial index, ie: `active = true`
+    pub predicate: Option<String>,
+    /// the indexed expression, ie: `lower(name)`
+    pub expression: Option<String>,
+}
+
+impl Index {
+
+    /// a plain index on the columns
+    pub fn new(name: &str, columns: &[&str]) -> Self {
+        Index {
+            name: name.to_owned(),
+            columns: columns.iter().map(|c| c.to_string()).collect(),
+            is_unique: false,
+            method: None,
+            predicate: None,
+            expression: None,
+        }
+    }
+}
+
 /// trait for table definition
 pub trait IsTable {
     fn table() -> Table;
@@ -225,6 +276,9 @@ pub struct Table {
 
     /// views can also be generated
     pub is_view: bool,
+
+    /// the indexes of this table, the primary key is not included
+    pub indexes: Vec<Index>,
 }
 impl fmt::Display for Table {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
@@ -273,6 +327,15 @@ impl Table {
             None => self.name.to_owned(),
         }
     }
+    /// the name of the foreign key constraint of the column, the introspected name
+    /// or else as postgresql names it: `table_column_fkey`
+    pub fn foreign_key_name(&self, column: &Column) -> String {
+        match column.foreign {
+            Some(Foreign { name: Some(ref name), .. }) => name.to_owned(),
+            _ => format!("{}_{}_fkey", self.name, column.name),
+        }
+    }
+
     /// capitalize the first later, if there is underscore remove it then capitalize the next letter
     pub fn struct_name(&self) -> String {
         let mut struct_name = String::new();
@@ -816,6 +879,40 @@ impl Table {
 }
 
 
+/// order the tables so the referenced and the parent tables come first,
+/// the tables referencing each other are kept in their given order
+pub fn order_by_foreign_keys(tables: Vec<Table>) -> Vec<Table> {
+    let mut remaining = tables;
+    let mut ordered = vec![];
+    while !remaining.is_empty() {
+        let next = remaining.iter()
+                            .position(|table| {
+                                !remaining.iter().any(|other| {
+                                    other.complete_name() != table.complete_name() && depends_on(table, other)
+                                })
+                            })
+                            .unwrap_or(0);
+        ordered.push(remaining.remove(next));
+    }
+    ordered
+}
+
+/// whether the table references or inherits the other table
+fn depends_on(table: &Table, other: &Table) -> bool {
+    let references = table.columns
+                          .iter()
+                          .filter_map(|column| column.foreign.as_ref())
+                          .any(|foreign| {
+                              foreign.table == other.name &&
+                              (foreign.schema.is_none() || foreign.schema == other.schema)
+                          });
+    let inherits = match table.parent_table {
+        Some(ref parent) => *parent == other.name || *parent == other.complete_name(),
+        None => false,
+    };
+    references || inherits
+}
+
 fn capitalize(str: &str) -> String {
     str.chars()
         .take(1)
@@ -828,3 +925,33 @@ fn capitalize(str: &str) -> String {
 fn test_capitalize() {
     assert_eq!(capitalize("hello"), "Hello".to_owned());
 }
+
+#[test]
+fn test_order_by_foreign_keys() {
+    let table = |name: &str, references: Option<&str>| {
+        let column = Column {
+            name: "id".to_owned(),
+            data_type: Type::I32,
+            db_data_type: String::new(),
+            is_primary: false,
+            is_unique: false,
+            default: None,
+            comment: None,
+            not_null: false,
+            foreign: references.map(|t| Foreign::from_str(t, "id")),
+            is_inherited: false,
+        };
+        Table {
+            schema: Some("bazaar".to_owned()),
+            name: name.to_owned(),
+            columns: vec![column],
+            ..Default::default()
+        }
+    };
+    let tables = vec![table("cart_line", Some("bazaar.cart")),
+                      table("cart", Some("bazaar.users")),
+                      table("category", Some("bazaar.category")),
+                      table("users", None)];
+    let names: Vec<String> = order_by_foreign_keys(tables).into_iter().map(|t| t.name).collect();
+    assert_eq!(names, vec!["category", "users", "cart", "cart_line"]);
+}
@@ -0,0 +1,104 @@
+use database::{Database, DbError};
+
+/// A database transaction guard
+/// The changes are only kept when `commit` is explicitly called,
+/// the transaction is rolled back when this goes out of scope without committing.
+///
+/// Nested transactions are done through savepoints
+///
+/// ```ignore
+/// let mut tx = try!(Transaction::begin(db.as_ref()));
+/// try!(Query::insert().into_table("bazaar.category").set("name", &"Electronic").execute(tx.as_ref()));
+/// {
+///     let sp = try!(tx.savepoint());
+///     // changes here are undone, when sp is dropped without committing
+/// }
+/// try!(tx.commit());
+/// ```
+pub struct Transaction<'a> {
+    db: &'a Database,
+    /// the name of the savepoint, when this is a nested transaction
+    savepoint: Option<String>,
+    /// how deep this transaction is nested, the outermost transaction is 0
+    depth: usize,
+    /// committed or rolled back
+    finished: bool,
+}
+
+impl <'a>Transaction<'a> {
+
+    /// begin a transaction on this database connection
+    pub fn begin(db: &'a Database) -> Result<Self, DbError> {
+        if db.is_transacted() {
+            return Err(DbError::new("There is already a transaction in progress, use savepoint to nest transactions"));
+        }
+        try!(db.begin());
+        Ok(Transaction {
+            db: db,
+            savepoint: None,
+            depth: 0,
+            finished: false,
+        })
+    }
+
+    /// start a nested transaction, backed by a savepoint
+    /// the outer transaction can not be committed while the nested one is still alive
+    pub fn savepoint<'b>(&'b mut self) -> Result<Transaction<'b>, DbError> {
+        let depth = self.depth + 1;
+        let name = format!("rustorm_sp_{}", depth);
+        try!(self.db.savepoint(&name));
+        Ok(Transaction {
+            db: self.db,
+            savepoint: Some(name),
+            depth: depth,
+            finished: false,
+        })
+    }
+
+    /// the database connection used in this transaction
+    pub fn as_ref(&self) -> &Database {
+        self.db
+    }
+
+    /// whether this is a nested transaction
+    pub fn is_nested(&self) -> bool {
+        self.savepoint.is_some()
+    }
+
+    /// make the changes permanent,
+    /// nested transaction releases its savepoint, which merges the changes to the outer transaction
+    pub fn commit(mut self) -> Result<(), DbError> {
+        self.finished = true;
+        match self.savepoint {
+            Some(ref name) => self.db.release_savepoint(name),
+            None => self.db.commit(),
+        }
+    }
+
+    /// discard the changes,
+    /// nested transaction discards only the changes done after its savepoint, then releases it
+    pub fn rollback(mut self) -> Result<(), DbError> {
+        self.finished = true;
+        self.undo()
+    }
+
+    fn undo(&self) -> Result<(), DbError> {
+        match self.savepoint {
+            Some(ref name) => {
+                try!(self.db.rollback_to_savepoint(name));
+                self.db.release_savepoint(name)
+            }
+            None => self.db.rollback(),
+        }
+    }
+}
+
+impl <'a>Drop for Transaction<'a> {
+
+    /// rollback when not committed
+    fn drop(&mut self) {
+        if !self.finished {
+            let _ = self.undo();
+        }
+    }
+}
@@ -1,5 +1,6 @@
 use dao::Value;
 use database::{SqlOption,BuildMode};
+use query::Error as QueryError;
 use std::fmt;
 
 /// sql fragment
@@ -130,6 +131,22 @@ impl SqlFrag {
         self.append("-- ");
         self.append(comment)
     }
+    /// append the sql and the parameters of a fragment that is built separately, such as subqueries.
+    /// The numbered parameters of the fragment are renumbered to continue from the parameters
+    /// already in this fragment, question mark parameters only needs to be in the same order.
+    pub fn merge(&mut self, frag: SqlFrag) -> Result<(), QueryError> {
+        let offset = self.params.len();
+        if offset > 0 && self.build_mode == BuildMode::Standard &&
+           self.sql_options.contains(&SqlOption::UsesNumberedParam) {
+            let sql = try!(renumber_params(&frag.sql, offset));
+            self.append(&sql);
+        } else {
+            self.append(&frag.sql);
+        }
+        self.params.extend(frag.params);
+        Ok(())
+    }
+
     ///append parameter including the needed sql keywords
     pub fn parameter(&mut self, param: Value) {
 		match self.build_mode{
@@ -150,3 +167,95 @@ impl SqlFrag {
     }
 
 }
+
+/// shift the numbered parameters `$n` of the sql by offset
+fn renumber_params(sql: &str, offset: usize) -> Result<String, QueryError> {
+    replace_params(sql, |n| Ok(format!("${}", n + offset)))
+}
+
+/// replace the numbered parameters `$n` of the sql with the text returned for their number,
+/// the quoted texts, dollar quoted bodies and comments are left as is
+pub fn replace_params<F>(sql: &str, mut replace: F) -> Result<String, QueryError>
+    where F: FnMut(usize) -> Result<String, QueryError>
+{
+    let mut replaced = String::with_capacity(sql.len());
+    let mut i = 0;
+    while i < sql.len() {
+        let rest = &sql[i..];
+        let skip = quoted_len(rest);
+        if skip > 0 {
+            replaced.push_str(&rest[..skip]);
+            i += skip;
+            continue;
+        }
+        let c = rest.chars().next().unwrap();
+        i += c.len_utf8();
+        if c == '$' {
+            let digits: String = sql[i..].chars().take_while(|d| d.is_digit(10)).collect();
+            if !digits.is_empty() {
+                let n = try!(digits.parse::<usize>()
+                                   .map_err(|_| QueryError::SqlError(format!("Invalid parameter ${}", digits))));
+                replaced.push_str(&try!(replace(n)));
+                i += digits.len();
+                continue;
+            }
+        }
+        replaced.push(c);
+    }
+    Ok(replaced)
+}
+
+/// the length of the quoted text, dollar quoted body or comment at the start of the sql,
+/// 0 when the sql doesn't start with one
+pub fn quoted_len(sql: &str) -> usize {
+    let c = match sql.chars().next() {
+        Some(c) => c,
+        None => return 0,
+    };
+    if c == '\'' || c == '"' || c == '`' {
+        // the doubled quote is an escaped quote, it is read as 2 quoted texts
+        sql[1..].find(c).map_or(sql.len(), |end| end + 2)
+    } else if sql.starts_with("--") {
+        sql.find('\n').unwrap_or(sql.len())
+    } else if sql.starts_with("/*") {
+        sql[2..].find("*/").map_or(sql.len(), |end| end + 4)
+    } else if let Some(tag) = dollar_tag(sql) {
+        sql[tag.len()..].find(tag).map_or(sql.len(), |end| end + 2 * tag.len())
+    } else {
+        0
+    }
+}
+
+/// the `$$` or `$tag$` starting the text
+fn dollar_tag(text: &str) -> Option<&str> {
+    if !text.starts_with('$') {
+        return None;
+    }
+    let end = match text[1..].find('$') {
+        Some(end) => end + 1,
+        None => return None,
+    };
+    let tag = &text[1..end];
+    let is_tag = tag.chars().all(|t| t.is_alphanumeric() || t == '_') &&
+                 !tag.chars().next().map_or(false, |t| t.is_digit(10));
+    if is_tag {
+        Some(&text[..end + 1])
+    } else {
+        None
+    }
+}
+
+#[test]
+fn test_renumber_params() {
+    let sql = "SELECT * FROM product WHERE name = $1 AND price > $2 ";
+    assert_eq!(renumber_params(sql, 3).unwrap(),
+               "SELECT * FROM product WHERE name = $4 AND price > $5 ");
+}
+
+#[test]
+fn test_renumber_params_skips_quoted_text() {
+    let sql = "SELECT '$1', \"$2\", $body$ $3 $body$ FROM product -- $4\nWHERE price > $1 /* $5 */";
+    assert_eq!(renumber_params(sql, 3).unwrap(),
+               "SELECT '$1', \"$2\", $body$ $3 $body$ FROM product -- $4\nWHERE price > $4 /* $5 */");
+    assert!(renumber_params("SELECT $99999999999999999999999", 1).is_err());
+}
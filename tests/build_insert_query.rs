@@ -6,8 +6,11 @@ extern crate rustc_serialize;
 use uuid::Uuid;
 
 use rustorm::query::Query;
-use rustorm::dao::{Dao, IsDao};
+use rustorm::dao::{Dao, IsDao, Type, Value};
+use rustorm::em::EntityManager;
+use rustorm::table::{Column, IsTable, Table};
 use rustorm::pool::ManagedPool;
+use rustorm::platform::{MockDatabase, Postgres};
 
 #[derive(Debug, Clone)]
 pub struct Photo {
@@ -34,6 +37,55 @@ impl IsDao for Photo{
     }
 }
 
+/// a record which leaves out the columns it has no value for
+#[derive(Debug, Clone, PartialEq)]
+pub struct Stock {
+    pub name: String,
+    pub quantity: Option<i32>,
+}
+
+impl IsDao for Stock {
+    fn from_dao(dao: &Dao) -> Self {
+        Stock {
+            name: dao.get("name"),
+            quantity: dao.get_opt("quantity"),
+        }
+    }
+
+    fn to_dao(&self) -> Dao {
+        let mut dao = Dao::new();
+        dao.set("name", &self.name);
+        if let Some(quantity) = self.quantity {
+            dao.set("quantity", &quantity);
+        }
+        dao
+    }
+}
+
+impl IsTable for Stock {
+    fn table() -> Table {
+        let column = |name: &str, data_type: Type| {
+            Column {
+                name: name.to_owned(),
+                data_type: data_type,
+                db_data_type: String::new(),
+                is_primary: false,
+                is_unique: false,
+                default: None,
+                comment: None,
+                not_null: false,
+                foreign: None,
+                is_inherited: false,
+            }
+        };
+        Table {
+            schema: Some("bazaar".to_owned()),
+            name: "stock".to_owned(),
+            columns: vec![column("name", Type::String), column("quantity", Type::I32)],
+            ..Default::default()
+        }
+    }
+}
 
 #[test]
 fn test_insert_query() {
@@ -45,17 +97,85 @@ fn test_insert_query() {
 
     query.into_table("bazaar.product")
          .set("name", &"product1")
-         .returns(vec!["category.name"]);
+         .returns(vec!["product.name"]);
 
-    let frag = query.build(db.as_ref());
+    let frag = query.build(db.as_ref()).unwrap();
 
     let expected = "
-   INSERT INTO bazaar.product( name )\x20
+   INSERT INTO \"bazaar\".\"product\"( \"name\" )\x20
    VALUES ($1 )\x20
-RETURNING name
+RETURNING \"name\"
 ".to_string();
     println!("actual:   {{\n{}}} [{}]", frag.sql, frag.sql.len());
     println!("expected: {{{}}} [{}]", expected, expected.len());
     assert!(frag.sql.trim() == expected.trim());
 
 }
+
+#[test]
+fn test_insert_on_conflict_query() {
+    let pg = Postgres::new();
+    let mut query = Query::insert();
+
+    query.into_table("bazaar.category")
+         .set("category_id", &"f7521093-734d-488a-9f60-fc9f11f7e750")
+         .set("name", &"Electronic")
+         .on_conflict(vec!["category_id"], vec!["name"])
+         .return_all();
+
+    let frag = query.build(&pg).unwrap();
+
+    let expected = "
+   INSERT INTO \"bazaar\".\"category\"( \"category_id\", \"name\" )\x20
+   VALUES ($1 , $2 )\x20
+ON CONFLICT (\"category_id\") DO UPDATE\x20
+      SET \"name\" = EXCLUDED.\"name\"\x20
+RETURNING *
+".to_string();
+    println!("actual:   {{\n{}}} [{}]", frag.sql, frag.sql.len());
+    println!("expected: {{{}}} [{}]", expected, expected.len());
+    assert_eq!(frag.sql.trim(), expected.trim());
+}
+
+#[test]
+fn test_insert_multiple_rows_query() {
+    let pg = Postgres::new();
+    let mut query = Query::insert();
+
+    query.into_table("bazaar.category")
+         .columns(vec!["name", "description"])
+         .add_row(&[Value::String("Electronic".to_owned()), Value::String("gadgets".to_owned())])
+         .add_row(&[Value::String("Books".to_owned()), Value::String("reading".to_owned())]);
+
+    let frag = query.build(&pg).unwrap();
+
+    let expected = "
+   INSERT INTO \"bazaar\".\"category\"( \"name\", \"description\" )\x20
+   VALUES ($1 , $2 ),
+          ($3 , $4 )\x20
+".to_string();
+    println!("actual:   {{\n{}}} [{}]", frag.sql, frag.sql.len());
+    println!("expected: {{{}}} [{}]", expected, expected.len());
+    assert_eq!(frag.sql.trim(), expected.trim());
+    assert_eq!(frag.params.len(), 4);
+}
+
+#[test]
+fn test_insert_all_uses_the_columns_of_every_record() {
+    let db = MockDatabase::new();
+    let stocks = vec![Stock {
+                          name: "Books".to_owned(),
+                          quantity: None,
+                      },
+                      Stock {
+                          name: "Pens".to_owned(),
+                          quantity: Some(12),
+                      }];
+    EntityManager::new(&db).insert_all(&stocks).unwrap();
+    db.assert_executed("INSERT INTO \"bazaar\".\"stock\"( \"name\", \"quantity\" ) VALUES ($1 , $2 ), ($3 , $4 )",
+                       &[Value::String("Books".to_owned()),
+                         Value::None(Type::I32),
+                         Value::String("Pens".to_owned()),
+                         Value::I32(12)]);
+    assert_eq!(db.executed().len(), 1);
+}
@@ -0,0 +1,71 @@
+#![cfg(feature = "sqlite")]
+extern crate rustorm;
+
+use rustorm::query::Query;
+use rustorm::database::Database;
+use rustorm::pool::{ManagedPool, Platform};
+use rustorm::copy::{CopiedTable, SchemaCopy};
+
+fn connect() -> Platform {
+    let pool = ManagedPool::init("sqlite://:memory:", 1).unwrap();
+    pool.connect().unwrap()
+}
+
+fn create_source() -> Platform {
+    let db = connect();
+    let sql = ["CREATE TABLE product(product_id integer PRIMARY KEY, name text NOT NULL, price real,
+                                     category_id integer REFERENCES category(category_id))",
+               "CREATE INDEX product_name_idx ON product(name)",
+               "CREATE TABLE category(category_id integer PRIMARY KEY, name text)",
+               "INSERT INTO category VALUES (1, 'Electronics')",
+               "INSERT INTO product VALUES (1, 'GTX660 Ti videocard', 250.5, 1)",
+               "INSERT INTO product VALUES (2, 'Mouse', NULL, 1)",
+               "INSERT INTO product VALUES (3, 'Cable', 2.0, NULL)"];
+    for statement in &sql {
+        db.as_ref().execute_sql(statement, &[]).unwrap();
+    }
+    db
+}
+
+fn copied_table(name: &str, rows: usize) -> CopiedTable {
+    CopiedTable {
+        name: name.to_owned(),
+        rows: rows,
+        skipped: vec![],
+    }
+}
+
+fn has_index(db: &Database, index: &str) -> bool {
+    let sql = format!("SELECT name FROM sqlite_master WHERE type = 'index' AND name = '{}'", index);
+    !db.execute_sql_with_return(&sql, &[]).unwrap().is_empty()
+}
+
+#[test]
+fn test_copy_tables_and_records() {
+    let source = create_source();
+    let target = connect();
+    let copied = SchemaCopy::new().batch_size(2).copy(&source, &target).unwrap();
+    assert_eq!(copied, vec![copied_table("category", 1), copied_table("product", 3)]);
+    assert!(has_index(target.as_ref(), "product_name_idx"));
+
+    let products = Query::select_all()
+                       .from_table("product")
+                       .asc("product_id")
+                       .retrieve(target.as_ref())
+                       .unwrap()
+                       .dao;
+    let names: Vec<String> = products.iter().map(|p| p.try_get("name").unwrap()).collect();
+    assert_eq!(names, vec!["GTX660 Ti videocard", "Mouse", "Cable"]);
+    assert_eq!(products[0].try_get::<f64>("price").unwrap(), 250.5);
+    assert_eq!(products[1].try_get_opt::<f64>("price").unwrap(), None);
+}
+
+#[test]
+fn test_schema_only() {
+    let source = create_source();
+    let target = connect();
+    let copied = SchemaCopy::new().schema_only().copy(&source, &target).unwrap();
+    assert_eq!(copied, vec![copied_table("category", 0), copied_table("product", 0)]);
+    let result = Query::select_all().from_table("product").retrieve(target.as_ref()).unwrap();
+    assert!(result.dao.is_empty());
+}
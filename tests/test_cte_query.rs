@@ -0,0 +1,88 @@
+extern crate rustorm;
+
+use rustorm::query::Query;
+use rustorm::query::Filter;
+use rustorm::platform::Postgres;
+
+#[test]
+fn test_recursive_query() {
+    let pg = Postgres::new();
+
+    let mut base = Query::select();
+    base.columns(vec!["organization_id", "parent_organization_id", "name"])
+        .from_table("bazaar.organization")
+        .add_filter(Filter::is_null("parent_organization_id"));
+
+    let mut recursive = Query::select();
+    recursive.columns(vec!["organization.organization_id",
+                           "organization.parent_organization_id",
+                           "organization.name"])
+             .from_table("bazaar.organization")
+             .inner_join_table("org_tree",
+                               "org_tree.organization_id",
+                               "organization.parent_organization_id");
+
+    let mut query = Query::select_all();
+    query.declare_recursive_query(base, recursive, "org_tree")
+         .from_table("org_tree");
+    let frag = query.build(&pg).unwrap();
+
+    let expected = "
+WITH RECURSIVE \"org_tree\" AS (
+   SELECT \"organization_id\", \"parent_organization_id\", \"name\"
+     FROM \"bazaar\".\"organization\"
+    WHERE \"parent_organization_id\" IS NULL
+UNION ALL\x20
+   SELECT \"organization\".\"organization_id\", \"organization\".\"parent_organization_id\", \"organization\".\"name\"
+     FROM \"bazaar\".\"organization\"
+          INNER JOIN \"org_tree\"\x20
+          ON \"org_tree\".\"organization_id\" = \"organization\".\"parent_organization_id\"\x20
+)
+   SELECT *
+     FROM \"org_tree\"".to_string();
+    println!("actual:   {{\n{}}} [{}]", frag.sql, frag.sql.len());
+    println!("expected: {{{}}} [{}]", expected, expected.len());
+    assert_eq!(frag.sql.trim(), expected.trim());
+}
+
+#[test]
+fn test_declared_query_params() {
+    let pg = Postgres::new();
+
+    let mut electronics = Query::select_all();
+    electronics.from_table("bazaar.category")
+               .filter_eq("name", &"Electronic");
+
+    let mut query = Query::select_all();
+    query.declare_query(electronics, "electronics")
+         .from_table("electronics")
+         .filter_eq("active", &true);
+    let frag = query.build(&pg).unwrap();
+
+    let expected = "
+     WITH \"electronics\" AS (
+   SELECT *
+     FROM \"bazaar\".\"category\"
+    WHERE \"name\" = $1\x20
+)
+   SELECT *
+     FROM \"electronics\"
+    WHERE \"active\" = $2".to_string();
+    println!("actual:   {{\n{}}} [{}]", frag.sql, frag.sql.len());
+    println!("expected: {{{}}} [{}]", expected, expected.len());
+    assert_eq!(frag.sql.trim(), expected.trim());
+    assert_eq!(frag.params.len(), 2);
+}
+
+#[cfg(feature = "mysql")]
+#[test]
+fn test_cte_unsupported() {
+    use rustorm::database::Database;
+    use rustorm::platform::Mysql;
+
+    let my = Mysql::new();
+    let mut query = Query::select_all();
+    query.declare_query(Query::select_all(), "t1")
+         .from_table("t1");
+    assert!(my.check_query_support(&query).is_err());
+}
@@ -0,0 +1,86 @@
+extern crate rustorm;
+
+use rustorm::query::Query;
+use rustorm::query::{Function, DateUnit};
+use rustorm::dialect::{Dialect, DialectKind, Version};
+use rustorm::database::Database;
+use rustorm::dao::Type;
+
+#[test]
+fn test_sqlite_without_connection() {
+    let mut query = Query::select();
+    query.function(Function::date_trunc(DateUnit::Day, "created"), "day")
+         .function(Function::now(), "today")
+         .from_table("orders");
+    let frag = Dialect::sqlite().render(&mut query).unwrap();
+
+    let expected = "
+   SELECT strftime('%Y-%m-%d 00:00:00', \"created\") AS \"day\", datetime('now') AS \"today\"
+     FROM \"orders\"".to_string();
+    println!("actual:   {{\n{}}} [{}]", frag.sql, frag.sql.len());
+    println!("expected: {{{}}} [{}]", expected, expected.len());
+    assert_eq!(frag.sql.trim(), expected.trim());
+}
+
+#[test]
+fn test_mysql_by_name() {
+    let dialect = Dialect::from_name("MySQL").unwrap();
+    assert_eq!(dialect.kind(), DialectKind::Mysql);
+    let mut query = Query::select();
+    query.column("name")
+         .from_table("bazaar.product")
+         .filter_eq("order", &1);
+    let frag = dialect.render(&mut query).unwrap();
+
+    let expected = "
+   SELECT `name`
+     FROM `product`
+    WHERE `order` = ?".to_string();
+    println!("actual:   {{\n{}}} [{}]", frag.sql, frag.sql.len());
+    println!("expected: {{{}}} [{}]", expected, expected.len());
+    assert_eq!(frag.sql.trim(), expected.trim());
+    assert_eq!(dialect.type_name(&Type::Uuid), Some("varchar(36)".to_owned()));
+    assert!(Dialect::from_name("oracle").is_none());
+}
+
+#[test]
+fn test_mysql_cte_by_version() {
+    let mut recent = Query::select_all();
+    recent.from_table("product")
+          .filter_eq("active", &true);
+    let mut query = Query::select_all();
+    query.declare_query(recent, "active_product")
+         .from_table("active_product");
+
+    let mut dialect = Dialect::mysql();
+    dialect.version(Version::parse("5.7.31-log").unwrap());
+    assert!(dialect.render(&mut query).is_err());
+    dialect.version(Version::new(8, 0, 21));
+    assert!(dialect.render(&mut query).is_ok());
+}
+
+#[test]
+fn test_renderer_is_not_connected() {
+    let renderer = Dialect::postgres().renderer();
+    assert!(renderer.execute_sql("SELECT 1", &[]).is_err());
+    assert!(!renderer.is_connected());
+}
+
+#[test]
+fn test_upsert_by_version() {
+    let mut query = Query::insert();
+    query.into_table("category")
+         .set("category_id", &1)
+         .set("name", &"Electronic")
+         .on_conflict(vec!["category_id"], vec!["name"]);
+
+    let mut dialect = Dialect::postgres();
+    assert!(dialect.render(&mut query).is_ok());
+    dialect.version(Version::new(9, 4, 10));
+    assert!(dialect.render(&mut query).is_err());
+    let mut dialect = Dialect::sqlite();
+    dialect.version(Version::new(3, 23, 1));
+    assert!(dialect.render(&mut query).is_err());
+    dialect.version(Version::new(3, 24, 0));
+    assert!(dialect.render(&mut query).is_ok());
+}
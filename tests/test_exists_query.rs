@@ -0,0 +1,73 @@
+extern crate rustorm;
+
+use rustorm::query::Query;
+use rustorm::query::{Filter, Equality};
+use rustorm::platform::Postgres;
+
+#[test]
+fn test_correlated_exists_and_in_query() {
+    let pg = Postgres::new();
+
+    let mut photos = Query::select_all();
+    photos.from_table("bazaar.product_photo")
+          .add_filter(Filter::with_column("product_photo.product_id",
+                                          Equality::EQ,
+                                          "product.product_id"));
+
+    let mut categories = Query::select();
+    categories.column("category_id")
+              .from_table("bazaar.category")
+              .filter_eq("name", &"Electronic");
+
+    let mut query = Query::select_all();
+    query.from_table("bazaar.product")
+         .add_filter(Filter::exists(photos))
+         .add_filter(Filter::in_query("category_id", categories))
+         .filter_eq("is_active", &true);
+    let frag = query.build(&pg).unwrap();
+
+    let expected = "
+   SELECT *
+     FROM \"bazaar\".\"product\"
+    WHERE EXISTS (
+   SELECT *
+     FROM \"bazaar\".\"product_photo\"
+    WHERE \"product_id\" = \"product\".\"product_id\")\x20
+      AND \"category_id\" IN (
+   SELECT \"category_id\"
+     FROM \"bazaar\".\"category\"
+    WHERE \"name\" = $1 )
+      AND \"is_active\" = $2".to_string();
+    println!("actual:   {{\n{}}} [{}]", frag.sql, frag.sql.len());
+    println!("expected: {{{}}} [{}]", expected, expected.len());
+    assert_eq!(frag.sql.trim(), expected.trim());
+    assert_eq!(frag.params.len(), 2);
+}
+
+#[test]
+fn test_not_exists_query() {
+    let pg = Postgres::new();
+
+    let mut orders = Query::select_all();
+    orders.from_table("bazaar.order_line")
+          .add_filter(Filter::with_column("order_line.product_id",
+                                          Equality::EQ,
+                                          "product.product_id"));
+
+    let mut query = Query::select();
+    query.column("name")
+         .from_table("bazaar.product")
+         .add_filter(Filter::not_exists(orders));
+    let frag = query.build(&pg).unwrap();
+
+    let expected = "
+   SELECT \"name\"
+     FROM \"bazaar\".\"product\"
+    WHERE NOT EXISTS (
+   SELECT *
+     FROM \"bazaar\".\"order_line\"
+    WHERE \"product_id\" = \"product\".\"product_id\")".to_string();
+    println!("actual:   {{\n{}}} [{}]", frag.sql, frag.sql.len());
+    println!("expected: {{{}}} [{}]", expected, expected.len());
+    assert_eq!(frag.sql.trim(), expected.trim());
+}
@@ -0,0 +1,69 @@
+extern crate rustorm;
+
+use rustorm::query::Query;
+use rustorm::query::{Function, DateUnit, Operand, ColumnName};
+use rustorm::dao::Value;
+use rustorm::platform::Postgres;
+
+#[test]
+fn test_aggregate_functions() {
+    let pg = Postgres::new();
+    let mut query = Query::select();
+    query.function(Function::date_trunc(DateUnit::Month, "created"), "month")
+         .function(Function::count("*"), "orders")
+         .function(Function::now(), "today")
+         .from_table("bazaar.orders")
+         .group_by(vec!["month"]);
+    let frag = query.build(&pg).unwrap();
+
+    let expected = "
+   SELECT date_trunc('month', \"created\") AS \"month\", COUNT(*) AS \"orders\", now() AS \"today\"
+     FROM \"bazaar\".\"orders\"
+ GROUP BY \"month\" ".to_string();
+    println!("actual:   {{\n{}}} [{}]", frag.sql, frag.sql.len());
+    println!("expected: {{{}}} [{}]", expected, expected.len());
+    assert_eq!(frag.sql.trim(), expected.trim());
+}
+
+#[test]
+fn test_concat_coalesce() {
+    let pg = Postgres::new();
+    let mut query = Query::select();
+    query.function(Function::concat(vec![Operand::ColumnName(ColumnName::from_str("firstname")),
+                                         Operand::Value(Value::String(" ".to_owned())),
+                                         Operand::ColumnName(ColumnName::from_str("lastname"))]),
+                   "full_name")
+         .function(Function::coalesce(vec![Operand::ColumnName(ColumnName::from_str("credit")),
+                                           Operand::Value(Value::I32(0))]),
+                   "credit")
+         .from_table("bazaar.users");
+    let frag = query.build(&pg).unwrap();
+
+    let expected = "
+   SELECT (\"firstname\" || $1  || \"lastname\") AS \"full_name\", COALESCE(\"credit\", $2 ) AS \"credit\"
+     FROM \"bazaar\".\"users\"".to_string();
+    println!("actual:   {{\n{}}} [{}]", frag.sql, frag.sql.len());
+    println!("expected: {{{}}} [{}]", expected, expected.len());
+    assert_eq!(frag.sql.trim(), expected.trim());
+    assert_eq!(frag.params.len(), 2);
+}
+
+#[cfg(feature = "sqlite")]
+#[test]
+fn test_sqlite_functions() {
+    use rustorm::platform::Sqlite;
+
+    let lite = Sqlite::new();
+    let mut query = Query::select();
+    query.function(Function::date_trunc(DateUnit::Day, "created"), "day")
+         .function(Function::now(), "today")
+         .from_table("orders");
+    let frag = query.build(&lite).unwrap();
+
+    let expected = "
+   SELECT strftime('%Y-%m-%d 00:00:00', \"created\") AS \"day\", datetime('now') AS \"today\"
+     FROM \"orders\"".to_string();
+    println!("actual:   {{\n{}}} [{}]", frag.sql, frag.sql.len());
+    println!("expected: {{{}}} [{}]", expected, expected.len());
+    assert_eq!(frag.sql.trim(), expected.trim());
+}
@@ -0,0 +1,107 @@
+extern crate rustorm;
+
+use rustorm::query::Query;
+use rustorm::query::Error;
+use rustorm::query::Equality;
+use rustorm::database::Database;
+use rustorm::platform::Postgres;
+
+#[test]
+fn test_reserved_and_mixed_case_names() {
+    let pg = Postgres::new();
+    let mut query = Query::select();
+    query.columns(vec!["type", "Display Name"])
+         .from_table("public.user")
+         .filter_eq("order", &1)
+         .desc("createdAt");
+    let frag = query.build(&pg).unwrap();
+
+    let expected = "
+   SELECT \"type\", \"Display Name\"
+     FROM \"public\".\"user\"
+    WHERE \"order\" = $1\x20
+ ORDER BY \"createdAt\" DESC".to_string();
+    println!("actual:   {{\n{}}} [{}]", frag.sql, frag.sql.len());
+    println!("expected: {{{}}} [{}]", expected, expected.len());
+    assert_eq!(frag.sql.trim(), expected.trim());
+}
+
+#[test]
+fn test_unsafe_sort_column_is_rejected() {
+    let pg = Postgres::new();
+    let mut query = Query::select_all();
+    query.from_table("bazaar.product")
+         .asc("name\"; DROP TABLE product; --");
+    match query.build(&pg) {
+        Err(Error::InvalidIdentifier(_)) => (),
+        Err(e) => panic!("unexpected error {}", e),
+        Ok(frag) => panic!("expecting an error, got {}", frag.sql),
+    }
+}
+
+#[test]
+fn test_having_aggregate() {
+    let pg = Postgres::new();
+    let mut query = Query::select();
+    query.column("category_id")
+         .from_table("bazaar.product")
+         .group_by(vec!["category_id"])
+         .having("count(*)", Equality::GT, &1);
+    let frag = query.build(&pg).unwrap();
+    println!("{}", frag.sql);
+    assert!(frag.sql.contains("HAVING count(*) > $1"));
+
+    let mut query = Query::select();
+    query.column("category_id")
+         .from_table("bazaar.product")
+         .group_by(vec!["category_id"])
+         .having("max(price)", Equality::LT, &100);
+    let frag = query.build(&pg).unwrap();
+    println!("{}", frag.sql);
+    assert!(frag.sql.contains("HAVING max(\"price\") < $1"));
+}
+
+#[test]
+fn test_unparsable_having_is_rejected() {
+    let pg = Postgres::new();
+    let mut query = Query::select();
+    query.column("category_id")
+         .from_table("bazaar.product")
+         .group_by(vec!["category_id"])
+         .having("sum(price * quantity)", Equality::GT, &1);
+    match query.build(&pg) {
+        Err(Error::InvalidIdentifier(_)) => (),
+        Err(e) => panic!("unexpected error {}", e),
+        Ok(frag) => panic!("expecting an error, got {}", frag.sql),
+    }
+}
+
+#[test]
+fn test_validate_identifier() {
+    let pg = Postgres::new();
+    assert!(pg.validate_identifier("product_id").is_ok());
+    assert!(pg.validate_identifier("Display Name").is_ok());
+    assert!(pg.validate_identifier("").is_err());
+    assert!(pg.validate_identifier("na\"me").is_err());
+    assert!(pg.validate_identifier("name\0").is_err());
+}
+
+#[cfg(feature = "mysql")]
+#[test]
+fn test_mysql_backtick_quoting() {
+    use rustorm::platform::Mysql;
+
+    let my = Mysql::new();
+    let mut query = Query::select();
+    query.column("order")
+         .from_table("user");
+    let frag = query.build(&my).unwrap();
+
+    let expected = "
+   SELECT `order`
+     FROM `user`".to_string();
+    println!("actual:   {{\n{}}} [{}]", frag.sql, frag.sql.len());
+    println!("expected: {{{}}} [{}]", expected, expected.len());
+    assert_eq!(frag.sql.trim(), expected.trim());
+    assert!(my.validate_identifier("ord`er").is_err());
+}
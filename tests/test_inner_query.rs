@@ -10,6 +10,8 @@ use rustorm::query::Equality;
 use rustorm::dao::{Dao, IsDao};
 use rustorm::pool::ManagedPool;
 use rustorm::query::{Join, Modifier, JoinType, ToTableName};
+use rustorm::query::{Filter, Operand, ColumnName};
+use rustorm::platform::Postgres;
 
 #[derive(Debug, Clone)]
 pub struct Photo {
@@ -61,24 +63,24 @@ fn test_inner_join_query() {
          .having("count(*)", Equality::GT, &1)
          .asc("product.name")
          .desc("product.created");
-    let frag = query.build(db.as_ref());
+    let frag = query.build(db.as_ref()).unwrap();
 
     let expected = "
    SELECT *
-     FROM bazaar.product
-          INNER JOIN bazaar.product_category\x20
-          ON product_category.product_id = product.product_id\x20
-          INNER JOIN bazaar.category\x20
-          ON category.category_id = product_category.category_id\x20
-          INNER JOIN product_photo\x20
-          ON product.product_id = product_photo.product_id\x20
-          INNER JOIN bazaar.photo\x20
-          ON product_photo.photo_id = photo.photo_id\x20
-    WHERE product.name = $1\x20
-      AND category.name = $2\x20
- GROUP BY category.name\x20
+     FROM \"bazaar\".\"product\"
+          INNER JOIN \"bazaar\".\"product_category\"\x20
+          ON \"product_category\".\"product_id\" = \"product\".\"product_id\"\x20
+          INNER JOIN \"bazaar\".\"category\"\x20
+          ON \"category\".\"category_id\" = \"product_category\".\"category_id\"\x20
+          INNER JOIN \"product_photo\"\x20
+          ON \"product\".\"product_id\" = \"product_photo\".\"product_id\"\x20
+          INNER JOIN \"bazaar\".\"photo\"\x20
+          ON \"product_photo\".\"photo_id\" = \"photo\".\"photo_id\"\x20
+    WHERE \"product\".\"name\" = $1\x20
+      AND \"category\".\"name\" = $2\x20
+ GROUP BY \"category\".\"name\"\x20
    HAVING count(*) > $3\x20
- ORDER BY product.name ASC, product.created DESC".to_string();
+ ORDER BY \"product\".\"name\" ASC, \"product\".\"created\" DESC".to_string();
     println!("actual:   {{\n{}}} [{}]", frag.sql, frag.sql.len());
     println!("expected: {{{}}} [{}]", expected, expected.len());
     assert!(frag.sql.trim() == expected.trim());
@@ -116,27 +118,58 @@ fn test_left_inner_join_query() {
          .having("count(*)", Equality::GT, &1)
          .asc("product.name")
          .desc("product.created");
-    let frag = query.build(db.as_ref());
+    let frag = query.build(db.as_ref()).unwrap();
 
     let expected = "
    SELECT *
-     FROM bazaar.product
+     FROM \"bazaar\".\"product\"
           LEFT\x20
-          INNER JOIN bazaar.product_category\x20
-          ON product_category.product_id = product.product_id\x20
-          INNER JOIN bazaar.category\x20
-          ON category.category_id = product_category.category_id\x20
-          LEFT JOIN product_photo\x20
-          ON product.product_id = product_photo.product_id\x20
-          INNER JOIN bazaar.photo\x20
-          ON product_photo.photo_id = photo.photo_id\x20
-    WHERE product.name = $1\x20
-      AND category.name = $2\x20
- GROUP BY category.name\x20
+          INNER JOIN \"bazaar\".\"product_category\"\x20
+          ON \"product_category\".\"product_id\" = \"product\".\"product_id\"\x20
+          INNER JOIN \"bazaar\".\"category\"\x20
+          ON \"category\".\"category_id\" = \"product_category\".\"category_id\"\x20
+          LEFT JOIN \"product_photo\"\x20
+          ON \"product\".\"product_id\" = \"product_photo\".\"product_id\"\x20
+          INNER JOIN \"bazaar\".\"photo\"\x20
+          ON \"product_photo\".\"photo_id\" = \"photo\".\"photo_id\"\x20
+    WHERE \"product\".\"name\" = $1\x20
+      AND \"category\".\"name\" = $2\x20
+ GROUP BY \"category\".\"name\"\x20
    HAVING count(*) > $3\x20
- ORDER BY product.name ASC, product.created DESC".to_string();
+ ORDER BY \"product\".\"name\" ASC, \"product\".\"created\" DESC".to_string();
     println!("actual:   {{\n{:?}}} [{}]", frag.sql, frag.sql.len());
     println!("expected: {{\n{:?}}} [{}]", expected, expected.len());
     assert_eq!(frag.sql.trim(), expected.trim());
 
 }
+
+#[test]
+fn test_filtered_subquery_params() {
+    let pg = Postgres::new();
+
+    let mut inner = Query::select();
+    inner.column("product_id")
+         .from_table("bazaar.product_category")
+         .filter_eq("category_id", &"f7521093-734d-488a-9f60-fc9f11f7e750");
+
+    let mut query = Query::select_all();
+    query.from_table("bazaar.product")
+         .filter("price", Equality::GT, &10)
+         .add_filter(Filter::bare_new(Operand::ColumnName(ColumnName::from_str("product_id")),
+                                      Equality::IN,
+                                      Operand::Query(inner)));
+    let frag = query.build(&pg).unwrap();
+
+    let expected = "
+   SELECT *
+     FROM \"bazaar\".\"product\"
+    WHERE \"price\" > $1\x20
+      AND \"product_id\" IN (
+   SELECT \"product_id\"
+     FROM \"bazaar\".\"product_category\"
+    WHERE \"category_id\" = $2 )".to_string();
+    println!("actual:   {{\n{}}} [{}]", frag.sql, frag.sql.len());
+    println!("expected: {{{}}} [{}]", expected, expected.len());
+    assert_eq!(frag.sql.trim(), expected.trim());
+    assert_eq!(frag.params.len(), 2);
+}
@@ -0,0 +1,149 @@
+#![cfg(feature = "sqlite")]
+extern crate rustorm;
+
+use std::env;
+use std::fs::{self, File};
+use std::io::Write;
+use std::process;
+use std::time::{SystemTime, UNIX_EPOCH};
+use rustorm::query::Query;
+use rustorm::database::Database;
+use rustorm::pool::{ManagedPool, Platform};
+use rustorm::migration::{Migrator, Migration, MIGRATIONS_TABLE};
+
+const CREATE_PRODUCT: &'static str = "CREATE TABLE product(name text);
+CREATE INDEX product_name_idx ON product(name);";
+
+fn connect() -> Platform {
+    let pool = ManagedPool::init("sqlite://:memory:", 1).unwrap();
+    pool.connect().unwrap()
+}
+
+fn create_product() -> Migration {
+    let mut migration = Migration::sql(1, "create_product", CREATE_PRODUCT);
+    migration.down_sql("DROP TABLE product");
+    migration
+}
+
+fn seed_product() -> Migration {
+    let mut migration = Migration::code(2, "seed_product", "v1", |db: &Database| {
+        try!(Query::insert().into_table("product").set("name", &"Books").execute(db));
+        Ok(())
+    });
+    migration.down_code(|db: &Database| {
+        try!(Query::delete().from_table("product").execute(db));
+        Ok(())
+    });
+    migration
+}
+
+fn has_table(db: &Database, table: &str) -> bool {
+    let sql = format!("SELECT name FROM sqlite_master WHERE type = 'table' AND name = '{}'", table);
+    !db.execute_sql_with_return(&sql, &[]).unwrap().is_empty()
+}
+
+fn count_products(db: &Database) -> usize {
+    Query::select_all().from_table("product").retrieve(db).unwrap().dao.len()
+}
+
+#[test]
+fn test_up_and_down() {
+    let db = connect();
+    let mut migrator = Migrator::new();
+    migrator.add(seed_product()).add(create_product());
+
+    assert_eq!(migrator.up(db.as_ref()).unwrap(), vec![1, 2]);
+    assert_eq!(count_products(db.as_ref()), 1);
+    assert!(migrator.pending(db.as_ref()).unwrap().is_empty());
+    assert_eq!(migrator.up(db.as_ref()).unwrap(), Vec::<u64>::new());
+
+    assert_eq!(migrator.down(db.as_ref()).unwrap(), Some(2));
+    assert_eq!(count_products(db.as_ref()), 0);
+    assert_eq!(migrator.down_to(db.as_ref(), 0).unwrap(), vec![1]);
+    assert!(migrator.applied(db.as_ref()).unwrap().is_empty());
+    assert!(!has_table(db.as_ref(), "product"));
+}
+
+#[test]
+fn test_edited_and_out_of_order() {
+    let db = connect();
+    let mut migrator = Migrator::new();
+    migrator.add(create_product());
+    migrator.up(db.as_ref()).unwrap();
+
+    let mut edited = Migrator::new();
+    edited.add(Migration::sql(1, "create_product", "CREATE TABLE product(title text)"));
+    assert!(edited.up(db.as_ref()).is_err());
+
+    let mut later = Migrator::new();
+    later.add(create_product()).add(Migration::sql(3, "create_cart", "CREATE TABLE cart(id int)"));
+    assert_eq!(later.up(db.as_ref()).unwrap(), vec![3]);
+
+    let mut older = Migrator::new();
+    older.add(create_product())
+         .add(Migration::sql(2, "create_category", "CREATE TABLE category(id int)"))
+         .add(Migration::sql(3, "create_cart", "CREATE TABLE cart(id int)"));
+    assert!(older.up(db.as_ref()).is_err());
+}
+
+#[test]
+fn test_failed_migration_is_rolled_back() {
+    let db = connect();
+    let mut migrator = Migrator::new();
+    migrator.add(Migration::sql(1, "broken", "CREATE TABLE product(name text); INSERT INTO missing VALUES (1)"));
+    assert!(migrator.up(db.as_ref()).is_err());
+    assert!(migrator.applied(db.as_ref()).unwrap().is_empty());
+    assert!(!has_table(db.as_ref(), "product"));
+}
+
+#[test]
+fn test_edited_code_revision() {
+    let db = connect();
+    let mut migrator = Migrator::new();
+    migrator.add(create_product()).add(seed_product());
+    migrator.up(db.as_ref()).unwrap();
+
+    let mut edited = Migrator::new();
+    edited.add(create_product())
+          .add(Migration::code(2, "seed_product", "v2", |_: &Database| Ok(())));
+    assert!(edited.pending(db.as_ref()).is_err());
+}
+
+#[test]
+fn test_migration_is_recorded_before_it_runs() {
+    let db = connect();
+    let mut migrator = Migrator::new();
+    migrator.add(Migration::code(1, "check_record", "v1", |db: &Database| {
+        let recorded = try!(Query::select_all()
+                                .from_table(MIGRATIONS_TABLE)
+                                .filter_eq("version", &1i64)
+                                .retrieve(db));
+        assert_eq!(recorded.dao.len(), 1);
+        Ok(())
+    }));
+    assert_eq!(migrator.up(db.as_ref()).unwrap(), vec![1]);
+}
+
+#[test]
+fn test_from_dir() {
+    let nanos = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().subsec_nanos();
+    let dir = env::temp_dir().join(format!("rustorm_test_migrations_{}_{}", process::id(), nanos));
+    fs::create_dir(&dir).unwrap();
+    let files = [("0002_seed.sql", "INSERT INTO product(name) VALUES ('Books')"),
+                 ("0001_create_product.up.sql", CREATE_PRODUCT),
+                 ("0001_create_product.down.sql", "DROP TABLE product"),
+                 ("README.md", "not a migration")];
+    for &(name, sql) in &files {
+        File::create(dir.join(name)).unwrap().write_all(sql.as_bytes()).unwrap();
+    }
+    let migrator = Migrator::from_dir(&dir).unwrap();
+    let versions: Vec<u64> = migrator.migrations().iter().map(|m| m.version).collect();
+    assert_eq!(versions, vec![1, 2]);
+    assert!(migrator.migrations()[0].is_reversible());
+    assert!(!migrator.migrations()[1].is_reversible());
+
+    let db = connect();
+    assert_eq!(migrator.up(db.as_ref()).unwrap(), vec![1, 2]);
+    assert_eq!(count_products(db.as_ref()), 1);
+    fs::remove_dir_all(&dir).unwrap();
+}
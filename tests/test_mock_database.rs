@@ -0,0 +1,100 @@
+extern crate rustorm;
+
+use rustorm::query::Query;
+use rustorm::dao::{Dao, IsDao, Value};
+use rustorm::database::{Database, DbError};
+use rustorm::em::EntityManager;
+use rustorm::table::{IsTable, Table};
+use rustorm::platform::MockDatabase;
+use rustorm::platform::mock::MockResponse;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Category {
+    pub category_id: i32,
+    pub name: String,
+}
+
+impl IsDao for Category {
+    fn from_dao(dao: &Dao) -> Self {
+        Category {
+            category_id: dao.get("category_id"),
+            name: dao.get("name"),
+        }
+    }
+
+    fn to_dao(&self) -> Dao {
+        let mut dao = Dao::new();
+        dao.set("category_id", &self.category_id);
+        dao.set("name", &self.name);
+        dao
+    }
+}
+
+impl IsTable for Category {
+    fn table() -> Table {
+        Table {
+            schema: Some("bazaar".to_owned()),
+            name: "category".to_owned(),
+            ..Default::default()
+        }
+    }
+}
+
+fn books() -> Category {
+    Category {
+        category_id: 1,
+        name: "Books".to_owned(),
+    }
+}
+
+#[test]
+fn test_entity_manager_with_scripted_rows() {
+    let mut db = MockDatabase::new();
+    db.expect_rows("SELECT * FROM \"bazaar\".\"category\"",
+                   vec![books().to_dao()]);
+    let categories: Vec<Category> = EntityManager::new(&db).get_all().unwrap();
+    assert_eq!(categories, vec![books()]);
+    db.assert_executed("SELECT * FROM \"bazaar\".\"category\"", &[]);
+    db.assert_expectations_met();
+}
+
+#[test]
+fn test_query_parameters_are_recorded() {
+    let db = MockDatabase::new();
+    let deleted = Query::delete()
+                      .from_table("bazaar.category")
+                      .filter_eq("name", &"Books")
+                      .execute(&db)
+                      .unwrap();
+    assert_eq!(deleted, 0);
+    db.assert_executed("DELETE FROM \"bazaar\".\"category\" WHERE \"name\" = $1",
+                       &[Value::String("Books".to_owned())]);
+    assert_eq!(db.executed().len(), 1);
+}
+
+#[test]
+fn test_scripted_error() {
+    let mut db = MockDatabase::new();
+    db.expect_any(MockResponse::Error(DbError::new("connection lost")));
+    let result: Result<Vec<Category>, DbError> = EntityManager::new(&db).get_all();
+    assert!(result.is_err());
+    db.assert_expectations_met();
+}
+
+#[test]
+fn test_transaction_statements() {
+    let mut db = MockDatabase::new();
+    db.expect_affected("UPDATE \"bazaar\".\"category\" SET \"name\" = $1", 3);
+    db.begin().unwrap();
+    assert!(db.is_transacted());
+    let updated = Query::update()
+                      .from_table("bazaar.category")
+                      .set("name", &"Books")
+                      .execute(&db)
+                      .unwrap();
+    assert_eq!(updated, 3);
+    db.commit().unwrap();
+    let executed: Vec<String> = db.executed().iter().map(|s| s.sql.to_owned()).collect();
+    assert_eq!(executed[0], "BEGIN");
+    assert_eq!(executed[2], "COMMIT");
+}
@@ -0,0 +1,129 @@
+extern crate rustorm;
+
+use rustorm::query::Operand;
+use rustorm::dao::{Type, Value};
+use rustorm::database::DatabaseDDL;
+use rustorm::table::{Table, Column, Foreign, Index};
+use rustorm::platform::Postgres;
+
+fn column(name: &str, data_type: Type) -> Column {
+    Column {
+        name: name.to_owned(),
+        data_type: data_type,
+        db_data_type: String::new(),
+        is_primary: false,
+        is_unique: false,
+        default: None,
+        comment: None,
+        not_null: false,
+        foreign: None,
+        is_inherited: false,
+    }
+}
+
+fn product() -> Table {
+    let mut product_id = column("product_id", Type::Uuid);
+    product_id.is_primary = true;
+    product_id.not_null = true;
+    product_id.default = Some(Operand::Sql("uuid_generate_v4()".to_owned()));
+    let mut name = column("name", Type::String);
+    name.not_null = true;
+    name.is_unique = true;
+    name.comment = Some("the product's display name".to_owned());
+    let mut active = column("active", Type::Bool);
+    active.default = Some(Operand::Value(Value::Bool(true)));
+    let mut status = column("status", Type::String);
+    status.default = Some(Operand::Value(Value::String("it's new".to_owned())));
+    let mut owner_id = column("owner_id", Type::Uuid);
+    owner_id.foreign = Some(Foreign {
+        schema: Some("bazaar".to_owned()),
+        table: "users".to_owned(),
+        column: "user_id".to_owned(),
+        name: None,
+    });
+    let mut created = column("created", Type::DateTime);
+    created.is_inherited = true;
+    Table {
+        schema: Some("bazaar".to_owned()),
+        name: "product".to_owned(),
+        parent_table: Some("record".to_owned()),
+        comment: Some("items for sale".to_owned()),
+        columns: vec![product_id, name, active, status, owner_id, created],
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_create_table() {
+    let pg = Postgres::new();
+    let frag = pg.build_create_table(&product()).unwrap();
+
+    let expected = "
+CREATE TABLE \"bazaar\".\"product\" (
+    \"product_id\" uuid NOT NULL DEFAULT uuid_generate_v4(),
+    \"name\" character varying NOT NULL UNIQUE,
+    \"active\" boolean DEFAULT 'true',
+    \"status\" character varying DEFAULT 'it''s new',
+    \"owner_id\" uuid,
+    PRIMARY KEY (\"product_id\"),
+    FOREIGN KEY (\"owner_id\") REFERENCES \"bazaar\".\"users\" (\"user_id\")
+) INHERITS (\"bazaar\".\"record\")".to_string();
+    println!("actual:   {{\n{}}} [{}]", frag.sql, frag.sql.len());
+    println!("expected: {{{}}} [{}]", expected, expected.len());
+    assert_eq!(frag.sql.trim(), expected.trim());
+}
+
+#[test]
+fn test_comments() {
+    let pg = Postgres::new();
+    let comments: Vec<String> = pg.build_comments(&product())
+                                  .unwrap()
+                                  .into_iter()
+                                  .map(|frag| frag.sql)
+                                  .collect();
+    assert_eq!(comments,
+               vec!["COMMENT ON TABLE \"bazaar\".\"product\" IS 'items for sale'".to_owned(),
+                    "COMMENT ON COLUMN \"bazaar\".\"product\".\"name\" IS 'the product''s display name'"
+                        .to_owned()]);
+}
+
+#[test]
+fn test_alter_statements() {
+    let pg = Postgres::new();
+    let table = product();
+    assert_eq!(pg.build_create_schema("bazaar").unwrap().sql,
+               "CREATE SCHEMA IF NOT EXISTS \"bazaar\"");
+    assert_eq!(pg.build_rename_table(&table, "item").unwrap().sql,
+               "ALTER TABLE \"bazaar\".\"product\" RENAME TO \"item\"");
+    assert_eq!(pg.build_drop_table(&table).unwrap().sql,
+               "DROP TABLE \"bazaar\".\"product\"");
+    assert_eq!(pg.build_primary_constraint(&table).unwrap().unwrap().sql,
+               "ALTER TABLE \"bazaar\".\"product\" ADD PRIMARY KEY (\"product_id\")");
+    let foreign = pg.build_foreign_constraints(&table).unwrap();
+    assert_eq!(foreign.len(), 1);
+    assert_eq!(foreign[0].sql,
+               "ALTER TABLE \"bazaar\".\"product\" ADD FOREIGN KEY (\"owner_id\") REFERENCES \
+                \"bazaar\".\"users\" (\"user_id\")");
+}
+
+#[test]
+fn test_create_index() {
+    let pg = Postgres::new();
+    let table = product();
+    let mut active_name = Index::new("product_active_name_idx", &["name", "owner_id"]);
+    active_name.method = Some("btree".to_owned());
+    active_name.predicate = Some("active = true".to_owned());
+    assert_eq!(pg.build_create_index(&table, &active_name).unwrap().sql,
+               "CREATE INDEX \"product_active_name_idx\" ON \"bazaar\".\"product\" USING btree \
+                (\"name\", \"owner_id\") WHERE active = true");
+
+    let mut lower_name = Index::new("product_lower_name_key", &[]);
+    lower_name.is_unique = true;
+    lower_name.expression = Some("lower(name::text)".to_owned());
+    assert_eq!(pg.build_create_index(&table, &lower_name).unwrap().sql,
+               "CREATE UNIQUE INDEX \"product_lower_name_key\" ON \"bazaar\".\"product\" \
+                (lower(name::text))");
+    assert_eq!(pg.build_drop_index(&table, &lower_name).unwrap().sql,
+               "DROP INDEX \"bazaar\".\"product_lower_name_key\"");
+    assert!(pg.build_create_index(&table, &Index::new("empty_idx", &[])).is_err());
+}
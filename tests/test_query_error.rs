@@ -0,0 +1,69 @@
+extern crate rustorm;
+
+use rustorm::query::Query;
+use rustorm::query::Error;
+use rustorm::query::{Equality, Limit, Range};
+use rustorm::platform::Postgres;
+
+#[test]
+fn test_insert_without_table() {
+    let pg = Postgres::new();
+    let mut query = Query::insert();
+    query.set("name", &"GTX660 Ti videocard");
+    match query.build(&pg) {
+        Err(Error::NoTableSpecified(_)) => (),
+        Err(e) => panic!("unexpected error {}", e),
+        Ok(frag) => panic!("expecting an error, got {}", frag.sql),
+    }
+}
+
+#[test]
+fn test_select_without_table() {
+    let pg = Postgres::new();
+    let mut query = Query::select_all();
+    query.filter_eq("name", &"Electronic");
+    assert!(query.build(&pg).is_err());
+}
+
+#[test]
+fn test_dotted_names_do_not_panic() {
+    let pg = Postgres::new();
+    let mut query = Query::select();
+    query.column("bazaar.product.name")
+         .from_table("bazaar.product");
+    let frag = query.build(&pg).unwrap();
+
+    let expected = "
+   SELECT \"name\"
+     FROM \"bazaar\".\"product\"".to_string();
+    println!("actual:   {{\n{}}} [{}]", frag.sql, frag.sql.len());
+    println!("expected: {{{}}} [{}]", expected, expected.len());
+    assert_eq!(frag.sql.trim(), expected.trim());
+}
+
+#[test]
+fn test_and_without_filter() {
+    let mut query = Query::select_all();
+    match query.and("name", Equality::EQ, &"Mouse") {
+        Err(Error::NoFilterSpecified(_)) => (),
+        _ => panic!("expecting an error"),
+    }
+    query.filter_eq("name", &"Mouse");
+    assert!(query.or("name", Equality::EQ, &"Cable").is_ok());
+}
+
+#[test]
+fn test_page_with_limit_and_offset() {
+    let mut query = Query::select_all();
+    query.range = Some(Range::Limit(Limit {
+        limit: Some(10),
+        offset: Some(20),
+    }));
+    match query.set_page(2) {
+        Err(Error::MixedRange(_)) => (),
+        _ => panic!("expecting an error"),
+    }
+    assert!(query.set_page_size(5).is_err());
+    query.limit(5);
+    assert_eq!(query.get_limit(), Some(Limit { limit: Some(5), offset: Some(20) }));
+}
@@ -0,0 +1,74 @@
+#![cfg(feature = "sqlite")]
+extern crate rustorm;
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use rustorm::query::Query;
+use rustorm::pool::ManagedPool;
+use rustorm::logger::{QueryLog, QueryLogger, QueryEvent};
+
+/// what the logger received for each statement
+#[derive(Debug)]
+struct Logged {
+    sql: String,
+    has_params: bool,
+    rows: Option<usize>,
+    failed: bool,
+    slow: bool,
+}
+
+struct CollectingLogger {
+    logged: Mutex<Vec<Logged>>,
+}
+
+impl QueryLogger for CollectingLogger {
+    fn log_query(&self, event: &QueryEvent) {
+        self.logged.lock().unwrap().push(Logged {
+            sql: event.sql.to_owned(),
+            has_params: event.params.is_some(),
+            rows: event.rows,
+            failed: event.error.is_some(),
+            slow: event.slow,
+        });
+    }
+}
+
+#[test]
+fn test_logger_receives_statements() {
+    let logger = Arc::new(CollectingLogger { logged: Mutex::new(vec![]) });
+    let mut query_log = QueryLog::new();
+    query_log.redact_params(true)
+             .slow_threshold(Duration::from_secs(0))
+             .logger(logger.clone());
+
+    let pool = ManagedPool::init("sqlite://:memory:", 1).unwrap();
+    let db = pool.connect_with_log(&query_log).unwrap();
+    db.as_ref()
+      .execute_sql("CREATE TABLE category(name text)", &[])
+      .unwrap();
+    Query::insert()
+        .into_table("category")
+        .set("name", &"Books")
+        .execute(db.as_ref())
+        .unwrap();
+    let result = Query::select_all()
+                     .from_table("category")
+                     .retrieve(db.as_ref())
+                     .unwrap();
+    assert_eq!(result.dao.len(), 1);
+    assert!(db.as_ref().execute_sql("SELECT * FROM missing", &[]).is_err());
+
+    let logged = logger.logged.lock().unwrap();
+    println!("logged: {:#?}", *logged);
+    let statement = |keyword: &str| {
+        logged.iter()
+              .find(|l| l.sql.trim().starts_with(keyword))
+              .expect(keyword)
+    };
+    assert_eq!(statement("INSERT").rows, Some(1));
+    assert_eq!(statement("SELECT *").rows, Some(1));
+    assert!(logged.iter().all(|l| !l.has_params && l.slow));
+    let failed = logged.last().unwrap();
+    assert!(failed.failed);
+    assert_eq!(failed.rows, None);
+}
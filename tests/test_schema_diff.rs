@@ -0,0 +1,163 @@
+extern crate rustorm;
+
+use rustorm::query::Operand;
+use rustorm::dao::{Type, Value};
+use rustorm::table::{Table, Column, Foreign, Index};
+use rustorm::diff::SchemaDiff;
+use rustorm::platform::Postgres;
+
+fn column(name: &str, data_type: Type) -> Column {
+    Column {
+        name: name.to_owned(),
+        data_type: data_type,
+        db_data_type: String::new(),
+        is_primary: false,
+        is_unique: false,
+        default: None,
+        comment: None,
+        not_null: false,
+        foreign: None,
+        is_inherited: false,
+    }
+}
+
+fn table(name: &str, columns: Vec<Column>) -> Table {
+    Table {
+        schema: Some("bazaar".to_owned()),
+        name: name.to_owned(),
+        columns: columns,
+        ..Default::default()
+    }
+}
+
+fn current() -> Vec<Table> {
+    let mut product_id = column("product_id", Type::Uuid);
+    product_id.is_primary = true;
+    product_id.not_null = true;
+    let mut owner_id = column("owner_id", Type::Uuid);
+    let mut owner = Foreign::from_str("bazaar.users", "user_id");
+    owner.name = Some("product_owner_fk".to_owned());
+    owner_id.foreign = Some(owner);
+    let mut product = table("product",
+                            vec![product_id,
+                                 column("title", Type::String),
+                                 column("price", Type::F32),
+                                 owner_id,
+                                 column("barcode", Type::String)]);
+    let mut by_id = Index::new("product_id_idx", &["product_id"]);
+    by_id.method = Some("btree".to_owned());
+    product.indexes = vec![Index::new("product_barcode_idx", &["barcode"]), by_id];
+    vec![product, table("users", vec![column("user_id", Type::Uuid)]), table("cart", vec![])]
+}
+
+fn desired() -> Vec<Table> {
+    let mut product_id = column("product_id", Type::Uuid);
+    product_id.is_primary = true;
+    product_id.not_null = true;
+    let mut name = column("name", Type::String);
+    name.not_null = true;
+    let mut price = column("price", Type::F64);
+    price.default = Some(Operand::Sql("0".to_owned()));
+    let mut active = column("active", Type::Bool);
+    active.default = Some(Operand::Sql("true".to_owned()));
+    let mut item = table("item", vec![product_id, name, price, active]);
+    item.indexes = vec![Index::new("item_name_idx", &["name"]), Index::new("product_id_idx", &["product_id"])];
+    let mut category_id = column("category_id", Type::I32);
+    category_id.is_primary = true;
+    vec![item,
+         table("users", vec![column("user_id", Type::Uuid)]),
+         table("category", vec![category_id])]
+}
+
+#[test]
+fn test_postgres_alter_statements() {
+    let mut diff = SchemaDiff::new();
+    diff.rename_table("bazaar.product", "bazaar.item")
+        .rename_column("bazaar.item", "title", "name");
+    let pg = Postgres::new();
+    let changes = diff.diff(&pg, &current(), &desired());
+    let statements: Vec<String> = changes.iter()
+                                         .map(|change| change.build(&pg).unwrap().sql)
+                                         .collect();
+    let expected = vec![
+        "ALTER TABLE \"bazaar\".\"product\" DROP CONSTRAINT \"product_owner_fk\"",
+        "DROP INDEX \"bazaar\".\"product_barcode_idx\"",
+        "ALTER TABLE \"bazaar\".\"product\" RENAME TO \"item\"",
+        "ALTER TABLE \"bazaar\".\"item\" RENAME COLUMN \"title\" TO \"name\"",
+        "CREATE TABLE \"bazaar\".\"category\" (\n    \"category_id\" integer,\n    PRIMARY KEY (\"category_id\")\n)",
+        "ALTER TABLE \"bazaar\".\"item\" ALTER COLUMN \"name\" SET NOT NULL",
+        "ALTER TABLE \"bazaar\".\"item\" ALTER COLUMN \"price\" TYPE double precision USING \"price\"::double precision",
+        "ALTER TABLE \"bazaar\".\"item\" ALTER COLUMN \"price\" SET DEFAULT 0",
+        "ALTER TABLE \"bazaar\".\"item\" ADD COLUMN \"active\" boolean DEFAULT true",
+        "ALTER TABLE \"bazaar\".\"item\" DROP COLUMN \"owner_id\"",
+        "ALTER TABLE \"bazaar\".\"item\" DROP COLUMN \"barcode\"",
+        "DROP TABLE \"bazaar\".\"cart\"",
+        "CREATE INDEX \"item_name_idx\" ON \"bazaar\".\"item\" (\"name\")",
+    ];
+    println!("statements: {:#?}", statements);
+    assert_eq!(statements, expected);
+}
+
+#[cfg(feature = "mysql")]
+#[test]
+fn test_mysql_modify_column() {
+    use rustorm::platform::Mysql;
+
+    let mut name = column("name", Type::String);
+    name.not_null = true;
+    let changes = SchemaDiff::new().diff(&Mysql::new(),
+                                         &[table("product", vec![column("name", Type::String)])],
+                                         &[table("product", vec![name])]);
+    assert_eq!(changes.len(), 1);
+    assert_eq!(changes[0].build(&Mysql::new()).unwrap().sql,
+               "ALTER TABLE `product` MODIFY COLUMN `name` text NOT NULL");
+}
+
+#[test]
+fn test_no_changes() {
+    assert!(SchemaDiff::new().diff(&Postgres::new(), &current(), &current()).is_empty());
+}
+
+#[test]
+fn test_default_compared_as_written() {
+    let mut introspected = column("status", Type::String);
+    introspected.default = Some(Operand::Sql("'new'".to_owned()));
+    let mut status = column("status", Type::String);
+    status.default = Some(Operand::Value(Value::String("new".to_owned())));
+    let changes = SchemaDiff::new().diff(&Postgres::new(),
+                                         &[table("product", vec![introspected])],
+                                         &[table("product", vec![status])]);
+    assert!(changes.is_empty());
+}
+
+#[test]
+fn test_tables_ordered_by_foreign_keys() {
+    let mut cart_owner = column("owner_id", Type::Uuid);
+    cart_owner.foreign = Some(Foreign::from_str("bazaar.owner", "owner_id"));
+    let cart = table("cart", vec![cart_owner]);
+    let owner = table("owner", vec![column("owner_id", Type::Uuid)]);
+    let pg = Postgres::new();
+    let statements = |changes: Vec<_>| -> Vec<String> {
+        changes.iter()
+               .map(|change: &rustorm::diff::SchemaChange| change.build(&pg).unwrap().sql)
+               .map(|sql| sql.lines().next().unwrap().to_owned())
+               .collect()
+    };
+    let created = SchemaDiff::new().diff(&pg, &[], &[cart.clone(), owner.clone()]);
+    assert_eq!(statements(created),
+               vec!["CREATE TABLE \"bazaar\".\"owner\" (", "CREATE TABLE \"bazaar\".\"cart\" ("]);
+    let dropped = SchemaDiff::new().diff(&pg, &[owner, cart], &[]);
+    assert_eq!(statements(dropped),
+               vec!["DROP TABLE \"bazaar\".\"cart\"", "DROP TABLE \"bazaar\".\"owner\""]);
+}
+
+#[test]
+fn test_unnamed_foreign_key_is_not_dropped() {
+    let mut owner_id = column("owner_id", Type::Uuid);
+    owner_id.foreign = Some(Foreign::from_str("bazaar.users", "user_id"));
+    let changes = SchemaDiff::new().diff(&Postgres::new(),
+                                         &[table("product", vec![owner_id])],
+                                         &[table("product", vec![column("owner_id", Type::Uuid)])]);
+    assert_eq!(changes.len(), 1);
+    assert!(changes[0].build(&Postgres::new()).is_err());
+}
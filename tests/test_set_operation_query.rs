@@ -0,0 +1,101 @@
+extern crate rustorm;
+
+use rustorm::query::Query;
+use rustorm::platform::Postgres;
+use rustorm::database::Database;
+use rustorm::dialect::{Dialect, Version};
+
+#[test]
+fn test_union_query() {
+    let pg = Postgres::new();
+
+    let mut archived = Query::select();
+    archived.columns(vec!["product_id", "name"])
+            .from_table("archive.product")
+            .filter_eq("is_active", &true);
+
+    let mut query = Query::select();
+    query.columns(vec!["product_id", "name"])
+         .from_table("bazaar.product")
+         .filter_eq("is_active", &true)
+         .union(archived)
+         .asc("name")
+         .limit(10);
+    let frag = query.build(&pg).unwrap();
+
+    let expected = "
+(
+   SELECT \"product_id\", \"name\"
+     FROM \"bazaar\".\"product\"
+    WHERE \"is_active\" = $1 )
+    UNION (
+   SELECT \"product_id\", \"name\"
+     FROM \"archive\".\"product\"
+    WHERE \"is_active\" = $2 )
+ ORDER BY \"name\" ASC
+    LIMIT 10".to_string();
+    println!("actual:   {{\n{}}} [{}]", frag.sql, frag.sql.len());
+    println!("expected: {{{}}} [{}]", expected, expected.len());
+    assert_eq!(frag.sql.trim(), expected.trim());
+    assert_eq!(frag.params.len(), 2);
+}
+
+#[test]
+fn test_except_query() {
+    let pg = Postgres::new();
+
+    let mut ordered = Query::select();
+    ordered.column("product_id")
+           .from_table("bazaar.order_line");
+
+    let mut query = Query::select();
+    query.column("product_id")
+         .from_table("bazaar.product")
+         .except(ordered);
+    let frag = query.build(&pg).unwrap();
+
+    let expected = "
+(
+   SELECT \"product_id\"
+     FROM \"bazaar\".\"product\")
+   EXCEPT (
+   SELECT \"product_id\"
+     FROM \"bazaar\".\"order_line\")".to_string();
+    println!("actual:   {{\n{}}} [{}]", frag.sql, frag.sql.len());
+    println!("expected: {{{}}} [{}]", expected, expected.len());
+    assert_eq!(frag.sql.trim(), expected.trim());
+}
+
+#[test]
+fn test_intersect_support() {
+    let mut paid = Query::select();
+    paid.column("product_id")
+        .from_table("order_line");
+    let mut query = Query::select();
+    query.column("product_id")
+         .from_table("product")
+         .intersect(paid);
+
+    let mut mysql = Dialect::mysql();
+    mysql.version(Version::new(8, 0, 30));
+    assert!(mysql.renderer().check_query_support(&query).is_err());
+    mysql.version(Version::new(8, 0, 31));
+    assert!(mysql.renderer().check_query_support(&query).is_ok());
+    assert!(Dialect::sqlite().renderer().check_query_support(&query).is_ok());
+}
+
+#[test]
+fn test_limited_operand_without_parentheses() {
+    let mut latest = Query::select();
+    latest.column("product_id")
+          .from_table("order_line")
+          .desc("created")
+          .limit(10);
+    let mut query = Query::select();
+    query.column("product_id")
+         .from_table("product")
+         .union(latest);
+
+    assert!(Dialect::sqlite().renderer().check_query_support(&query).is_err());
+    assert!(Dialect::postgres().renderer().check_query_support(&query).is_ok());
+}
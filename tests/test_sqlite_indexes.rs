@@ -0,0 +1,52 @@
+#![cfg(feature = "sqlite")]
+extern crate rustorm;
+
+use rustorm::pool::ManagedPool;
+use rustorm::table::Index;
+
+#[test]
+fn test_index_round_trip() {
+    let pool = ManagedPool::init("sqlite://:memory:", 1).unwrap();
+    let db = pool.connect().unwrap();
+    db.as_ref()
+      .execute_sql("CREATE TABLE product(
+                        product_id integer PRIMARY KEY,
+                        sku text UNIQUE,
+                        name text,
+                        active boolean)",
+                   &[])
+      .unwrap();
+    let table = db.as_dev().get_table_metadata("", "product", false);
+    assert!(table.indexes.is_empty());
+
+    let mut by_name = Index::new("idx_product_name", &["name", "active"]);
+    by_name.predicate = Some("active = 1".to_owned());
+    let mut by_lower_name = Index::new("idx_product_lower_name", &[]);
+    by_lower_name.is_unique = true;
+    by_lower_name.expression = Some("lower(name)".to_owned());
+    db.as_ddl().create_index(&table, &by_name).unwrap();
+    db.as_ddl().create_index(&table, &by_lower_name).unwrap();
+
+    let mut indexes = db.as_dev().get_table_metadata("", "product", false).indexes;
+    indexes.sort_by(|a, b| a.name.cmp(&b.name));
+    assert_eq!(indexes, vec![by_lower_name, by_name.clone()]);
+
+    db.as_ddl().drop_index(&table, &by_name).unwrap();
+    let indexes = db.as_dev().get_table_metadata("", "product", false).indexes;
+    assert_eq!(indexes.len(), 1);
+}
+
+#[test]
+fn test_reserved_names_are_quoted() {
+    let pool = ManagedPool::init("sqlite://:memory:", 1).unwrap();
+    let db = pool.connect().unwrap();
+    db.as_ref()
+      .execute_sql("CREATE TABLE \"order\"(\"group\" text)", &[])
+      .unwrap();
+    db.as_ref()
+      .execute_sql("CREATE INDEX \"index\" ON \"order\"(\"group\")", &[])
+      .unwrap();
+    let table = db.as_dev().get_table_metadata("", "order", false);
+    assert_eq!(table.columns.len(), 1);
+    assert_eq!(table.indexes, vec![Index::new("index", &["group"])]);
+}
@@ -0,0 +1,114 @@
+#![cfg(feature = "sqlite")]
+extern crate rustorm;
+extern crate uuid;
+extern crate chrono;
+
+use uuid::Uuid;
+use chrono::naive::date::NaiveDate;
+
+use rustorm::query::Query;
+use rustorm::dao::Value;
+use rustorm::pool::ManagedPool;
+
+#[test]
+fn test_sqlite_type_round_trip() {
+    let pool = ManagedPool::init("sqlite://:memory:", 1).unwrap();
+    let db = pool.connect().unwrap();
+    db.as_ref()
+      .execute_sql("CREATE TABLE product(
+                        product_id uuid PRIMARY KEY,
+                        name text,
+                        price real,
+                        stocks integer,
+                        is_active boolean,
+                        photo blob,
+                        created timestamp,
+                        description text)",
+                   &[])
+      .unwrap();
+
+    let product_id = Uuid::new_v4();
+    let created = NaiveDate::from_ymd(2016, 1, 25).and_hms(13, 45, 0);
+    Query::insert()
+        .into_table("product")
+        .set("product_id", &product_id)
+        .set("name", &"GTX660 Ti videocard")
+        .set("price", &250.5f64)
+        .set("stocks", &12i64)
+        .set("is_active", &true)
+        .set_value("photo", &Value::VecU8(vec![1, 2, 3]))
+        .set_value("created", &Value::NaiveDateTime(created))
+        .set_value("description", &Value::None(rustorm::dao::Type::String))
+        .execute(db.as_ref())
+        .unwrap();
+
+    let dao = Query::select_all()
+                  .from_table("product")
+                  .retrieve_one(db.as_ref())
+                  .unwrap()
+                  .unwrap();
+    assert_eq!(dao.get_value("product_id"), Value::Uuid(product_id));
+    assert_eq!(dao.get_value("name"), Value::String("GTX660 Ti videocard".to_owned()));
+    assert_eq!(dao.get_value("price"), Value::F64(250.5));
+    assert_eq!(dao.get_value("stocks"), Value::I64(12));
+    assert_eq!(dao.get_value("is_active"), Value::Bool(true));
+    assert_eq!(dao.get_value("photo"), Value::VecU8(vec![1, 2, 3]));
+    assert_eq!(dao.get_value("created"), Value::NaiveDateTime(created));
+    assert_eq!(dao.get_opt::<String>("description"), None);
+}
+
+#[test]
+fn test_sqlite_unique_violation() {
+    use rustorm::database::ErrorKind;
+
+    let pool = ManagedPool::init("sqlite://:memory:", 1).unwrap();
+    let db = pool.connect().unwrap();
+    db.as_ref()
+      .execute_sql("CREATE TABLE category(name text UNIQUE)", &[])
+      .unwrap();
+    let mut insert = Query::insert();
+    insert.into_table("category")
+          .set("name", &"Electronic");
+    insert.execute(db.as_ref()).unwrap();
+    let err = insert.execute(db.as_ref()).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::UniqueViolation);
+    let detail = err.detail();
+    assert_eq!(detail.table, Some("category".to_owned()));
+    assert_eq!(detail.column, Some("name".to_owned()));
+}
+
+#[test]
+fn test_declared_types_by_position() {
+    let pool = ManagedPool::init("sqlite://:memory:", 1).unwrap();
+    let db = pool.connect().unwrap();
+    let sql = ["CREATE TABLE category(category_id integer PRIMARY KEY, flag integer)",
+               "CREATE TABLE product(product_id integer PRIMARY KEY, category_id integer, flag boolean)",
+               "INSERT INTO category VALUES (1, 5)",
+               "INSERT INTO product VALUES (1, 1, 1)"];
+    for statement in &sql {
+        db.as_ref().execute_sql(statement, &[]).unwrap();
+    }
+    let dao = Query::select()
+                  .column("product.flag")
+                  .column("category.flag")
+                  .from_table("product")
+                  .inner_join_table("category", "product.category_id", "category.category_id")
+                  .retrieve_one(db.as_ref())
+                  .unwrap()
+                  .unwrap();
+    // the conflicting columns are renamed, each is read as declared in its own table
+    assert_eq!(dao.get_value("product.flag"), Value::Bool(true));
+    assert_eq!(dao.get_value("category.flag"), Value::I64(5));
+}
+
+#[test]
+fn test_integer_out_of_range() {
+    let pool = ManagedPool::init("sqlite://:memory:", 1).unwrap();
+    let db = pool.connect().unwrap();
+    db.as_ref().execute_sql("CREATE TABLE stock(quantity smallint)", &[]).unwrap();
+    db.as_ref().execute_sql("INSERT INTO stock VALUES (40000)", &[]).unwrap();
+    let result = Query::select_all()
+                     .from_table("stock")
+                     .retrieve(db.as_ref());
+    assert!(result.is_err());
+}
@@ -0,0 +1,160 @@
+#![cfg(feature = "sqlite")]
+extern crate rustorm;
+
+use rustorm::query::Query;
+use rustorm::dao::{Dao, IsDao, Type, Value};
+use rustorm::pool::ManagedPool;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Category {
+    pub name: String,
+}
+
+impl IsDao for Category {
+    fn from_dao(dao: &Dao) -> Self {
+        Category { name: dao.get("name") }
+    }
+
+    fn to_dao(&self) -> Dao {
+        let mut dao = Dao::new();
+        dao.set("name", &self.name);
+        dao
+    }
+}
+
+#[test]
+fn test_stream_in_batches() {
+    let pool = ManagedPool::init("sqlite://:memory:", 1).unwrap();
+    let db = pool.connect().unwrap();
+    db.as_ref()
+      .execute_sql("CREATE TABLE category(name text)", &[])
+      .unwrap();
+    let names = ["Books", "Electronic", "Food", "Music", "Toys"];
+    for name in &names {
+        Query::insert()
+            .into_table("category")
+            .set("name", name)
+            .execute(db.as_ref())
+            .unwrap();
+    }
+
+    let streamed: Vec<String> = Query::select_all()
+                                    .from_table("category")
+                                    .asc("name")
+                                    .stream_batch(db.as_ref(), 2)
+                                    .unwrap()
+                                    .map(|dao| dao.unwrap().get("name"))
+                                    .collect();
+    assert_eq!(streamed, names.iter().map(|n| n.to_string()).collect::<Vec<String>>());
+
+    let limited: Vec<Category> = Query::select_all()
+                                     .from_table("category")
+                                     .asc("name")
+                                     .limit(3)
+                                     .stream_as::<Category>(db.as_ref())
+                                     .unwrap()
+                                     .map(|c| c.unwrap())
+                                     .collect();
+    assert_eq!(limited.len(), 3);
+    assert_eq!(limited[2], Category { name: "Food".to_owned() });
+}
+
+#[test]
+fn test_unordered_stream_is_refused() {
+    let pool = ManagedPool::init("sqlite://:memory:", 1).unwrap();
+    let db = pool.connect().unwrap();
+    db.as_ref()
+      .execute_sql("CREATE TABLE category(name text)", &[])
+      .unwrap();
+    let stream = Query::select_all()
+                     .from_table("category")
+                     .stream(db.as_ref());
+    assert!(stream.is_err());
+}
+
+#[test]
+fn test_stream_after_the_last_keys() {
+    let pool = ManagedPool::init("sqlite://:memory:", 1).unwrap();
+    let db = pool.connect().unwrap();
+    db.as_ref()
+      .execute_sql("CREATE TABLE product(category text, name text)", &[])
+      .unwrap();
+    let products = [(None, "Cable"), (Some("Books"), "Atlas"), (Some("Books"), "Novel"),
+                    (Some("Music"), "Jazz"), (None, "Mouse"), (Some("Books"), "Comics")];
+    for &(category, name) in &products {
+        db.as_ref()
+          .execute_sql("INSERT INTO product(category, name) VALUES (?, ?)",
+                       &[category.map(|c| Value::String(c.to_owned())).unwrap_or(Value::None(Type::String)),
+                         Value::String(name.to_owned())])
+          .unwrap();
+    }
+    // the NULLs sort last in descending order
+    let expected = vec!["Jazz", "Novel", "Comics", "Atlas", "Mouse", "Cable"];
+    for batch_size in 1..4 {
+        let streamed: Vec<String> = Query::select_all()
+                                        .from_table("product")
+                                        .desc("category")
+                                        .desc("name")
+                                        .stream_batch(db.as_ref(), batch_size)
+                                        .unwrap()
+                                        .map(|dao| dao.unwrap().get("name"))
+                                        .collect();
+        assert_eq!(streamed, expected);
+    }
+    let streamed: Vec<String> = Query::select_all()
+                                    .from_table("product")
+                                    .asc("category")
+                                    .asc("name")
+                                    .stream_batch(db.as_ref(), 2)
+                                    .unwrap()
+                                    .map(|dao| dao.unwrap().get("name"))
+                                    .collect();
+    // and first in ascending order
+    assert_eq!(streamed, vec!["Cable", "Mouse", "Atlas", "Comics", "Novel", "Jazz"]);
+}
+
+#[test]
+fn test_stream_cast_error() {
+    let pool = ManagedPool::init("sqlite://:memory:", 1).unwrap();
+    let db = pool.connect().unwrap();
+    db.as_ref()
+      .execute_sql("CREATE TABLE category(id integer)", &[])
+      .unwrap();
+    db.as_ref().execute_sql("INSERT INTO category VALUES (1)", &[]).unwrap();
+    let mut stream = Query::select_all()
+                         .from_table("category")
+                         .asc("id")
+                         .stream_as::<Category>(db.as_ref())
+                         .unwrap();
+    assert!(stream.next().unwrap().is_err());
+}
+
+#[test]
+fn test_stream_combined_query() {
+    let pool = ManagedPool::init("sqlite://:memory:", 1).unwrap();
+    let db = pool.connect().unwrap();
+    db.as_ref()
+      .execute_sql("CREATE TABLE category(name text)", &[])
+      .unwrap();
+    db.as_ref()
+      .execute_sql("CREATE TABLE archived_category(name text)", &[])
+      .unwrap();
+    for name in &["Books", "Food", "Toys"] {
+        Query::insert().into_table("category").set("name", name).execute(db.as_ref()).unwrap();
+    }
+    for name in &["Electronic", "Music"] {
+        Query::insert().into_table("archived_category").set("name", name).execute(db.as_ref()).unwrap();
+    }
+    let mut archived = Query::select();
+    archived.column("name").from_table("archived_category");
+    let streamed: Vec<String> = Query::select()
+                                    .column("name")
+                                    .from_table("category")
+                                    .union(archived)
+                                    .asc("name")
+                                    .stream_batch(db.as_ref(), 2)
+                                    .unwrap()
+                                    .map(|dao| dao.unwrap().get("name"))
+                                    .collect();
+    assert_eq!(streamed, vec!["Books", "Electronic", "Food", "Music", "Toys"]);
+}
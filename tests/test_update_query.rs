@@ -0,0 +1,165 @@
+extern crate rustorm;
+
+use rustorm::query::Query;
+use rustorm::platform::Postgres;
+
+#[test]
+fn test_update_query() {
+    let pg = Postgres::new();
+    let mut query = Query::update();
+    query.from_table("bazaar.product")
+         .set("name", &"GTX660 Ti videocard")
+         .set("description", &"Graphics card")
+         .filter_eq("product_id", &"f7521093-734d-488a-9f60-fc9f11f7e750")
+         .return_all();
+
+    let frag = query.build(&pg).unwrap();
+
+    let expected = "
+   UPDATE \"bazaar\".\"product\"
+      SET \"name\" = $1 , \"description\" = $2\x20
+    WHERE \"product_id\" = $3\x20
+RETURNING *".to_string();
+    println!("actual:   {{\n{}}} [{}]", frag.sql, frag.sql.len());
+    println!("expected: {{{}}} [{}]", expected, expected.len());
+    assert_eq!(frag.sql.trim(), expected.trim());
+    assert_eq!(frag.params.len(), 3);
+}
+
+#[cfg(feature = "sqlite")]
+mod sqlite {
+    use rustorm::query::{Query, Operand};
+    use rustorm::query::{Equality, Filter};
+    use rustorm::dao::{Dao, IsDao, Type};
+    use rustorm::table::{Column, IsTable, Table};
+    use rustorm::em::EntityManager;
+    use rustorm::pool::{ManagedPool, Platform};
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct Product {
+        product_id: i32,
+        name: String,
+        is_active: bool,
+        status: String,
+    }
+
+    impl IsDao for Product {
+        fn from_dao(dao: &Dao) -> Self {
+            Product {
+                product_id: dao.get("product_id"),
+                name: dao.get("name"),
+                is_active: dao.get("is_active"),
+                status: dao.get("status"),
+            }
+        }
+
+        fn to_dao(&self) -> Dao {
+            let mut dao = Dao::new();
+            dao.set("product_id", &self.product_id);
+            dao.set("name", &self.name);
+            dao.set("is_active", &self.is_active);
+            dao.set("status", &self.status);
+            dao
+        }
+    }
+
+    impl IsTable for Product {
+        fn table() -> Table {
+            let column = |name: &str, data_type: Type| {
+                Column {
+                    name: name.to_owned(),
+                    data_type: data_type,
+                    db_data_type: String::new(),
+                    is_primary: false,
+                    is_unique: false,
+                    default: None,
+                    comment: None,
+                    not_null: false,
+                    foreign: None,
+                    is_inherited: false,
+                }
+            };
+            let mut product_id = column("product_id", Type::I32);
+            product_id.is_primary = true;
+            let mut status = column("status", Type::String);
+            status.default = Some(Operand::Sql("'new'".to_owned()));
+            Table {
+                name: "product".to_owned(),
+                columns: vec![product_id,
+                              column("name", Type::String),
+                              column("is_active", Type::Bool),
+                              status],
+                ..Default::default()
+            }
+        }
+    }
+
+    fn connect() -> Platform {
+        let pool = ManagedPool::init("sqlite://:memory:", 1).unwrap();
+        let db = pool.connect().unwrap();
+        db.as_ref()
+          .execute_sql("CREATE TABLE product(product_id int PRIMARY KEY, name text, is_active boolean, \
+                        status text DEFAULT 'new')",
+                       &[])
+          .unwrap();
+        db.as_ref()
+          .execute_sql("INSERT INTO product VALUES (1, 'GTX660', 1, 'sold'), (2, 'GTX660 Ti', 0, 'new')",
+                       &[])
+          .unwrap();
+        db
+    }
+
+    #[test]
+    fn test_reselect_the_updated_record() {
+        let db = connect();
+        let mut query = Query::update();
+        query.from_table("product")
+             .set("status", &"sold")
+             .filter_eq("name", &"GTX660 Ti")
+             .filter("is_active", Equality::EQ, &false);
+        let updated = db.as_ref().update(&query).unwrap();
+        assert_eq!(updated.try_get::<i32>("product_id").unwrap(), 2);
+        assert_eq!(updated.try_get::<bool>("is_active").unwrap(), false);
+
+        // the filters would match an other record after the update
+        let mut query = Query::update();
+        query.from_table("product")
+             .set("name", &"GTX660 Ti")
+             .filter_eq("name", &"GTX660");
+        assert!(db.as_ref().update(&query).is_err());
+    }
+
+    #[test]
+    fn test_update_with_filter_reselects_by_primary_key() {
+        let db = connect();
+        let em = EntityManager::new(db.as_ref());
+        let mut dao = Dao::new();
+        dao.set("product_id", &10);
+        dao.set("name", &"GTX660 Ti");
+        let filter = Filter::new("name", Equality::EQ, &"GTX660");
+        let updated: Product = em.update_with_filter(&dao, vec![filter]).unwrap();
+        assert_eq!(updated,
+                   Product {
+                       product_id: 1,
+                       name: "GTX660 Ti".to_owned(),
+                       is_active: true,
+                       status: "sold".to_owned(),
+                   });
+    }
+
+    #[test]
+    fn test_update_ignore_defaulted_columns() {
+        let db = connect();
+        let em = EntityManager::new(db.as_ref());
+        let product = Product {
+            product_id: 1,
+            name: "GTX660 videocard".to_owned(),
+            is_active: false,
+            status: "returned".to_owned(),
+        };
+        let updated: Product = em.update_ignore_defaulted_columns(&product.to_dao()).unwrap();
+        assert_eq!(updated.name, "GTX660 videocard");
+        assert_eq!(updated.is_active, false);
+        assert_eq!(updated.status, "new");
+    }
+}
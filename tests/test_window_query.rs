@@ -0,0 +1,33 @@
+extern crate rustorm;
+
+use rustorm::query::Query;
+use rustorm::query::{Function, Window, FrameBound};
+use rustorm::platform::Postgres;
+
+#[test]
+fn test_window_functions() {
+    let pg = Postgres::new();
+
+    let mut ranking = Window::new(Function::row_number());
+    ranking.partition_by(vec!["category"])
+           .asc("price");
+
+    let mut running_total = Window::new(Function::sum("price"));
+    running_total.asc("created")
+                 .rows_between(FrameBound::UNBOUNDED_PRECEDING, FrameBound::CURRENT_ROW);
+
+    let mut query = Query::select();
+    query.columns(vec!["product_id", "category", "price"])
+         .window(ranking, "rank")
+         .window(running_total, "running_total")
+         .from_table("bazaar.product");
+    let frag = query.build(&pg).unwrap();
+
+    let expected = "
+   SELECT \"product_id\", \"category\", \"price\",\x20
+          ROW_NUMBER() OVER (PARTITION BY \"category\" ORDER BY \"price\" ASC) AS \"rank\", SUM(\"price\") OVER (ORDER BY \"created\" ASC ROWS BETWEEN UNBOUNDED PRECEDING AND CURRENT ROW) AS \"running_total\"
+     FROM \"bazaar\".\"product\"".to_string();
+    println!("actual:   {{\n{}}} [{}]", frag.sql, frag.sql.len());
+    println!("expected: {{{}}} [{}]", expected, expected.len());
+    assert_eq!(frag.sql.trim(), expected.trim());
+}